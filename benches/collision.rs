@@ -0,0 +1,162 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tet_rs::engine::mechanics::{Cell, Field, Stone, StoneKind};
+use tet_rs::rendering::renderer::{Color, Position, Texture, Tile};
+
+// The collision checks `Stone::can_move_*` used before the bitboard: the field is a texture
+// whose gray tiles count as empty and the stone is tested point by point.
+mod texture_collision {
+    use super::*;
+
+    pub struct TextureField {
+        pub pixels: Vec<Vec<Option<Tile>>>,
+    }
+
+    impl TextureField {
+        pub fn from_field(field: &Field) -> Self {
+            let dimensions = field.dimensions();
+            let pixels = (0..dimensions.height).map(|y| {
+                (0..dimensions.width).map(|x| {
+                    let position = Position { x: x as i32, y: y as i32 };
+                    match field.get_cell_at_pos(&position) {
+                        Some(Cell::Empty) | None => Some(Tile::new_background(Color::Gray)),
+                        Some(_) => Some(Tile::new_background(Color::Red)),
+                    }
+                }).collect()
+            }).collect();
+
+            Self { pixels }
+        }
+
+        fn position_free(&self, position: &Position) -> bool {
+            if position.y < 0 || position.x < 0 {
+                return true;
+            }
+
+            match self.pixels.get(position.y as usize).and_then(|row| row.get(position.x as usize)) {
+                Some(Some(tile)) => tile.background == Color::Gray,
+                _ => true,
+            }
+        }
+
+        fn all_positions_free(&self, positions: &[Position]) -> bool {
+            positions.iter().all(|position| self.position_free(position))
+        }
+    }
+
+    /// The texture of the stone without empty rows and columns, built once per stone outside of
+    /// the measured checks.
+    pub fn trimmed_texture(stone: &Stone) -> Texture {
+        let texture = stone.texture(Tile::new_background(Color::Red));
+        let rows: Vec<_> = texture.pixels.into_iter().filter(|row| row.iter().any(Option::is_some)).collect();
+        let first = (0..rows[0].len()).find(|&x| rows.iter().any(|row| row[x].is_some())).unwrap();
+        let last = (0..rows[0].len()).rev().find(|&x| rows.iter().any(|row| row[x].is_some())).unwrap();
+        let pixels: Vec<Vec<_>> = rows.into_iter().map(|row| row[first..=last].to_vec()).collect();
+        let mut trimmed = Texture::new(tet_rs::rendering::renderer::Dimensions { width: last - first + 1, height: pixels.len() });
+        trimmed.pixels = pixels;
+        trimmed
+    }
+
+    pub fn can_move_down(stone: &Stone, texture: &Texture, field: &TextureField) -> bool {
+        let position = Position {
+            x: stone.position.x + stone.shape().min_x,
+            y: stone.position.y + stone.shape().min_y,
+        };
+
+        let not_at_bottom = (position.y + texture.dimensions.height as i32) < 20;
+        let mut bottom_positions = texture.pixels.iter().enumerate().fold(vec![Position::default(); texture.dimensions.width],
+            |mut acc, (row_index, row)| {
+                for (column_index, tile) in row.iter().enumerate() {
+                    if tile.is_some() {
+                        acc[column_index] = Position {
+                            x: position.x + column_index as i32,
+                            y: position.y + row_index as i32,
+                        }
+                    }
+                }
+                acc
+            });
+        bottom_positions.iter_mut().for_each(|position| position.move_down());
+        not_at_bottom && field.all_positions_free(bottom_positions.as_slice())
+    }
+
+    pub fn can_move_left(stone: &Stone, texture: &Texture, field: &TextureField) -> bool {
+        let position = Position {
+            x: stone.position.x + stone.shape().min_x,
+            y: stone.position.y + stone.shape().min_y,
+        };
+
+        let mut positions_to_left: Vec<_> = texture.pixels.iter().enumerate().map(|(row_number, columns)| {
+            let left_most = columns.iter().position(Option::is_some).unwrap() as i32;
+            Position { x: position.x + left_most, y: position.y + row_number as i32 }
+        }).collect();
+        positions_to_left.iter_mut().for_each(|position| position.move_left());
+        position.x > 0 && field.all_positions_free(positions_to_left.as_slice())
+    }
+}
+
+fn stacked_field() -> Field {
+    let mut field = Field::default();
+    for y in 12..20 {
+        for x in 0..10 {
+            if (x * 7 + y * 3) % 5 != 0 {
+                field.set_cell_at_pos(&Position { x, y }, Cell::Garbage);
+            }
+        }
+    }
+    field
+}
+
+fn test_stones() -> Vec<Stone> {
+    StoneKind::ALL.iter().flat_map(|&kind| {
+        (0..4).map(move |rotation| Stone { kind, rotation, position: Position { x: 3, y: 8 } })
+    }).collect()
+}
+
+fn collision(c: &mut Criterion) {
+    let field = stacked_field();
+    let texture_field = texture_collision::TextureField::from_field(&field);
+    let stones = test_stones();
+    let textures: Vec<_> = stones.iter().map(texture_collision::trimmed_texture).collect();
+
+    let mut group = c.benchmark_group("can_move_down");
+    group.bench_function("texture", |b| b.iter(|| {
+        stones.iter().zip(&textures).filter(|(stone, texture)| texture_collision::can_move_down(black_box(stone), texture, &texture_field)).count()
+    }));
+    group.bench_function("bitboard", |b| b.iter(|| {
+        stones.iter().filter(|stone| black_box(stone).can_move_down(&field)).count()
+    }));
+    group.finish();
+
+    let mut group = c.benchmark_group("can_move_left");
+    group.bench_function("texture", |b| b.iter(|| {
+        stones.iter().zip(&textures).filter(|(stone, texture)| texture_collision::can_move_left(black_box(stone), texture, &texture_field)).count()
+    }));
+    group.bench_function("bitboard", |b| b.iter(|| {
+        stones.iter().filter(|stone| {
+            let mut moved = **black_box(stone);
+            moved.move_left(&field)
+        }).count()
+    }));
+    group.finish();
+
+    let mut group = c.benchmark_group("hard_drop");
+    group.bench_function("texture", |b| b.iter(|| {
+        stones.iter().zip(&textures).map(|(stone, texture)| {
+            let mut dropped = *black_box(stone);
+            while texture_collision::can_move_down(&dropped, texture, &texture_field) {
+                dropped.position.move_down();
+            }
+            dropped.position.y
+        }).sum::<i32>()
+    }));
+    group.bench_function("bitboard", |b| b.iter(|| {
+        stones.iter().map(|stone| {
+            let stone = black_box(stone);
+            stone.position.y + field.board().drop_distance(stone.shape(), &stone.position)
+        }).sum::<i32>()
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, collision);
+criterion_main!(benches);
@@ -0,0 +1,113 @@
+//! A minimal bot speaking the Tetris Bot Protocol that plays the placements of the built-in bot,
+//! to try the TBP frontend without an external bot: `tet-rs --bot tbp-reference-bot marathon`.
+//! An optional argument names a weights file for the built-in bot.
+
+use std::collections::VecDeque;
+use std::io::{stdin, stdout, BufRead, Write};
+use tet_rs::bot::ai::{Bot, BotMessage, FrontendMessage, Move, PieceLocation, Spin, Weights};
+use tet_rs::engine::mechanics::{BoardLayout, Cell, Field, Stone, StoneKind};
+use tet_rs::rendering::renderer::Position;
+
+struct Game {
+    field: Field,
+    hold: Option<StoneKind>,
+    queue: VecDeque<StoneKind>,
+}
+
+impl Game {
+    /// The lower half of the board is visible, like in marathon.
+    fn new(board: &[Vec<Option<char>>], hold: Option<StoneKind>, queue: Vec<StoneKind>) -> Self {
+        let width = board.first().map_or(10, Vec::len);
+        let height = board.len();
+        let mut field = Field::new(BoardLayout { width, visible_height: height - height / 2, hidden_rows: height / 2 });
+        for (row, cells) in board.iter().enumerate() {
+            for (x, cell) in cells.iter().enumerate() {
+                let cell = cell.map_or(Cell::Empty, |cell| Cell::from_char(cell).unwrap_or(Cell::Garbage));
+                field.set_cell_at_pos(&Position { x: x as i32, y: (height - 1 - row) as i32 }, cell);
+            }
+        }
+        Self { field, hold, queue: queue.into() }
+    }
+
+    fn suggest(&self, bot: &Bot) -> Vec<Move> {
+        let current = match self.queue.front() {
+            Some(&kind) => Stone::new(self.field.spawn_position(kind), kind),
+            None => return Vec::new(),
+        };
+        let hold = self.hold.or_else(|| self.queue.get(1).copied());
+        bot.plan(&self.field, &current, hold, true).into_iter()
+            .map(|plan| Move { location: PieceLocation::of(&plan.target, &self.field), spin: Spin::None })
+            .collect()
+    }
+
+    fn play(&mut self, placement: &Move) {
+        let stone = placement.location.stone(&self.field);
+        if self.queue.front() != Some(&stone.kind) {
+            let swapped = self.queue.pop_front();
+            if self.hold.take().is_none() {
+                self.queue.pop_front();
+            }
+            self.hold = swapped;
+        } else {
+            self.queue.pop_front();
+        }
+        self.field.add_stone(&stone);
+        self.field.try_delete_lines();
+    }
+}
+
+fn send(message: &BotMessage) {
+    let line = serde_json::to_string(message).expect("messages can always be serialized");
+    let mut out = stdout();
+    let _ = writeln!(out, "{}", line);
+    let _ = out.flush();
+}
+
+fn main() {
+    let bot = match std::env::args().nth(1) {
+        Some(path) => match Weights::load_from(std::path::Path::new(&path)) {
+            Ok(weights) => Bot::new(weights),
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        },
+        None => Bot::default(),
+    };
+    send(&BotMessage::Info {
+        name: "tet-rs reference bot".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        author: "tet-rs".to_string(),
+        features: Vec::new(),
+    });
+
+    let mut game = None;
+    for line in stdin().lock().lines() {
+        let message = match line.map(|line| serde_json::from_str(&line)) {
+            Ok(Ok(message)) => message,
+            Ok(Err(_)) => continue,
+            Err(_) => return,
+        };
+
+        match message {
+            FrontendMessage::Rules => send(&BotMessage::Ready),
+            FrontendMessage::Start { hold, queue, board, .. } => game = Some(Game::new(&board, hold, queue)),
+            FrontendMessage::Stop => game = None,
+            FrontendMessage::Suggest => {
+                let moves = game.as_ref().map(|game| game.suggest(&bot)).unwrap_or_default();
+                send(&BotMessage::Suggestion { moves });
+            }
+            FrontendMessage::Play { placement } => {
+                if let Some(game) = &mut game {
+                    game.play(&placement);
+                }
+            }
+            FrontendMessage::NewPiece { piece } => {
+                if let Some(game) = &mut game {
+                    game.queue.push_back(piece);
+                }
+            }
+            FrontendMessage::Quit => return,
+        }
+    }
+}
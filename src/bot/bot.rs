@@ -0,0 +1,117 @@
+use crate::bot::heuristic::Weights;
+use crate::bot::search::{reachable_placements, Placement};
+use crate::engine::mechanics::{Field, Stone, StoneKind};
+
+/// Where the bot wants the current piece to go and whether it holds it first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    pub use_hold: bool,
+    pub target: Stone,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Bot {
+    weights: Weights,
+}
+
+impl Bot {
+    pub fn new(weights: Weights) -> Self {
+        Self { weights }
+    }
+
+    pub fn weights(&self) -> &Weights {
+        &self.weights
+    }
+
+    /// Scores the board after the stone locked at its landing position, placements that lock out
+    /// are never chosen.
+    pub fn score(&self, field: &Field, landed: &Stone) -> Option<f64> {
+        if field.is_locked_out(landed) {
+            return None;
+        }
+
+        let mut board = field.board().clone();
+        board.place(landed.shape(), &landed.position);
+        let lines = board.clear_lines();
+        Some(self.weights.evaluate(&board, lines))
+    }
+
+    /// The best placement of the stone, `None` when it can't be placed at all.
+    pub fn best_placement(&self, field: &Field, start: &Stone) -> Option<(Placement, f64)> {
+        reachable_placements(field, start).into_iter()
+            .filter_map(|placement| self.score(field, &placement.stone).map(|score| (placement, score)))
+            .fold(None, |best: Option<(Placement, f64)>, (placement, score)| match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((placement, score)),
+            })
+    }
+
+    /// Compares placing the current piece with placing the piece hold would give, which is the held
+    /// piece or the next one when hold is empty.
+    pub fn plan(&self, field: &Field, current: &Stone, hold: Option<StoneKind>, can_hold: bool) -> Option<Plan> {
+        let direct = self.best_placement(field, current).map(|(placement, score)| Plan {
+            use_hold: false,
+            target: placement.stone,
+            score,
+        });
+        if !can_hold {
+            return direct;
+        }
+
+        let swapped = hold.map(|kind| Stone::new(field.spawn_position(kind), kind));
+        let held = swapped.and_then(|stone| self.best_placement(field, &stone)).map(|(placement, score)| Plan {
+            use_hold: true,
+            target: placement.stone,
+            score,
+        });
+
+        match (direct, held) {
+            (Some(direct), Some(held)) if held.score > direct.score => Some(held),
+            (None, held) => held,
+            (direct, _) => direct,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bot::ai::Bot;
+    use crate::engine::mechanics::{Cell, Field, Stone, StoneKind};
+    use crate::rendering::renderer::Position;
+
+    #[test]
+    fn fills_the_gap() {
+        let mut field = Field::default();
+        for x in 0..10 {
+            if x != 4 {
+                field.set_cell_at_pos(&Position { x, y: 19 }, Cell::Garbage);
+            }
+        }
+
+        let bot = Bot::default();
+        let i_stone = Stone::new(field.spawn_position(StoneKind::I), StoneKind::I);
+        let plan = bot.plan(&field, &i_stone, None, false).unwrap();
+        assert!(!plan.use_hold);
+        let mut cells: Vec<_> = plan.target.cells().collect();
+        cells.sort_by_key(|cell| cell.y);
+        assert_eq!(cells.last(), Some(&Position { x: 4, y: 19 }));
+    }
+
+    #[test]
+    fn holds_the_better_piece() {
+        let mut field = Field::default();
+        for y in 16..20 {
+            for x in 0..9 {
+                field.set_cell_at_pos(&Position { x, y }, Cell::Garbage);
+            }
+        }
+
+        let bot = Bot::default();
+        let s_stone = Stone::new(field.spawn_position(StoneKind::S), StoneKind::S);
+        let plan = bot.plan(&field, &s_stone, Some(StoneKind::I), true).unwrap();
+        assert!(plan.use_hold);
+        assert_eq!(plan.target.kind, StoneKind::I);
+        assert!(plan.target.cells().all(|cell| cell.x == 9));
+    }
+}
@@ -0,0 +1,356 @@
+use crate::bot::bot::Bot;
+use crate::bot::heuristic::Weights;
+use crate::bot::search::{path_to, reachable_placements};
+use crate::bot::tbp::TbpBot;
+use crate::config::settings::Action;
+use crate::engine::mechanics::{Field, Stone, StoneKind};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_ACTION_DELAY: Duration = Duration::from_millis(60);
+
+/// How well the CPU plays in versus games.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    /// Upper bound of the pieces placed per second.
+    pub fn pieces_per_second(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.8,
+            Difficulty::Medium => 1.5,
+            Difficulty::Hard => 3.0,
+        }
+    }
+
+    /// Share of the pieces that are dropped at a random spot instead of the best one.
+    pub fn mistake_rate(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.2,
+            Difficulty::Medium => 0.08,
+            Difficulty::Hard => 0.02,
+        }
+    }
+
+    pub fn action_delay(self) -> Duration {
+        match self {
+            Difficulty::Easy => Duration::from_millis(120),
+            Difficulty::Medium => Duration::from_millis(80),
+            Difficulty::Hard => Duration::from_millis(40),
+        }
+    }
+}
+
+/// What the driver needs to know about the game to choose its next action.
+pub struct Situation<'a> {
+    pub field: &'a Field,
+    pub current: &'a Stone,
+    /// Counts the pieces that entered the field, a new piece means a new plan.
+    pub piece: u64,
+    /// The piece hold would give, the held piece or the next one.
+    pub hold: Option<StoneKind>,
+    pub can_hold: bool,
+    /// The held piece, the upcoming pieces and the chains, for bots that keep their own copy of
+    /// the game.
+    pub held: Option<StoneKind>,
+    pub queue: Vec<StoneKind>,
+    pub combo: u32,
+    pub back_to_back: bool,
+}
+
+impl<'a> Situation<'a> {
+    /// A situation without hold and queue.
+    pub fn new(field: &'a Field, current: &'a Stone, piece: u64) -> Self {
+        Self {
+            field,
+            current,
+            piece,
+            hold: None,
+            can_hold: false,
+            held: None,
+            queue: Vec::new(),
+            combo: 0,
+            back_to_back: false,
+        }
+    }
+}
+
+/// Which bot plays a field, the built-in one or an external bot speaking the Tetris Bot Protocol.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BotChoice {
+    Builtin(Weights),
+    /// The command that starts the bot, e.g. `cold-clear-tbp --quiet`.
+    External(String),
+}
+
+impl BotChoice {
+    /// `builtin`, which plays with the given weights, or the command of an external bot.
+    pub fn parse(choice: &str, weights: &Weights) -> Self {
+        match choice {
+            "builtin" => BotChoice::Builtin(weights.clone()),
+            command => BotChoice::External(command.to_string()),
+        }
+    }
+
+    /// Starts an external bot anew, the built-in bot plays if it can't be started.
+    pub fn driver(&self, action_delay: Duration, now: Instant) -> BotDriver {
+        match self {
+            BotChoice::Builtin(weights) => BotDriver::new(Bot::new(weights.clone()), action_delay, now),
+            BotChoice::External(command) => BotDriver::new(Bot::default(), action_delay, now).with_external(TbpBot::spawn(command)),
+        }
+    }
+}
+
+/// Plays through the same actions as a player, one every `action_delay`. The path to the target
+/// is searched again before every action so gravity can't throw the bot off. The targets come from
+/// the built-in bot or from an external one, the built-in bot takes over if the external one fails.
+pub struct BotDriver {
+    bot: Bot,
+    external: Option<TbpBot>,
+    thinking: bool,
+    error: Option<String>,
+    action_delay: Duration,
+    next_action: Instant,
+    piece_interval: Duration,
+    piece_started: Instant,
+    mistake_rate: f64,
+    rng: StdRng,
+    piece: Option<u64>,
+    target: Option<Stone>,
+    holding: bool,
+}
+
+impl BotDriver {
+    pub fn new(bot: Bot, action_delay: Duration, now: Instant) -> Self {
+        Self {
+            bot,
+            external: None,
+            thinking: false,
+            error: None,
+            action_delay,
+            next_action: now,
+            piece_interval: Duration::from_millis(0),
+            piece_started: now,
+            mistake_rate: 0.0,
+            rng: StdRng::from_entropy(),
+            piece: None,
+            target: None,
+            holding: false,
+        }
+    }
+
+    /// A driver that never drops pieces faster than the difficulty allows and sometimes drops
+    /// them at a random spot.
+    pub fn with_difficulty(bot: Bot, difficulty: Difficulty, now: Instant) -> Self {
+        Self {
+            piece_interval: Duration::from_secs_f64(1.0 / difficulty.pieces_per_second()),
+            mistake_rate: difficulty.mistake_rate(),
+            ..Self::new(bot, difficulty.action_delay(), now)
+        }
+    }
+
+    /// Uses a seeded random generator for the mistakes.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { rng: StdRng::seed_from_u64(seed), ..self }
+    }
+
+    /// Asks the external bot for the targets.
+    pub fn with_external(self, external: Result<TbpBot, String>) -> Self {
+        match external {
+            Ok(external) => Self { external: Some(external), ..self },
+            Err(error) => Self { error: Some(error), ..self },
+        }
+    }
+
+    /// The name of the external bot while it's playing.
+    pub fn external_name(&self) -> Option<&str> {
+        self.external.as_ref().map(TbpBot::name)
+    }
+
+    /// Why the external bot stopped playing.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Where the current piece is going to be placed.
+    pub fn target(&self) -> Option<&Stone> {
+        self.target.as_ref()
+    }
+
+    pub fn delay(&mut self, now: Instant) {
+        self.next_action = now + self.action_delay;
+    }
+
+    pub fn next_action(&mut self, now: Instant, situation: &Situation) -> Option<Action> {
+        if now < self.next_action {
+            return None;
+        }
+        self.delay(now);
+
+        if self.piece != Some(situation.piece) {
+            self.piece = Some(situation.piece);
+            self.piece_started = now;
+            if self.holding {
+                self.holding = false;
+            } else if let Some(action) = self.plan(situation) {
+                return Some(action);
+            }
+        }
+
+        if self.thinking {
+            match self.external.as_mut().and_then(|external| external.suggestion(situation)) {
+                None => return None,
+                Some(Ok(target)) => {
+                    self.thinking = false;
+                    self.target = target;
+                    if target.is_some_and(|target| target.kind != situation.current.kind) {
+                        self.holding = true;
+                        return Some(Action::Hold);
+                    }
+                }
+                Some(Err(error)) => {
+                    self.thinking = false;
+                    self.external = None;
+                    self.error = Some(error);
+                    if let Some(action) = self.plan(situation) {
+                        return Some(action);
+                    }
+                }
+            }
+        }
+
+        let path = match &self.target {
+            Some(target) => path_to(situation.field, situation.current, target),
+            None => None,
+        };
+        let path = match path {
+            Some(path) => path,
+            None => {
+                let best = self.bot.best_placement(situation.field, situation.current);
+                self.target = best.as_ref().map(|(placement, _)| placement.stone);
+                best.map(|(placement, _)| placement.actions).unwrap_or_default()
+            }
+        };
+
+        match path.first() {
+            Some(&action) => Some(action),
+            None if now < self.piece_started + self.piece_interval => None,
+            None => Some(Action::HardDrop),
+        }
+    }
+
+    /// Chooses the target of a new piece. Returns `Hold` if the plan starts with it, the external
+    /// bot answers later.
+    fn plan(&mut self, situation: &Situation) -> Option<Action> {
+        if let Some(external) = &mut self.external {
+            let requested = match external.is_ready() {
+                Ok(true) => external.request(situation).map(|_| true),
+                not_ready => not_ready,
+            };
+            match requested {
+                Ok(true) => {
+                    self.thinking = true;
+                    return None;
+                }
+                // the built-in bot places the pieces until the external one is ready
+                Ok(false) => {}
+                Err(error) => {
+                    self.external = None;
+                    self.error = Some(error);
+                }
+            }
+        }
+
+        if self.rng.gen_bool(self.mistake_rate) {
+            self.target = self.random_target(situation);
+            return None;
+        }
+
+        let plan = self.bot.plan(situation.field, situation.current, situation.hold, situation.can_hold);
+        self.target = plan.as_ref().map(|plan| plan.target);
+        if plan.as_ref().is_some_and(|plan| plan.use_hold) {
+            self.holding = true;
+            return Some(Action::Hold);
+        }
+        None
+    }
+
+    fn random_target(&mut self, situation: &Situation) -> Option<Stone> {
+        let placements: Vec<_> = reachable_placements(situation.field, situation.current).into_iter()
+            .filter(|placement| !situation.field.is_locked_out(&placement.stone))
+            .collect();
+        placements.choose(&mut self.rng).map(|placement| placement.stone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bot::ai::{Bot, BotDriver, Difficulty, Situation};
+    use crate::config::settings::Action;
+    use crate::engine::mechanics::{BoardLayout, Field, Stone, StoneKind};
+    use crate::rendering::renderer::Position;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn waits_between_actions() {
+        let field = Field::default();
+        let stone = Stone::new(field.spawn_position(StoneKind::O), StoneKind::O);
+        let situation = Situation::new(&field, &stone, 0);
+
+        let start = Instant::now();
+        let mut driver = BotDriver::new(Bot::default(), Duration::from_millis(50), start);
+        assert!(driver.next_action(start, &situation).is_some());
+        assert!(driver.target().is_some());
+        assert_eq!(driver.next_action(start + Duration::from_millis(20), &situation), None);
+        assert!(driver.next_action(start + Duration::from_millis(50), &situation).is_some());
+    }
+
+    #[test]
+    fn holds_before_moving() {
+        let field = Field::default();
+        let s_stone = Stone::new(field.spawn_position(StoneKind::S), StoneKind::S);
+        let situation = Situation { hold: Some(StoneKind::I), can_hold: true, ..Situation::new(&field, &s_stone, 0) };
+
+        let start = Instant::now();
+        let mut driver = BotDriver::new(Bot::default(), Duration::from_millis(0), start);
+        assert_eq!(driver.next_action(start, &situation), Some(Action::Hold));
+
+        let i_stone = Stone::new(field.spawn_position(StoneKind::I), StoneKind::I);
+        let situation = Situation { hold: Some(StoneKind::S), ..Situation::new(&field, &i_stone, 1) };
+        assert_ne!(driver.next_action(start, &situation), Some(Action::Hold));
+        assert_eq!(driver.target().map(|target| target.kind), Some(StoneKind::I));
+    }
+
+    #[test]
+    fn difficulty_caps_the_speed() {
+        let field = Field::new(BoardLayout { width: 2, visible_height: 4, hidden_rows: 0 });
+        let stone = Stone::new(Position { x: 0, y: 2 }, StoneKind::O);
+        let situation = Situation::new(&field, &stone, 0);
+
+        let start = Instant::now();
+        let piece_interval = Duration::from_secs_f64(1.0 / Difficulty::Easy.pieces_per_second());
+        let mut driver = BotDriver::with_difficulty(Bot::default(), Difficulty::Easy, start).with_seed(1);
+        assert_eq!(driver.next_action(start, &situation), None);
+        assert_eq!(driver.next_action(start + piece_interval / 2, &situation), None);
+        assert_eq!(driver.next_action(start + piece_interval, &situation), Some(Action::HardDrop));
+    }
+}
@@ -0,0 +1,196 @@
+use crate::engine::mechanics::Board;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Weights of the board features, positive weights reward a feature.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Weights {
+    pub height: f64,
+    pub holes: f64,
+    pub bumpiness: f64,
+    pub lines: f64,
+    pub wells: f64,
+    pub t_slots: f64,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self {
+            height: -0.51,
+            holes: -0.36,
+            bumpiness: -0.18,
+            lines: 0.76,
+            wells: -0.1,
+            t_slots: 0.2,
+        }
+    }
+}
+
+impl std::fmt::Display for Weights {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "height {:.3}, holes {:.3}, bumpiness {:.3}, lines {:.3}, wells {:.3}, t_slots {:.3}",
+            self.height, self.holes, self.bumpiness, self.lines, self.wells, self.t_slots)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Features {
+    pub height: f64,
+    pub holes: f64,
+    pub bumpiness: f64,
+    pub lines: f64,
+    pub wells: f64,
+    pub t_slots: f64,
+}
+
+impl Features {
+    /// Features of the board after a placement cleared the given number of lines.
+    pub fn of(board: &Board, lines: usize) -> Self {
+        let heights = board.column_heights();
+        let bumpiness = heights.windows(2).map(|pair| (pair[0] as i64 - pair[1] as i64).abs()).sum::<i64>();
+
+        Self {
+            height: heights.iter().sum::<usize>() as f64,
+            holes: board.holes() as f64,
+            bumpiness: bumpiness as f64,
+            lines: lines as f64,
+            wells: well_depths(&heights) as f64,
+            t_slots: t_slots(board) as f64,
+        }
+    }
+}
+
+impl Weights {
+    /// Number of features, the length of `values`.
+    pub const COUNT: usize = 6;
+
+    pub fn values(&self) -> [f64; Weights::COUNT] {
+        [self.height, self.holes, self.bumpiness, self.lines, self.wells, self.t_slots]
+    }
+
+    pub fn from_values(values: [f64; Weights::COUNT]) -> Self {
+        let [height, holes, bumpiness, lines, wells, t_slots] = values;
+        Self { height, holes, bumpiness, lines, wells, t_slots }
+    }
+
+    /// Reads weights from a TOML file like the ones `tet-rs tune` writes, missing weights keep
+    /// their defaults.
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path).map_err(|error| format!("could not read weights {}: {}", path.display(), error))?;
+        toml::from_str(&content).map_err(|error| format!("invalid weights {}: {}", path.display(), error))
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        let content = toml::to_string_pretty(self).map_err(|error| format!("could not serialize weights: {}", error))?;
+        std::fs::write(path, content).map_err(|error| format!("could not write weights {}: {}", path.display(), error))
+    }
+
+    pub fn score(&self, features: &Features) -> f64 {
+        self.height * features.height
+            + self.holes * features.holes
+            + self.bumpiness * features.bumpiness
+            + self.lines * features.lines
+            + self.wells * features.wells
+            + self.t_slots * features.t_slots
+    }
+
+    pub fn evaluate(&self, board: &Board, lines: usize) -> f64 {
+        self.score(&Features::of(board, lines))
+    }
+}
+
+/// Sum of the depths of all columns that are lower than both of their neighbours, walls count as
+/// infinitely high.
+fn well_depths(heights: &[usize]) -> usize {
+    (0..heights.len()).map(|x| {
+        let left = if x == 0 { usize::MAX } else { heights[x - 1] };
+        let right = heights.get(x + 1).copied().unwrap_or(usize::MAX);
+        left.min(right).saturating_sub(heights[x])
+    }).filter(|&depth| depth != usize::MAX).sum()
+}
+
+fn filled(board: &Board, x: i32, y: i32) -> bool {
+    x < 0 || x >= board.width() as i32 || y >= board.height() as i32 || board.is_occupied(x, y)
+}
+
+/// Spots where a T pointing down fits with at least three of the corners around its center filled,
+/// which is what a T-spin needs.
+fn t_slots(board: &Board) -> usize {
+    let mut slots = 0;
+    for y in 1..board.height() as i32 - 1 {
+        for x in 1..board.width() as i32 - 1 {
+            let t_cells = [(x - 1, y), (x, y), (x + 1, y), (x, y + 1)];
+            if t_cells.iter().any(|&(cell_x, cell_y)| filled(board, cell_x, cell_y)) {
+                continue;
+            }
+
+            let corners = [(x - 1, y - 1), (x + 1, y - 1), (x - 1, y + 1), (x + 1, y + 1)];
+            let filled_corners = corners.iter().filter(|&&(corner_x, corner_y)| filled(board, corner_x, corner_y)).count();
+            if filled_corners >= 3 {
+                slots += 1;
+            }
+        }
+    }
+    slots
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bot::ai::{Features, Weights};
+    use crate::engine::mechanics::Board;
+
+    fn board(rows: &[&str]) -> Board {
+        let mut board = Board::new(rows[0].len(), rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            for (x, cell) in row.chars().enumerate() {
+                board.set(x, y, cell == '#');
+            }
+        }
+        board
+    }
+
+    #[test]
+    fn features() {
+        let board = board(&[
+            "......",
+            "#.....",
+            "#.#...",
+            "#.##.#",
+        ]);
+        let features = Features::of(&board, 1);
+
+        assert_eq!(features.height, 3.0 + 2.0 + 1.0 + 1.0);
+        assert_eq!(features.holes, 0.0);
+        assert_eq!(features.bumpiness, 3.0 + 2.0 + 1.0 + 1.0 + 1.0);
+        assert_eq!(features.wells, 2.0 + 1.0);
+        assert_eq!(features.lines, 1.0);
+        assert_eq!(Weights::default().score(&Features::default()), 0.0);
+    }
+
+    #[test]
+    fn weights_files() {
+        let path = std::env::temp_dir().join(format!("tet-rs-weights-{}.toml", std::process::id()));
+        let weights = Weights::from_values([-1.0, -2.0, -0.5, 1.0, 0.0, 0.25]);
+        weights.save_to(&path).unwrap();
+        assert_eq!(Weights::load_from(&path), Ok(weights.clone()));
+        assert_eq!(Weights::from_values(weights.values()), weights);
+
+        std::fs::write(&path, "holes = -3.0\n").unwrap();
+        assert_eq!(Weights::load_from(&path), Ok(Weights { holes: -3.0, ..Weights::default() }));
+        std::fs::write(&path, "spins = 1.0\n").unwrap();
+        assert!(Weights::load_from(&path).unwrap_err().contains("spins"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn t_slot() {
+        let board = board(&[
+            "......",
+            "##....",
+            "#...##",
+            "##.###",
+        ]);
+        assert_eq!(Features::of(&board, 0).t_slots, 1.0);
+    }
+}
@@ -0,0 +1,15 @@
+mod bot;
+mod driver;
+mod heuristic;
+mod perfect_clear;
+mod search;
+mod tbp;
+
+pub mod ai {
+    pub use super::bot::{Bot, Plan};
+    pub use super::driver::{BotChoice, BotDriver, Difficulty, Situation, DEFAULT_ACTION_DELAY};
+    pub use super::heuristic::{Features, Weights};
+    pub use super::perfect_clear::{find_perfect_clear, MAX_PC_LINES};
+    pub use super::search::{apply_move, path_to, reachable_placements, Placement};
+    pub use super::tbp::{tbp_board, BotMessage, FrontendMessage, Move, Orientation, PieceLocation, Spin, TbpBot, TBP_TIMEOUT};
+}
@@ -0,0 +1,152 @@
+use crate::bot::search::reachable_placements;
+use crate::engine::mechanics::{Board, Field, Stone, StoneKind};
+use std::collections::HashSet;
+
+/// Perfect clears are searched within at most this many lines.
+pub const MAX_PC_LINES: usize = 4;
+
+/// Searches placements that leave the board empty, clearing at most `max_lines` lines. `pieces`
+/// starts with the falling piece followed by the upcoming ones, `hold` is the held piece. Returns
+/// the placements in the order they're made, a placement of another kind than the piece it's made
+/// for means holding first. Placements have to be reachable by moving, rotating and soft dropping.
+/// Fewer lines are tried first.
+pub fn find_perfect_clear(field: &Field, pieces: &[StoneKind], hold: Option<StoneKind>, max_lines: usize) -> Option<Vec<Stone>> {
+    let board = field.board();
+    let filled: usize = board.rows().iter().map(|row| row.count_ones() as usize).sum();
+    let lowest = board.column_heights().into_iter().max().unwrap_or(0).max(1);
+
+    let mut search = Search { pieces, failed: HashSet::new(), placements: Vec::new() };
+    for lines in (lowest..=max_lines.min(MAX_PC_LINES)).filter(|lines| (lines * board.width() - filled).is_multiple_of(4)) {
+        search.failed.clear();
+        if search.solve(field, 0, hold, lines) {
+            return Some(search.placements);
+        }
+    }
+    None
+}
+
+/// Depth first search that remembers the states it couldn't clear from.
+struct Search<'a> {
+    pieces: &'a [StoneKind],
+    failed: HashSet<(Board, usize, Option<StoneKind>, usize)>,
+    placements: Vec<Stone>,
+}
+
+impl<'a> Search<'a> {
+    /// `next` is the index of the piece to place, `lines` how many lines may still be cleared.
+    fn solve(&mut self, field: &Field, next: usize, hold: Option<StoneKind>, lines: usize) -> bool {
+        if !self.placements.is_empty() && field.board().is_empty() {
+            return true;
+        }
+        let available = self.pieces.len().saturating_sub(next) + hold.map_or(0, |_| 1);
+        if !fits_in(field.board(), lines, available) {
+            return false;
+        }
+        let key = (field.board().clone(), next, hold, lines);
+        if self.failed.contains(&key) {
+            return false;
+        }
+
+        // the piece to place, the index of the piece after it and what's held afterwards
+        let mut choices = Vec::with_capacity(2);
+        if let Some(&current) = self.pieces.get(next) {
+            choices.push((current, next + 1, hold));
+            match hold {
+                Some(held) if held != current => choices.push((held, next + 1, Some(current))),
+                None => if let Some(&following) = self.pieces.get(next + 1) {
+                    if following != current {
+                        choices.push((following, next + 2, Some(current)));
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        for (kind, after, hold) in choices {
+            let start = Stone::new(field.spawn_position(kind), kind);
+            let top = (field.total_height() - lines) as i32;
+            for placement in reachable_placements(field, &start) {
+                if placement.stone.cells().any(|cell| cell.y < top) {
+                    continue;
+                }
+
+                let mut next_field = field.clone();
+                next_field.add_stone(&placement.stone);
+                let cleared = next_field.try_delete_lines();
+                self.placements.push(placement.stone);
+                if self.solve(&next_field, after, hold, lines - cleared) {
+                    return true;
+                }
+                self.placements.pop();
+            }
+        }
+
+        self.failed.insert(key);
+        false
+    }
+}
+
+/// Whether the blocks are all within the lowest `lines` rows and the pieces can fill the rest of them.
+fn fits_in(board: &Board, lines: usize, pieces: usize) -> bool {
+    let rows = board.rows();
+    let (above, within) = rows.split_at(rows.len() - lines);
+    if above.iter().any(|&row| row != 0) {
+        return false;
+    }
+    let empty = lines * board.width() - within.iter().map(|row| row.count_ones() as usize).sum::<usize>();
+    empty.is_multiple_of(4) && empty <= 4 * pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bot::ai::find_perfect_clear;
+    use crate::engine::mechanics::{Cell, Field, Mode, Stone, StoneKind};
+    use crate::rendering::renderer::Position;
+
+    fn field(rows: &[&str]) -> Field {
+        let mut field = Field::new(Mode::Marathon.layout());
+        let bottom = field.total_height() as i32 - 1;
+        for (index, row) in rows.iter().rev().enumerate() {
+            for (x, cell) in row.chars().enumerate() {
+                if cell == '#' {
+                    field.set_cell_at_pos(&Position { x: x as i32, y: bottom - index as i32 }, Cell::Garbage);
+                }
+            }
+        }
+        field
+    }
+
+    fn clears(field: &Field, placements: &[Stone]) -> bool {
+        let mut field = field.clone();
+        for stone in placements {
+            field.add_stone(stone);
+            field.try_delete_lines();
+        }
+        field.board().is_empty()
+    }
+
+    #[test]
+    fn finds_a_two_line_clear() {
+        let field = field(&[
+            "###.....##",
+            "####...###",
+        ]);
+        let pieces = [StoneKind::T, StoneKind::Z, StoneKind::O];
+        let placements = find_perfect_clear(&field, &pieces, None, 4).unwrap();
+        assert!(clears(&field, &placements));
+        assert_eq!(placements.len(), 2);
+        assert_eq!(find_perfect_clear(&field, &[StoneKind::S, StoneKind::O, StoneKind::I], None, 4), None);
+    }
+
+    #[test]
+    fn uses_hold() {
+        let field = field(&[
+            "######....",
+            "######....",
+        ]);
+        assert!(find_perfect_clear(&field, &[StoneKind::S, StoneKind::O], None, 2).is_none());
+        let placements = find_perfect_clear(&field, &[StoneKind::S, StoneKind::O], Some(StoneKind::O), 2).unwrap();
+        assert!(clears(&field, &placements));
+        assert_eq!(placements.len(), 2);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::config::settings::Action;
+use crate::engine::mechanics::{landing_cells, Field, Stone};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// A reachable resting place of a stone and the actions that lead there, the final hard drop
+/// isn't part of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placement {
+    pub stone: Stone,
+    pub actions: Vec<Action>,
+}
+
+const MOVES: [Action; 6] = [
+    Action::MoveLeft,
+    Action::MoveRight,
+    Action::RotateCW,
+    Action::RotateCCW,
+    Action::Rotate180,
+    Action::SoftDrop,
+];
+
+/// Applies a single movement the way the play view does, soft drop moves by one cell.
+pub fn apply_move(stone: &mut Stone, field: &Field, action: Action) -> bool {
+    match action {
+        Action::MoveLeft => stone.move_left(field),
+        Action::MoveRight => stone.move_right(field),
+        Action::RotateCW => stone.rotate(field),
+        Action::RotateCCW => stone.rotate_counterclockwise(field),
+        Action::Rotate180 => stone.rotate_180(field),
+        Action::SoftDrop => stone.move_down(field),
+        _ => false,
+    }
+}
+
+/// Every distinct placement the stone can reach by moving, rotating and soft dropping, each with
+/// one of the shortest action sequences.
+pub fn reachable_placements(field: &Field, start: &Stone) -> Vec<Placement> {
+    if field.board().collides(start.shape(), &start.position) {
+        return Vec::new();
+    }
+
+    let key = |stone: &Stone| (stone.position.x, stone.position.y, stone.rotation);
+    let mut paths: HashMap<(i32, i32, u8), Vec<Action>> = HashMap::new();
+    let mut landings = HashSet::new();
+    let mut placements = Vec::new();
+    let mut queue = VecDeque::new();
+
+    paths.insert(key(start), Vec::new());
+    queue.push_back(*start);
+
+    while let Some(stone) = queue.pop_front() {
+        let actions = paths[&key(&stone)].clone();
+
+        if landings.insert(landing_cells(&stone, field)) {
+            let mut landed = stone;
+            landed.position.y += field.board().drop_distance(stone.shape(), &stone.position);
+            placements.push(Placement { stone: landed, actions: actions.clone() });
+        }
+
+        for &action in MOVES.iter() {
+            let mut next = stone;
+            if apply_move(&mut next, field, action) && !paths.contains_key(&key(&next)) {
+                let mut next_actions = actions.clone();
+                next_actions.push(action);
+                paths.insert(key(&next), next_actions);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    placements
+}
+
+/// The shortest actions that bring the stone to the target, `None` if the target can't be reached
+/// from where the stone is.
+pub fn path_to(field: &Field, start: &Stone, target: &Stone) -> Option<Vec<Action>> {
+    let target_cells = landing_cells(target, field);
+    reachable_placements(field, start).into_iter()
+        .find(|placement| landing_cells(&placement.stone, field) == target_cells)
+        .map(|placement| placement.actions)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bot::ai::{apply_move, path_to, reachable_placements};
+    use crate::config::settings::Action;
+    use crate::engine::mechanics::{Cell, Field, Stone, StoneKind};
+    use crate::rendering::renderer::Position;
+
+    #[test]
+    fn open_field_matches_hard_drop_placements() {
+        let field = Field::default();
+        let t_stone = Stone::new(field.spawn_position(StoneKind::T), StoneKind::T);
+        assert_eq!(reachable_placements(&field, &t_stone).len(), 34);
+    }
+
+    #[test]
+    fn tucks_need_soft_drops() {
+        let mut field = Field::default();
+        for x in 0..8 {
+            field.set_cell_at_pos(&Position { x, y: 17 }, Cell::Garbage);
+        }
+
+        let o_stone = Stone::new(Position { x: 4, y: 0 }, StoneKind::O);
+        let placements = reachable_placements(&field, &o_stone);
+        let under_the_roof = placements.iter()
+            .find(|placement| placement.stone.position == Position { x: 7, y: 18 })
+            .expect("the O can be tucked under the overhang");
+        assert!(under_the_roof.actions.contains(&Action::SoftDrop));
+
+        let mut stone = o_stone;
+        for &action in under_the_roof.actions.iter() {
+            assert!(apply_move(&mut stone, &field, action));
+        }
+        assert_eq!(stone.position, Position { x: 7, y: 18 });
+        assert_eq!(path_to(&field, &o_stone, &under_the_roof.stone).as_ref(), Some(&under_the_roof.actions));
+    }
+}
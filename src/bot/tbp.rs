@@ -0,0 +1,523 @@
+use crate::bot::driver::Situation;
+use crate::bot::search::path_to;
+use crate::engine::mechanics::{Board, Cell, Field, Stone, StoneKind};
+use crate::rendering::renderer::Position;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::time::{Duration, Instant};
+
+/// How long a bot may take to introduce itself and to accept the rules.
+pub const TBP_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Orientation {
+    const ALL: [Orientation; 4] = [Orientation::North, Orientation::East, Orientation::South, Orientation::West];
+
+    pub fn from_rotation(rotation: u8) -> Self {
+        Self::ALL[rotation as usize % 4]
+    }
+
+    pub fn rotation(self) -> u8 {
+        self as u8
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Spin {
+    None,
+    Mini,
+    Full,
+}
+
+/// Where a piece rests in TBP coordinates: `x` from the left, `y` from the bottom row, both of
+/// the cell the piece rotates around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PieceLocation {
+    #[serde(rename = "type")]
+    pub kind: StoneKind,
+    pub orientation: Orientation,
+    pub x: i32,
+    pub y: i32,
+}
+
+/// The cell TBP rotates a piece around, relative to the box of the stone: the middle of the three
+/// wide pieces, for I and O the cell SRS puts at the origin.
+fn center(kind: StoneKind, rotation: u8) -> (i32, i32) {
+    let size = kind.shape(0).size;
+    let mut center = if kind == StoneKind::O { (0, 1) } else { (1, 1) };
+    for _ in 0..rotation % 4 {
+        center = (size - 1 - center.1, center.0);
+    }
+    center
+}
+
+impl PieceLocation {
+    pub fn of(stone: &Stone, field: &Field) -> Self {
+        let (x, y) = center(stone.kind, stone.rotation);
+        Self {
+            kind: stone.kind,
+            orientation: Orientation::from_rotation(stone.rotation),
+            x: stone.position.x + x,
+            y: field.total_height() as i32 - 1 - (stone.position.y + y),
+        }
+    }
+
+    pub fn stone(&self, field: &Field) -> Stone {
+        let rotation = self.orientation.rotation();
+        let (x, y) = center(self.kind, rotation);
+        Stone {
+            kind: self.kind,
+            rotation,
+            position: Position { x: self.x - x, y: field.total_height() as i32 - 1 - self.y - y },
+        }
+    }
+
+    /// The stone if it's on the field without overlapping any blocks, a bot may send anything.
+    pub fn placed(&self, field: &Field) -> Option<Stone> {
+        let width = field.dimensions().width as i32;
+        if !(0..width).contains(&self.x) || !(0..field.total_height() as i32).contains(&self.y) {
+            return None;
+        }
+        Some(self.stone(field)).filter(|stone| !field.board().collides(stone.shape(), &stone.position))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Move {
+    pub location: PieceLocation,
+    pub spin: Spin,
+}
+
+/// The rows of the field from the bottom up, `None` for empty cells.
+pub fn tbp_board(field: &Field) -> Vec<Vec<Option<char>>> {
+    (0..field.total_height()).rev().map(|y| {
+        (0..field.dimensions().width).map(|x| match field.get_cell_at_pos(&Position { x: x as i32, y: y as i32 }) {
+            Some(Cell::Empty) | None => None,
+            Some(cell) => Some(cell.to_char()),
+        }).collect()
+    }).collect()
+}
+
+/// The messages the game sends to the bot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FrontendMessage {
+    Rules,
+    Start {
+        hold: Option<StoneKind>,
+        /// Starts with the current piece.
+        queue: Vec<StoneKind>,
+        combo: u32,
+        back_to_back: bool,
+        board: Vec<Vec<Option<char>>>,
+    },
+    Stop,
+    Suggest,
+    Play {
+        #[serde(rename = "move")]
+        placement: Move,
+    },
+    NewPiece { piece: StoneKind },
+    Quit,
+}
+
+/// The messages the bot sends to the game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BotMessage {
+    Error { reason: String },
+    Ready,
+    Info { name: String, version: String, author: String, features: Vec<String> },
+    Suggestion { moves: Vec<Move> },
+}
+
+/// What the bot believes the game looks like, to notice when garbage or a placement the bot didn't
+/// suggest makes it wrong.
+struct Model {
+    board: Board,
+    hold: Option<StoneKind>,
+    queue: VecDeque<StoneKind>,
+}
+
+impl Model {
+    /// Whether the bot may play the stone: it has to be the current piece or, if hold can be used,
+    /// the held piece or the next one when nothing is held. With `reachable` the stone also has
+    /// to be reachable without wall kicks, for games where the pieces are moved there.
+    fn check(&self, stone: &Stone, situation: &Situation, reachable: bool) -> Result<(), String> {
+        let holding = self.queue.front() != Some(&stone.kind);
+        let from_hold = self.hold.or_else(|| self.queue.get(1).copied());
+        if holding && (!situation.can_hold || from_hold != Some(stone.kind)) {
+            return Err(format!("the bot suggested an {:?} piece, which it doesn't have", stone.kind));
+        }
+
+        let start = if holding { Stone::new(situation.field.spawn_position(stone.kind), stone.kind) } else { *situation.current };
+        if reachable && path_to(situation.field, &start, stone).is_none() {
+            return Err(format!("the bot suggested a placement of an {:?} piece that can't be reached", stone.kind));
+        }
+        Ok(())
+    }
+
+    fn play(&mut self, stone: &Stone) {
+        if self.queue.front() != Some(&stone.kind) {
+            let swapped = self.queue.pop_front();
+            if self.hold.take().is_none() {
+                self.queue.pop_front();
+            }
+            self.hold = swapped;
+        } else {
+            self.queue.pop_front();
+        }
+        self.board.place(stone.shape(), &stone.position);
+        self.board.clear_lines();
+    }
+}
+
+/// Reads one message from the bot.
+fn read_message(lines: &mut impl Iterator<Item=std::io::Result<String>>) -> Result<BotMessage, String> {
+    match lines.next() {
+        Some(Ok(line)) => serde_json::from_str(&line).map_err(|error| format!("the bot sent an invalid message: {}", error)),
+        Some(Err(error)) => Err(format!("could not read from the bot: {}", error)),
+        None => Err("the bot exited".to_string()),
+    }
+}
+
+fn write_message(stdin: &mut ChildStdin, message: &FrontendMessage) -> Result<(), String> {
+    let mut line = serde_json::to_string(message).expect("messages can always be serialized");
+    line.push('\n');
+    stdin.write_all(line.as_bytes())
+        .and_then(|_| stdin.flush())
+        .map_err(|error| format!("could not talk to the bot: {}", error))
+}
+
+/// Waits for the bot to introduce itself and to accept the rules, returns its name and version.
+fn handshake(lines: &mut impl Iterator<Item=std::io::Result<String>>, stdin: &mut ChildStdin) -> Result<String, String> {
+    let name = match read_message(lines)? {
+        BotMessage::Info { name, version, .. } => format!("{} {}", name, version),
+        message => return Err(format!("the bot sent {:?} instead of its info", message)),
+    };
+    write_message(stdin, &FrontendMessage::Rules)?;
+    match read_message(lines)? {
+        BotMessage::Ready => Ok(name),
+        BotMessage::Error { reason } => Err(format!("the bot refused the rules: {}", reason)),
+        message => Err(format!("the bot sent {:?} instead of getting ready", message)),
+    }
+}
+
+/// The introduction of a bot that's still going on, its thread hands stdin back once it's done.
+struct Handshake {
+    done: Receiver<Result<(String, ChildStdin), String>>,
+    deadline: Instant,
+}
+
+/// An external bot that speaks the Tetris Bot Protocol, one JSON message per line on its stdin and
+/// stdout. A thread reads its answers so the game never waits for it while playing, the same thread
+/// does the introduction after the bot started.
+pub struct TbpBot {
+    name: String,
+    child: Child,
+    /// `None` until the bot is ready.
+    stdin: Option<ChildStdin>,
+    handshake: Option<Handshake>,
+    incoming: Receiver<Result<BotMessage, String>>,
+    model: Option<Model>,
+}
+
+impl TbpBot {
+    /// Starts the command, split at whitespace. The bot introduces itself in the background, see
+    /// `is_ready` and `wait_until_ready`.
+    pub fn spawn(command: &str) -> Result<Self, String> {
+        let mut parts = command.split_whitespace();
+        let program = parts.next().ok_or("the bot command is empty")?;
+        let mut child = Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|error| format!("could not start the bot '{}': {}", program, error))?;
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        let (done_sender, done) = mpsc::channel();
+        let (sender, incoming) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut lines = BufReader::new(stdout).lines();
+            match handshake(&mut lines, &mut stdin) {
+                Ok(name) => {
+                    if done_sender.send(Ok((name, stdin))).is_err() {
+                        return;
+                    }
+                }
+                Err(error) => {
+                    let _ = done_sender.send(Err(error));
+                    return;
+                }
+            }
+            loop {
+                let message = read_message(&mut lines);
+                let failed = message.is_err();
+                if sender.send(message).is_err() || failed {
+                    return;
+                }
+            }
+        });
+
+        let handshake = Handshake { done, deadline: Instant::now() + 2 * TBP_TIMEOUT };
+        Ok(Self { name: program.to_string(), child, stdin: None, handshake: Some(handshake), incoming, model: None })
+    }
+
+    /// The name and version the bot introduced itself with, the program until then.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the bot accepted the rules, an error if it refused or took too long.
+    pub fn is_ready(&mut self) -> Result<bool, String> {
+        let result = match &self.handshake {
+            None => return Ok(true),
+            Some(handshake) => match handshake.done.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) if Instant::now() < handshake.deadline => return Ok(false),
+                Err(TryRecvError::Empty) => Err("the bot didn't get ready in time".to_string()),
+                Err(TryRecvError::Disconnected) => Err("the bot exited".to_string()),
+            },
+        };
+        self.finish_handshake(result).map(|_| true)
+    }
+
+    /// Blocks until the bot accepted the rules, for games that don't run in real time.
+    pub fn wait_until_ready(&mut self) -> Result<(), String> {
+        let result = match &self.handshake {
+            None => return Ok(()),
+            Some(handshake) => match handshake.done.recv_timeout(handshake.deadline.saturating_duration_since(Instant::now())) {
+                Ok(result) => result,
+                Err(RecvTimeoutError::Timeout) => Err("the bot didn't get ready in time".to_string()),
+                Err(RecvTimeoutError::Disconnected) => Err("the bot exited".to_string()),
+            },
+        };
+        self.finish_handshake(result)
+    }
+
+    fn finish_handshake(&mut self, result: Result<(String, ChildStdin), String>) -> Result<(), String> {
+        self.handshake = None;
+        let (name, stdin) = result?;
+        self.name = name;
+        self.stdin = Some(stdin);
+        Ok(())
+    }
+
+    fn wait(&mut self) -> Result<BotMessage, String> {
+        match self.incoming.recv_timeout(TBP_TIMEOUT) {
+            Ok(message) => message,
+            Err(RecvTimeoutError::Timeout) => Err("the bot didn't answer in time".to_string()),
+            Err(RecvTimeoutError::Disconnected) => Err("the bot exited".to_string()),
+        }
+    }
+
+    fn send(&mut self, message: &FrontendMessage) -> Result<(), String> {
+        let stdin = self.stdin.as_mut().ok_or("the bot isn't ready yet")?;
+        write_message(stdin, message)
+    }
+
+    /// Tells the bot about new pieces, or starts it over if the game went differently than it
+    /// expects, and asks for a move. The answer arrives through `suggestion`.
+    pub fn request(&mut self, situation: &Situation) -> Result<(), String> {
+        let queue: Vec<StoneKind> = std::iter::once(situation.current.kind).chain(situation.queue.iter().copied()).collect();
+        let known = match &self.model {
+            Some(model) if model.board == *situation.field.board() && model.hold == situation.held
+                && model.queue.len() <= queue.len() && model.queue.iter().eq(queue[..model.queue.len()].iter()) => Some(model.queue.len()),
+            _ => None,
+        };
+
+        match known {
+            Some(known) => {
+                for &piece in &queue[known..] {
+                    self.send(&FrontendMessage::NewPiece { piece })?;
+                }
+                if let Some(model) = &mut self.model {
+                    model.queue.extend(&queue[known..]);
+                }
+            }
+            None => {
+                if self.model.is_some() {
+                    self.send(&FrontendMessage::Stop)?;
+                }
+                self.send(&FrontendMessage::Start {
+                    hold: situation.held,
+                    queue: queue.clone(),
+                    combo: situation.combo,
+                    back_to_back: situation.back_to_back,
+                    board: tbp_board(situation.field),
+                })?;
+                self.model = Some(Model { board: situation.field.board().clone(), hold: situation.held, queue: queue.into() });
+            }
+        }
+        self.send(&FrontendMessage::Suggest)
+    }
+
+    /// `None` while the bot is still thinking. The first suggested move is played on the bot's
+    /// side right away if it can be reached, `Ok(None)` means the bot gave up.
+    pub fn suggestion(&mut self, situation: &Situation) -> Option<Result<Option<Stone>, String>> {
+        let message = match self.incoming.try_recv() {
+            Ok(message) => message,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err("the bot exited".to_string()),
+        };
+        Some(self.play_suggestion(message, situation, true))
+    }
+
+    /// Waits for the answer to `request` instead of polling, for games that don't run in real time.
+    /// Those place the pieces directly, so the move doesn't have to be reachable.
+    pub fn wait_for_suggestion(&mut self, situation: &Situation) -> Result<Option<Stone>, String> {
+        let message = self.wait();
+        self.play_suggestion(message, situation, false)
+    }
+
+    fn play_suggestion(&mut self, message: Result<BotMessage, String>, situation: &Situation, reachable: bool) -> Result<Option<Stone>, String> {
+        let moves = match message? {
+            BotMessage::Suggestion { moves } => moves,
+            BotMessage::Error { reason } => return Err(format!("the bot failed: {}", reason)),
+            message => return Err(format!("the bot sent {:?} instead of a suggestion", message)),
+        };
+
+        let placement = match moves.first() {
+            Some(&placement) => placement,
+            None => return Ok(None),
+        };
+        let stone = placement.location.placed(situation.field)
+            .ok_or_else(|| format!("the bot suggested {:?}, which isn't free on the field", placement.location))?;
+        self.model.as_ref().ok_or("the bot suggested a move before the game started")?.check(&stone, situation, reachable)?;
+        self.send(&FrontendMessage::Play { placement })?;
+        if let Some(model) = &mut self.model {
+            model.play(&stone);
+        }
+        Ok(Some(stone))
+    }
+}
+
+impl Drop for TbpBot {
+    fn drop(&mut self) {
+        let _ = self.send(&FrontendMessage::Quit);
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bot::ai::{BotMessage, FrontendMessage, Orientation, PieceLocation, Situation, TbpBot};
+    use crate::engine::mechanics::{Cell, Field, Mode, Stone, StoneKind};
+    use crate::rendering::renderer::Position;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn locations_follow_srs_centers() {
+        let field = Field::new(Mode::Marathon.layout());
+        let bottom = field.total_height() as i32 - 1;
+
+        let mut t_stone = Stone::new(Position { x: 3, y: bottom - 1 }, StoneKind::T);
+        let location = PieceLocation::of(&t_stone, &field);
+        assert_eq!((location.orientation, location.x, location.y), (Orientation::North, 4, 0));
+        assert_eq!(location.stone(&field), t_stone);
+
+        let mut i_stone = Stone::new(Position { x: 0, y: bottom - 3 }, StoneKind::I);
+        i_stone.rotation = 1;
+        let location = PieceLocation::of(&i_stone, &field);
+        assert_eq!((location.orientation, location.x, location.y), (Orientation::East, 2, 2));
+        assert_eq!(location.stone(&field), i_stone);
+
+        let o_stone = Stone::new(Position { x: 8, y: bottom - 1 }, StoneKind::O);
+        let location = PieceLocation::of(&o_stone, &field);
+        assert_eq!((location.x, location.y), (8, 0));
+
+        for rotation in 0..4 {
+            t_stone.rotation = rotation;
+            assert_eq!(PieceLocation::of(&t_stone, &field).stone(&field), t_stone);
+        }
+    }
+
+    #[test]
+    fn suggestions_have_to_be_playable() {
+        let mut field = Field::new(Mode::Marathon.layout());
+        let bottom = field.total_height() as i32 - 1;
+        let current = Stone::new(field.spawn_position(StoneKind::T), StoneKind::T);
+        let model = super::Model { board: field.board().clone(), hold: None, queue: vec![StoneKind::T, StoneKind::I, StoneKind::O].into() };
+        let mut situation = Situation::new(&field, &current, 0);
+        situation.can_hold = true;
+
+        let t_stone = Stone::new(Position { x: 3, y: bottom - 1 }, StoneKind::T);
+        let i_stone = Stone::new(Position { x: 0, y: bottom - 1 }, StoneKind::I);
+        let o_stone = Stone::new(Position { x: 0, y: bottom - 1 }, StoneKind::O);
+        assert_eq!(model.check(&t_stone, &situation, true), Ok(()));
+        assert_eq!(model.check(&i_stone, &situation, true), Ok(()));
+        assert!(model.check(&o_stone, &situation, true).is_err());
+        situation.can_hold = false;
+        assert!(model.check(&i_stone, &situation, true).is_err());
+
+        for x in 0..10 {
+            field.set_cell_at_pos(&Position { x, y: bottom - 2 }, Cell::Garbage);
+        }
+        let situation = Situation::new(&field, &current, 0);
+        assert!(model.check(&t_stone, &situation, true).is_err());
+        assert_eq!(model.check(&t_stone, &situation, false), Ok(()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn slow_bots_get_ready_in_the_background() {
+        let started = Instant::now();
+        let mut bot = TbpBot::spawn("sleep 30").unwrap();
+        assert_eq!(bot.is_ready(), Ok(false));
+        assert_eq!(bot.name(), "sleep");
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn rejects_placements_off_the_field() {
+        let mut field = Field::new(Mode::Marathon.layout());
+        let bottom = field.total_height() as i32 - 1;
+        let location = |x, y| PieceLocation { kind: StoneKind::T, orientation: Orientation::North, x, y };
+
+        assert!(location(4, 0).placed(&field).is_some());
+        for &(x, y) in [(0, 0), (9, 0), (40, 0), (i32::MAX, 0), (4, -1), (4, 40), (4, i32::MIN)].iter() {
+            assert_eq!(location(x, y).placed(&field), None, "{} {}", x, y);
+        }
+        field.set_cell_at_pos(&Position { x: 4, y: bottom }, Cell::Garbage);
+        assert_eq!(location(4, 0).placed(&field), None);
+        assert!(location(4, 1).placed(&field).is_some());
+    }
+
+    #[test]
+    fn wire_format() {
+        let mut field = Field::new(Mode::Marathon.layout());
+        let bottom = field.total_height() as i32 - 1;
+        field.set_cell_at_pos(&Position { x: 0, y: bottom }, Cell::Garbage);
+
+        let board = crate::bot::ai::tbp_board(&field);
+        assert_eq!(board.len(), 40);
+        assert_eq!(board[0][0], Some('G'));
+        assert_eq!(board[0][1], None);
+
+        let start = FrontendMessage::Start { hold: None, queue: vec![StoneKind::T], combo: 0, back_to_back: false, board: vec![vec![None, Some('G')]] };
+        assert_eq!(serde_json::to_string(&start).unwrap(),
+            r#"{"type":"start","hold":null,"queue":["T"],"combo":0,"back_to_back":false,"board":[[null,"G"]]}"#);
+
+        let suggestion = r#"{"type":"suggestion","moves":[{"location":{"type":"I","orientation":"west","x":0,"y":2},"spin":"none"}],"move_info":{}}"#;
+        match serde_json::from_str(suggestion).unwrap() {
+            BotMessage::Suggestion { moves } => assert_eq!(moves[0].location.orientation, Orientation::West),
+            message => panic!("unexpected {:?}", message),
+        }
+    }
+}
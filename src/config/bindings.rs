@@ -0,0 +1,433 @@
+use crate::config::keys::Key;
+use crossterm::event::{KeyCode, KeyEvent};
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    HardDrop,
+    RotateCW,
+    RotateCCW,
+    Rotate180,
+    Hold,
+    Pause,
+    Restart,
+    CopyFumen,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 12] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::SoftDrop,
+        Action::HardDrop,
+        Action::RotateCW,
+        Action::RotateCCW,
+        Action::Rotate180,
+        Action::Hold,
+        Action::Pause,
+        Action::Restart,
+        Action::CopyFumen,
+        Action::Quit,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "move left",
+            Action::MoveRight => "move right",
+            Action::SoftDrop => "soft drop",
+            Action::HardDrop => "hard drop",
+            Action::RotateCW => "rotate clockwise",
+            Action::RotateCCW => "rotate counterclockwise",
+            Action::Rotate180 => "rotate 180",
+            Action::Hold => "hold",
+            Action::Pause => "pause",
+            Action::Restart => "restart",
+            Action::CopyFumen => "copy board as fumen",
+            Action::Quit => "quit",
+        }
+    }
+
+    /// The name of the action in the config file.
+    pub fn config_name(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::SoftDrop => "soft_drop",
+            Action::HardDrop => "hard_drop",
+            Action::RotateCW => "rotate_cw",
+            Action::RotateCCW => "rotate_ccw",
+            Action::Rotate180 => "rotate_180",
+            Action::Hold => "hold",
+            Action::Pause => "pause",
+            Action::Restart => "restart",
+            Action::CopyFumen => "copy_fumen",
+            Action::Quit => "quit",
+        }
+    }
+
+    /// Whether the action moves the piece of one player rather than controlling the game.
+    pub fn is_piece_action(self) -> bool {
+        !matches!(self, Action::Pause | Action::Restart | Action::CopyFumen | Action::Quit)
+    }
+}
+
+struct OneOrMany;
+
+impl<'de> Visitor<'de> for OneOrMany {
+    type Value = Vec<Key>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a key or a list of keys")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        Key::try_from(value.to_string()).map(|key| vec![key]).map_err(E::custom)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut sequence: A) -> Result<Self::Value, A::Error> {
+        let mut keys = Vec::new();
+        while let Some(key) = sequence.next_element()? {
+            keys.push(key);
+        }
+        Ok(keys)
+    }
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Key>, D::Error> {
+    deserializer.deserialize_any(OneOrMany)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyBindings {
+    #[serde(deserialize_with = "one_or_many")]
+    pub move_left: Vec<Key>,
+    #[serde(deserialize_with = "one_or_many")]
+    pub move_right: Vec<Key>,
+    #[serde(deserialize_with = "one_or_many")]
+    pub soft_drop: Vec<Key>,
+    #[serde(deserialize_with = "one_or_many")]
+    pub hard_drop: Vec<Key>,
+    #[serde(deserialize_with = "one_or_many")]
+    pub rotate_cw: Vec<Key>,
+    #[serde(deserialize_with = "one_or_many")]
+    pub rotate_ccw: Vec<Key>,
+    #[serde(deserialize_with = "one_or_many")]
+    pub rotate_180: Vec<Key>,
+    #[serde(deserialize_with = "one_or_many")]
+    pub hold: Vec<Key>,
+    #[serde(deserialize_with = "one_or_many")]
+    pub pause: Vec<Key>,
+    #[serde(deserialize_with = "one_or_many")]
+    pub restart: Vec<Key>,
+    #[serde(deserialize_with = "one_or_many")]
+    pub copy_fumen: Vec<Key>,
+    #[serde(deserialize_with = "one_or_many")]
+    pub quit: Vec<Key>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            move_left: vec![Key::new(KeyCode::Left)],
+            move_right: vec![Key::new(KeyCode::Right)],
+            soft_drop: vec![Key::new(KeyCode::Down)],
+            hard_drop: vec![Key::char(' ')],
+            rotate_cw: vec![Key::new(KeyCode::Up), Key::char('x')],
+            rotate_ccw: vec![Key::char('z')],
+            rotate_180: vec![Key::char('a')],
+            hold: vec![Key::char('c')],
+            pause: vec![Key::new(KeyCode::Esc), Key::char('p')],
+            restart: vec![Key::ctrl('r')],
+            copy_fumen: vec![Key::char('f')],
+            quit: vec![Key::ctrl('c')],
+        }
+    }
+}
+
+impl KeyBindings {
+    /// WASD to move and drop, Q and E to rotate, the left player of split screen games.
+    pub fn left_player() -> Self {
+        Self {
+            move_left: vec![Key::char('a')],
+            move_right: vec![Key::char('d')],
+            soft_drop: vec![Key::char('s')],
+            hard_drop: vec![Key::char('w')],
+            rotate_cw: vec![Key::char('e')],
+            rotate_ccw: vec![Key::char('q')],
+            hold: vec![Key::char('c')],
+            ..Self::unbound()
+        }
+    }
+
+    /// The arrow keys to move and drop, comma and period to rotate, the right player of split
+    /// screen games.
+    pub fn right_player() -> Self {
+        Self {
+            move_left: vec![Key::new(KeyCode::Left)],
+            move_right: vec![Key::new(KeyCode::Right)],
+            soft_drop: vec![Key::new(KeyCode::Down)],
+            hard_drop: vec![Key::new(KeyCode::Up)],
+            rotate_cw: vec![Key::char('.')],
+            rotate_ccw: vec![Key::char(',')],
+            hold: vec![Key::char('-')],
+            ..Self::unbound()
+        }
+    }
+
+    fn unbound() -> Self {
+        Self {
+            move_left: Vec::new(),
+            move_right: Vec::new(),
+            soft_drop: Vec::new(),
+            hard_drop: Vec::new(),
+            rotate_cw: Vec::new(),
+            rotate_ccw: Vec::new(),
+            rotate_180: Vec::new(),
+            hold: Vec::new(),
+            pause: Vec::new(),
+            restart: Vec::new(),
+            copy_fumen: Vec::new(),
+            quit: Vec::new(),
+        }
+    }
+
+    pub fn keys(&self, action: Action) -> &[Key] {
+        match action {
+            Action::MoveLeft => &self.move_left,
+            Action::MoveRight => &self.move_right,
+            Action::SoftDrop => &self.soft_drop,
+            Action::HardDrop => &self.hard_drop,
+            Action::RotateCW => &self.rotate_cw,
+            Action::RotateCCW => &self.rotate_ccw,
+            Action::Rotate180 => &self.rotate_180,
+            Action::Hold => &self.hold,
+            Action::Pause => &self.pause,
+            Action::Restart => &self.restart,
+            Action::CopyFumen => &self.copy_fumen,
+            Action::Quit => &self.quit,
+        }
+    }
+
+    pub fn keys_mut(&mut self, action: Action) -> &mut Vec<Key> {
+        match action {
+            Action::MoveLeft => &mut self.move_left,
+            Action::MoveRight => &mut self.move_right,
+            Action::SoftDrop => &mut self.soft_drop,
+            Action::HardDrop => &mut self.hard_drop,
+            Action::RotateCW => &mut self.rotate_cw,
+            Action::RotateCCW => &mut self.rotate_ccw,
+            Action::Rotate180 => &mut self.rotate_180,
+            Action::Hold => &mut self.hold,
+            Action::Pause => &mut self.pause,
+            Action::Restart => &mut self.restart,
+            Action::CopyFumen => &mut self.copy_fumen,
+            Action::Quit => &mut self.quit,
+        }
+    }
+
+    pub fn action_for(&self, event: &KeyEvent) -> Option<Action> {
+        self.action_for_key(&Key::from_event(event))
+    }
+
+    pub fn action_for_key(&self, key: &Key) -> Option<Action> {
+        Action::ALL.iter().copied().find(|&action| self.keys(action).contains(key))
+    }
+
+    /// Binds the key to the action and takes it away from any other action.
+    pub fn bind(&mut self, action: Action, key: Key) -> Result<(), String> {
+        if action != Action::Pause && self.pause == [key] {
+            return Err(format!("'{}' is the only key for pause", key));
+        }
+
+        for &other in Action::ALL.iter() {
+            self.keys_mut(other).retain(|&bound| bound != key);
+        }
+        self.keys_mut(action).push(key);
+        Ok(())
+    }
+
+    pub fn describe(&self, action: Action) -> String {
+        let keys: Vec<_> = self.keys(action).iter().map(Key::to_string).collect();
+        if keys.is_empty() {
+            "unbound".to_string()
+        } else {
+            keys.join(", ")
+        }
+    }
+
+    pub fn help(&self) -> Vec<String> {
+        Action::ALL.iter()
+            .filter(|&&action| !self.keys(action).is_empty())
+            .map(|&action| format!("{} - {}", self.describe(action), action.name()))
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.pause.is_empty() {
+            return Err("pause needs at least one key binding".to_string());
+        }
+        self.check_duplicates()
+    }
+
+    fn check_duplicates(&self) -> Result<(), String> {
+        for (index, &action) in Action::ALL.iter().enumerate() {
+            for key in self.keys(action) {
+                if let Some(other) = Action::ALL[index + 1..].iter().find(|&&other| self.keys(other).contains(key)) {
+                    return Err(format!("key '{}' is bound to both {} and {}", key, action.name(), other.name()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A list of keys as written in the config file, a single key or a list.
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+struct KeyList(Vec<Key>);
+
+impl<'de> Deserialize<'de> for KeyList {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        one_or_many(deserializer).map(KeyList)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SplitScreenKeys {
+    left: BTreeMap<String, KeyList>,
+    right: BTreeMap<String, KeyList>,
+}
+
+fn apply_overrides(mut bindings: KeyBindings, overrides: BTreeMap<String, KeyList>) -> Result<KeyBindings, String> {
+    for (name, KeyList(keys)) in overrides {
+        let action = Action::ALL.iter().copied()
+            .filter(|action| action.is_piece_action())
+            .find(|action| action.config_name() == name)
+            .ok_or_else(|| format!("unknown action '{}' for a player", name))?;
+        *bindings.keys_mut(action) = keys;
+    }
+    Ok(bindings)
+}
+
+fn overrides(bindings: &KeyBindings) -> BTreeMap<String, KeyList> {
+    Action::ALL.iter()
+        .filter(|action| action.is_piece_action())
+        .map(|&action| (action.config_name().to_string(), KeyList(bindings.keys(action).to_vec())))
+        .collect()
+}
+
+/// The keys of both players in split screen games, pause, restart and quit use the normal
+/// bindings. Actions missing in the config keep their default keys.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "SplitScreenKeys", into = "SplitScreenKeys")]
+pub struct TwoPlayerKeys {
+    pub left: KeyBindings,
+    pub right: KeyBindings,
+}
+
+impl Default for TwoPlayerKeys {
+    fn default() -> Self {
+        Self {
+            left: KeyBindings::left_player(),
+            right: KeyBindings::right_player(),
+        }
+    }
+}
+
+impl TryFrom<SplitScreenKeys> for TwoPlayerKeys {
+    type Error = String;
+
+    fn try_from(keys: SplitScreenKeys) -> Result<Self, Self::Error> {
+        Ok(Self {
+            left: apply_overrides(KeyBindings::left_player(), keys.left)?,
+            right: apply_overrides(KeyBindings::right_player(), keys.right)?,
+        })
+    }
+}
+
+impl From<TwoPlayerKeys> for SplitScreenKeys {
+    fn from(keys: TwoPlayerKeys) -> Self {
+        Self {
+            left: overrides(&keys.left),
+            right: overrides(&keys.right),
+        }
+    }
+}
+
+impl TwoPlayerKeys {
+    /// Keys must not be shared between the players or with the keys that control the game.
+    pub fn validate(&self, shared: &KeyBindings) -> Result<(), String> {
+        self.left.check_duplicates().map_err(|error| format!("left player: {}", error))?;
+        self.right.check_duplicates().map_err(|error| format!("right player: {}", error))?;
+
+        for &action in Action::ALL.iter().filter(|action| action.is_piece_action()) {
+            for key in self.left.keys(action) {
+                if self.right.action_for_key(key).is_some() {
+                    return Err(format!("key '{}' is bound for both players", key));
+                }
+            }
+            for key in self.left.keys(action).iter().chain(self.right.keys(action)) {
+                if let Some(other) = shared.action_for_key(key).filter(|other| !other.is_piece_action()) {
+                    return Err(format!("key '{}' of a player is already bound to {}", key, other.name()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::settings::{Action, Key, KeyBindings, TwoPlayerKeys};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    #[test]
+    fn several_keys_per_action() {
+        let bindings = KeyBindings::default();
+        assert_eq!(bindings.action_for(&KeyEvent::from(KeyCode::Up)), Some(Action::RotateCW));
+        assert_eq!(bindings.action_for(&KeyEvent::from(KeyCode::Char('x'))), Some(Action::RotateCW));
+        assert_eq!(bindings.action_for(&KeyEvent::from(KeyCode::Char('q'))), None);
+        assert_eq!(bindings.action_for(&KeyEvent { code: KeyCode::Char('c'), modifiers: KeyModifiers::CONTROL }), Some(Action::Quit));
+        assert_eq!(bindings.help()[4], "up, x - rotate clockwise");
+    }
+
+    #[test]
+    fn binding_moves_the_key() {
+        let mut bindings = KeyBindings::default();
+        assert_eq!(bindings.bind(Action::Hold, Key::char('x')), Ok(()));
+        assert_eq!(bindings.rotate_cw, vec![Key::new(KeyCode::Up)]);
+        assert_eq!(bindings.hold, vec![Key::char('c'), Key::char('x')]);
+        assert_eq!(bindings.validate(), Ok(()));
+
+        bindings.pause = vec![Key::char('p')];
+        assert!(bindings.bind(Action::Hold, Key::char('p')).is_err());
+        assert_eq!(bindings.pause, vec![Key::char('p')]);
+
+        bindings.rotate_ccw.push(Key::char('c'));
+        assert_eq!(bindings.validate(), Err("key 'c' is bound to both rotate counterclockwise and hold".to_string()));
+    }
+
+    #[test]
+    fn split_screen_keys() {
+        let keys = TwoPlayerKeys::default();
+        assert_eq!(keys.left.action_for(&KeyEvent::from(KeyCode::Char('w'))), Some(Action::HardDrop));
+        assert_eq!(keys.right.action_for(&KeyEvent::from(KeyCode::Char(','))), Some(Action::RotateCCW));
+        assert_eq!(keys.validate(&KeyBindings::default()), Ok(()));
+
+        let mut clashing = keys.clone();
+        clashing.right.hold = vec![Key::char('q')];
+        assert_eq!(clashing.validate(&KeyBindings::default()), Err("key 'q' is bound for both players".to_string()));
+        clashing.right.hold = vec![Key::char('p')];
+        assert_eq!(clashing.validate(&KeyBindings::default()), Err("key 'p' of a player is already bound to pause".to_string()));
+    }
+}
@@ -0,0 +1,314 @@
+use crate::bot::ai::{Difficulty, Weights};
+use crate::config::bindings::{Action, KeyBindings, TwoPlayerKeys};
+use crate::config::keys::Key;
+use crate::engine::mechanics::Mode;
+use crate::rendering::renderer::RenderMode;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const MAX_NEXT_QUEUE_LENGTH: usize = 6;
+pub const MIN_FRAME_RATE: u32 = 5;
+pub const MAX_FRAME_RATE: u32 = 240;
+/// The fastest the garbage of the survival mode may rise, in milliseconds.
+pub const MIN_RISE_INTERVAL: u64 = 100;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Handling {
+    pub das: u64,
+    pub arr: u64,
+    pub sdf: u32,
+}
+
+impl Default for Handling {
+    fn default() -> Self {
+        Self {
+            das: 167,
+            arr: 33,
+            sdf: 6,
+        }
+    }
+}
+
+impl Handling {
+    pub fn delayed_auto_shift(&self) -> Duration {
+        Duration::from_millis(self.das)
+    }
+
+    pub fn auto_repeat_rate(&self) -> Duration {
+        Duration::from_millis(self.arr)
+    }
+}
+
+/// The tempo of the survival mode: pairs of the seconds survived and the milliseconds between
+/// rising garbage rows from then on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Survival {
+    pub tempo: Vec<(u64, u64)>,
+}
+
+impl Default for Survival {
+    fn default() -> Self {
+        Self {
+            tempo: vec![(0, 4000), (30, 3000), (60, 2500), (90, 2000), (120, 1500), (180, 1000), (300, 700)],
+        }
+    }
+}
+
+impl Survival {
+    fn validate(&self) -> Result<(), String> {
+        if self.tempo.first().map(|&(second, _)| second) != Some(0) {
+            return Err("the survival tempo has to start at second 0".to_string());
+        }
+        if self.tempo.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+            return Err("the seconds of the survival tempo have to increase".to_string());
+        }
+        if self.tempo.iter().any(|&(_, interval)| interval < MIN_RISE_INTERVAL) {
+            return Err(format!("garbage can rise at most every {} milliseconds", MIN_RISE_INTERVAL));
+        }
+        Ok(())
+    }
+
+    /// The time between rising rows once the player survived for the given time.
+    pub fn rise_interval(&self, survived: Duration) -> Duration {
+        let step = self.tempo.iter().take_while(|&&(second, _)| Duration::from_secs(second) <= survived).last();
+        Duration::from_millis(step.or_else(|| self.tempo.first()).map_or(MIN_RISE_INTERVAL, |&(_, interval)| interval.max(MIN_RISE_INTERVAL)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub theme: String,
+    pub render_mode: RenderMode,
+    pub ghost: bool,
+    pub next_queue_length: usize,
+    pub default_mode: Mode,
+    pub frame_rate: u32,
+    pub cpu_difficulty: Difficulty,
+    /// The weights of the built-in bot, e.g. the ones `tet-rs tune` found.
+    pub bot_weights: Weights,
+    pub handling: Handling,
+    pub keys: KeyBindings,
+    pub two_player: TwoPlayerKeys,
+    pub survival: Survival,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: "classic".to_string(),
+            render_mode: RenderMode::default(),
+            ghost: true,
+            next_queue_length: 1,
+            default_mode: Mode::default(),
+            frame_rate: 30,
+            cpu_difficulty: Difficulty::default(),
+            bot_weights: Weights::default(),
+            handling: Handling::default(),
+            keys: KeyBindings::default(),
+            two_player: TwoPlayerKeys::default(),
+            survival: Survival::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Invalid(PathBuf, String),
+    Serialize(toml::ser::Error),
+    NoConfigDirectory,
+}
+
+/// What can be wrong with the contents of a config file, wherever they come from.
+enum ContentError {
+    Parse(toml::de::Error),
+    Invalid(String),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(path, error) => write!(f, "could not access config file {}: {}", path.display(), error),
+            ConfigError::Parse(path, error) => write!(f, "invalid config file {}: {}", path.display(), error),
+            ConfigError::Invalid(path, reason) => write!(f, "invalid config file {}: {}", path.display(), reason),
+            ConfigError::Serialize(error) => write!(f, "could not serialize config: {}", error),
+            ConfigError::NoConfigDirectory => write!(f, "could not determine the config directory"),
+        }
+    }
+}
+
+impl Config {
+    pub fn directory() -> Option<PathBuf> {
+        dirs::config_dir().map(|directory| directory.join("tet-rs"))
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        Self::directory().map(|directory| directory.join("config.toml"))
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, String> {
+        Self::parse(content).map_err(|error| match error {
+            ContentError::Parse(error) => error.to_string(),
+            ContentError::Invalid(reason) => reason,
+        })
+    }
+
+    fn parse(content: &str) -> Result<Self, ContentError> {
+        let config: Config = toml::from_str(content).map_err(ContentError::Parse)?;
+        config.validate().map_err(ContentError::Invalid)?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(MIN_FRAME_RATE..=MAX_FRAME_RATE).contains(&self.frame_rate) {
+            return Err(format!("frame_rate has to be between {} and {}", MIN_FRAME_RATE, MAX_FRAME_RATE));
+        }
+
+        if self.next_queue_length > MAX_NEXT_QUEUE_LENGTH {
+            return Err(format!("next_queue_length can be at most {}", MAX_NEXT_QUEUE_LENGTH));
+        }
+
+        self.keys.validate()?;
+        self.two_player.validate(&self.keys)?;
+        self.survival.validate()?;
+
+        Ok(())
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, ConfigError> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(ConfigError::Io(path.to_path_buf(), error)),
+        };
+
+        Self::parse(&content).map_err(|error| match error {
+            ContentError::Parse(error) => ConfigError::Parse(path.to_path_buf(), error),
+            ContentError::Invalid(reason) => ConfigError::Invalid(path.to_path_buf(), reason),
+        })
+    }
+
+    pub fn load() -> Result<Self, ConfigError> {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), ConfigError> {
+        let content = toml::to_string_pretty(self).map_err(ConfigError::Serialize)?;
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory).map_err(|error| ConfigError::Io(directory.to_path_buf(), error))?;
+        }
+        std::fs::write(path, content).map_err(|error| ConfigError::Io(path.to_path_buf(), error))
+    }
+
+    pub fn save(&self) -> Result<(), ConfigError> {
+        let path = Self::path().ok_or(ConfigError::NoConfigDirectory)?;
+        self.save_to(&path)
+    }
+
+    /// Binds the key to the action like `KeyBindings::bind`, unless it's one of the split-screen
+    /// keys of a player and the action isn't a piece action, which the saved config wouldn't load.
+    pub fn bind_key(&mut self, action: Action, key: Key) -> Result<(), String> {
+        let mut keys = self.keys.clone();
+        keys.bind(action, key)?;
+        self.two_player.validate(&keys)?;
+        self.keys = keys;
+        Ok(())
+    }
+
+    pub fn frame_duration(&self) -> Duration {
+        Duration::from_millis(1000 / self.frame_rate.clamp(MIN_FRAME_RATE, MAX_FRAME_RATE) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bot::ai::Difficulty;
+    use crate::config::settings::{Action, Config, ConfigError, Key};
+    use crate::engine::mechanics::Mode;
+    use crate::rendering::renderer::RenderMode;
+    use std::time::Duration;
+
+    #[test]
+    fn round_trip() {
+        let mut config = Config {
+            theme: "nord".to_string(),
+            render_mode: RenderMode::Ansi256,
+            default_mode: Mode::FourWide,
+            cpu_difficulty: Difficulty::Hard,
+            ..Config::default()
+        };
+        config.keys.hold = vec![Key::char('h'), Key::ctrl('h')];
+
+        let serialized = toml::to_string_pretty(&config).unwrap();
+        assert_eq!(Config::from_toml(&serialized), Ok(config));
+    }
+
+    #[test]
+    fn missing_values_use_defaults() {
+        let config = Config::from_toml("ghost = false\n[handling]\ndas = 100\n").unwrap();
+        assert!(!config.ghost);
+        assert_eq!(config.handling.das, 100);
+        assert_eq!(config.handling.arr, Config::default().handling.arr);
+        assert_eq!(config.keys, Config::default().keys);
+    }
+
+    #[test]
+    fn readable_errors() {
+        let error = Config::from_toml("frame_rate = 1000").unwrap_err();
+        assert!(error.contains("frame_rate"), "{}", error);
+
+        let error = Config::from_toml("[keys]\nrotate_cw = \"hyper\"").unwrap_err();
+        assert!(error.contains("unknown key 'hyper'"), "{}", error);
+
+        let error = Config::from_toml("[two_player.left]\npause = \"x\"").unwrap_err();
+        assert!(error.contains("unknown action 'pause' for a player"), "{}", error);
+
+        let error = Config::from_toml("default_mode = \"sprint\"").unwrap_err();
+        assert!(error.contains("sprint"), "{}", error);
+
+        let error = Config::from_toml("[survival]\ntempo = [[10, 1000]]").unwrap_err();
+        assert!(error.contains("second 0"), "{}", error);
+        assert!(Config::from_toml("[survival]\ntempo = [[0, 1000], [0, 500]]").is_err());
+        assert!(Config::from_toml("[survival]\ntempo = [[0, 10]]").is_err());
+    }
+
+    #[test]
+    fn loading_tells_parse_errors_from_invalid_values() {
+        let path = std::env::temp_dir().join(format!("tet-rs-config-{}.toml", std::process::id()));
+        std::fs::write(&path, "frame_rate = \"fast\"").unwrap();
+        assert!(matches!(Config::load_from(&path), Err(ConfigError::Parse(..))));
+        std::fs::write(&path, "frame_rate = 1000").unwrap();
+        assert!(matches!(Config::load_from(&path), Err(ConfigError::Invalid(..))));
+        std::fs::write(&path, "frame_rate = 60").unwrap();
+        assert_eq!(Config::load_from(&path).unwrap().frame_rate, 60);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn survival_tempo_steps() {
+        let config = Config::from_toml("[survival]\ntempo = [[0, 2000], [10, 1000], [20, 500]]").unwrap();
+        let interval = |seconds| config.survival.rise_interval(Duration::from_secs(seconds)).as_millis();
+        assert_eq!((interval(0), interval(9), interval(10), interval(19), interval(600)), (2000, 2000, 1000, 1000, 500));
+    }
+
+    #[test]
+    fn rebinding_keeps_the_config_loadable() {
+        let mut config = Config::default();
+        let error = config.bind_key(Action::Restart, Key::char('a')).unwrap_err();
+        assert_eq!(error, "key 'a' of a player is already bound to restart");
+        assert_eq!(config, Config::default());
+
+        config.bind_key(Action::Hold, Key::char('a')).unwrap();
+        config.bind_key(Action::Restart, Key::char('y')).unwrap();
+        let saved = toml::to_string_pretty(&config).unwrap();
+        assert_eq!(Config::from_toml(&saved), Ok(config));
+    }
+}
@@ -0,0 +1,184 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+const MODIFIER_NAMES: [(&str, KeyModifiers); 4] = [
+    ("ctrl", KeyModifiers::CONTROL),
+    ("control", KeyModifiers::CONTROL),
+    ("alt", KeyModifiers::ALT),
+    ("shift", KeyModifiers::SHIFT),
+];
+
+impl Key {
+    pub fn new(code: KeyCode) -> Self {
+        Self::with_modifiers(code, KeyModifiers::empty())
+    }
+
+    pub fn char(character: char) -> Self {
+        Self::new(KeyCode::Char(character))
+    }
+
+    pub fn ctrl(character: char) -> Self {
+        Self::with_modifiers(KeyCode::Char(character), KeyModifiers::CONTROL)
+    }
+
+    /// Shift is already part of typed characters ('R' instead of 'r'), terminals disagree on
+    /// whether they report it as modifier as well, so it's ignored for characters.
+    pub fn with_modifiers(code: KeyCode, mut modifiers: KeyModifiers) -> Self {
+        if let KeyCode::Char(_) = code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self { code, modifiers }
+    }
+
+    pub fn from_event(event: &KeyEvent) -> Self {
+        Self::with_modifiers(event.code, event.modifiers)
+    }
+
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        *self == Self::from_event(event)
+    }
+
+    fn code_name(&self) -> String {
+        match self.code {
+            KeyCode::Left => "left".to_string(),
+            KeyCode::Right => "right".to_string(),
+            KeyCode::Up => "up".to_string(),
+            KeyCode::Down => "down".to_string(),
+            KeyCode::Enter => "enter".to_string(),
+            KeyCode::Esc => "esc".to_string(),
+            KeyCode::Tab => "tab".to_string(),
+            KeyCode::BackTab => "backtab".to_string(),
+            KeyCode::Backspace => "backspace".to_string(),
+            KeyCode::Delete => "delete".to_string(),
+            KeyCode::Insert => "insert".to_string(),
+            KeyCode::Home => "home".to_string(),
+            KeyCode::End => "end".to_string(),
+            KeyCode::PageUp => "pageup".to_string(),
+            KeyCode::PageDown => "pagedown".to_string(),
+            KeyCode::F(number) => format!("f{}", number),
+            KeyCode::Char(' ') => "space".to_string(),
+            KeyCode::Char(character) => character.to_string(),
+            KeyCode::Null => "null".to_string(),
+        }
+    }
+
+    fn parse_code(value: &str) -> Option<KeyCode> {
+        let code = match value.to_lowercase().as_str() {
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" | "ins" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "space" => KeyCode::Char(' '),
+            lower => {
+                let mut characters = value.chars();
+                match (characters.next(), characters.next()) {
+                    (Some(character), None) => KeyCode::Char(character),
+                    _ => match lower.strip_prefix('f').and_then(|number| number.parse().ok()) {
+                        Some(number) if (1..=24).contains(&number) => KeyCode::F(number),
+                        _ => return None,
+                    }
+                }
+            }
+        };
+
+        Some(code)
+    }
+}
+
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for &(name, modifier) in [MODIFIER_NAMES[0], MODIFIER_NAMES[2], MODIFIER_NAMES[3]].iter() {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        write!(f, "{}", self.code_name())
+    }
+}
+
+impl From<Key> for String {
+    fn from(key: Key) -> Self {
+        key.to_string()
+    }
+}
+
+impl TryFrom<String> for Key {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let mut rest = value.as_str();
+        let mut modifiers = KeyModifiers::empty();
+
+        'modifiers: loop {
+            for &(name, modifier) in MODIFIER_NAMES.iter() {
+                let prefix_length = name.len() + 1;
+                let is_prefix = rest.len() > prefix_length
+                    && rest.is_char_boundary(prefix_length)
+                    && rest[..prefix_length].eq_ignore_ascii_case(&format!("{}+", name));
+                if is_prefix {
+                    modifiers.insert(modifier);
+                    rest = &rest[prefix_length..];
+                    continue 'modifiers;
+                }
+            }
+            break;
+        }
+
+        match Key::parse_code(rest) {
+            Some(code) => Ok(Key::with_modifiers(code, modifiers)),
+            None => Err(format!("unknown key '{}'", value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::settings::Key;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn parse_and_print_keys() {
+        for name in &["left", "space", "f5", "esc", "x", "X", ",", "+", "ctrl+r", "ctrl+alt+shift+left", "alt++"] {
+            let key = Key::try_from(name.to_string()).unwrap();
+            assert_eq!(key.to_string(), *name);
+        }
+
+        assert_eq!(Key::try_from("Escape".to_string()), Ok(Key::new(KeyCode::Esc)));
+        assert_eq!(Key::try_from("Control+R".to_string()), Ok(Key::ctrl('R')));
+        assert_eq!(Key::try_from("shift+a".to_string()), Ok(Key::char('a')));
+        assert!(Key::try_from("f25".to_string()).is_err());
+        assert!(Key::try_from("shift".to_string()).is_err());
+        assert!(Key::try_from("ctrl+".to_string()).is_err());
+    }
+
+    #[test]
+    fn match_events() {
+        let ctrl_r = KeyEvent { code: KeyCode::Char('r'), modifiers: KeyModifiers::CONTROL };
+        let shifted = KeyEvent { code: KeyCode::Char('R'), modifiers: KeyModifiers::SHIFT };
+
+        assert!(Key::ctrl('r').matches(&ctrl_r));
+        assert!(!Key::char('r').matches(&ctrl_r));
+        assert!(Key::char('R').matches(&shifted));
+        assert!(!Key::new(KeyCode::Left).matches(&KeyEvent::from(KeyCode::Right)));
+    }
+}
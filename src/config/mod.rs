@@ -0,0 +1,9 @@
+mod bindings;
+mod config;
+mod keys;
+
+pub mod settings {
+    pub use super::bindings::{Action, KeyBindings, TwoPlayerKeys};
+    pub use super::config::{Config, ConfigError, Handling, Survival, MAX_NEXT_QUEUE_LENGTH, MIN_FRAME_RATE, MAX_FRAME_RATE, MIN_RISE_INTERVAL};
+    pub use super::keys::Key;
+}
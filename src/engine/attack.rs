@@ -0,0 +1,151 @@
+use crate::engine::field::Field;
+use crate::engine::stones::{Stone, StoneKind};
+
+/// Extra garbage for consecutive line clears, indexed by the number of clears before this one.
+pub const COMBO_ATTACK: [u32; 11] = [0, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5];
+pub const BACK_TO_BACK_BONUS: u32 = 1;
+pub const PERFECT_CLEAR_ATTACK: u32 = 10;
+
+/// Garbage lines sent for clearing the given number of lines at once.
+pub fn line_clear_attack(lines: usize) -> u32 {
+    match lines {
+        0 | 1 => 0,
+        2 => 1,
+        3 => 2,
+        _ => 4,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TSpin {
+    #[default]
+    None,
+    Mini,
+    Full,
+}
+
+/// What locking a piece did to the field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Clear {
+    pub lines: usize,
+    pub t_spin: TSpin,
+    pub perfect_clear: bool,
+}
+
+impl Clear {
+    /// Tetrises and line clearing T-spins, they continue back-to-back chains.
+    pub fn is_difficult(&self) -> bool {
+        self.lines >= 4 || (self.lines > 0 && self.t_spin != TSpin::None)
+    }
+}
+
+/// Classifies a T that's about to lock with the three corner rule, `rotated_last` tells whether
+/// the last successful input was a rotation. Walls and the floor count as filled corners.
+pub fn t_spin(field: &Field, stone: &Stone, rotated_last: bool) -> TSpin {
+    if stone.kind != StoneKind::T || !rotated_last {
+        return TSpin::None;
+    }
+
+    let filled = |x: i32, y: i32| {
+        let board = field.board();
+        x < 0 || x >= board.width() as i32 || y >= board.height() as i32 || board.is_occupied(x, y)
+    };
+    let (center_x, center_y) = (stone.position.x + 1, stone.position.y + 1);
+    let corners = [(-1, -1), (1, -1), (1, 1), (-1, 1)];
+    let is_filled: Vec<bool> = corners.iter().map(|&(x, y)| filled(center_x + x, center_y + y)).collect();
+    if is_filled.iter().filter(|&&filled| filled).count() < 3 {
+        return TSpin::None;
+    }
+
+    // the two corners on the side the T points to, in the order of `corners`
+    let front = [(0, 1), (1, 2), (2, 3), (3, 0)][stone.rotation as usize % 4];
+    if is_filled[front.0] && is_filled[front.1] {
+        TSpin::Full
+    } else {
+        TSpin::Mini
+    }
+}
+
+/// Keeps track of combos and back-to-back chains to compute the attack of every clear.
+#[derive(Debug, Clone, Default)]
+pub struct AttackTable {
+    combo: Option<usize>,
+    back_to_back: bool,
+}
+
+impl AttackTable {
+    /// Number of consecutive clears before the latest one, `None` when the latest piece didn't clear.
+    pub fn combo(&self) -> Option<usize> {
+        self.combo
+    }
+
+    pub fn back_to_back(&self) -> bool {
+        self.back_to_back
+    }
+
+    pub fn attack(&mut self, clear: &Clear) -> u32 {
+        if clear.lines == 0 {
+            self.combo = None;
+            return 0;
+        }
+
+        let combo = self.combo.map_or(0, |combo| combo + 1);
+        self.combo = Some(combo);
+
+        let mut attack = match clear.t_spin {
+            TSpin::None => line_clear_attack(clear.lines),
+            TSpin::Mini => clear.lines as u32 - 1,
+            TSpin::Full => 2 * clear.lines as u32,
+        };
+        if clear.is_difficult() && self.back_to_back {
+            attack += BACK_TO_BACK_BONUS;
+        }
+        self.back_to_back = clear.is_difficult();
+
+        attack += COMBO_ATTACK[combo.min(COMBO_ATTACK.len() - 1)];
+        if clear.perfect_clear {
+            attack += PERFECT_CLEAR_ATTACK;
+        }
+        attack
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::mechanics::{t_spin, AttackTable, Cell, Clear, Field, Stone, StoneKind, TSpin};
+    use crate::rendering::renderer::Position;
+
+    fn clear(lines: usize, t_spin: TSpin) -> Clear {
+        Clear { lines, t_spin, perfect_clear: false }
+    }
+
+    #[test]
+    fn guideline_attacks() {
+        let mut table = AttackTable::default();
+        assert_eq!(table.attack(&clear(4, TSpin::None)), 4);
+        assert_eq!(table.attack(&clear(2, TSpin::Full)), 4 + 1 + 1);
+        assert_eq!(table.attack(&clear(1, TSpin::None)), 1);
+        assert_eq!(table.attack(&clear(4, TSpin::None)), 4 + 2);
+        assert_eq!(table.attack(&clear(0, TSpin::None)), 0);
+        assert_eq!(table.combo(), None);
+        assert_eq!(table.attack(&clear(2, TSpin::Mini)), 1 + 1);
+        assert_eq!(table.attack(&Clear { lines: 2, t_spin: TSpin::None, perfect_clear: true }), 1 + 1 + 10);
+    }
+
+    #[test]
+    fn three_corner_rule() {
+        let mut field = Field::default();
+        for (x, y) in [(0, 17), (2, 17), (0, 19), (2, 19)].iter() {
+            field.set_cell_at_pos(&Position { x: *x, y: *y }, Cell::Garbage);
+        }
+
+        let mut t_stone = Stone::new(Position { x: 0, y: 17 }, StoneKind::T);
+        t_stone.rotation = 2;
+        assert_eq!(t_spin(&field, &t_stone, true), TSpin::Full);
+        assert_eq!(t_spin(&field, &t_stone, false), TSpin::None);
+
+        t_stone.rotation = 0;
+        field.set_cell_at_pos(&Position { x: 2, y: 17 }, Cell::Empty);
+        assert_eq!(t_spin(&field, &t_stone, true), TSpin::Mini);
+    }
+}
@@ -0,0 +1,219 @@
+use crate::engine::stones::Shape;
+use crate::rendering::renderer::Position;
+
+pub type Row = u32;
+
+pub const MAX_WIDTH: usize = Row::BITS as usize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Board {
+    width: usize,
+    full_row: Row,
+    rows: Vec<Row>,
+}
+
+fn shift(mask: Row, x: i32) -> Row {
+    if x >= 0 { mask << x } else { mask >> -x }
+}
+
+impl Board {
+    pub fn new(width: usize, height: usize) -> Self {
+        assert!(width > 0 && width <= MAX_WIDTH, "board width has to be between 1 and {}", MAX_WIDTH);
+
+        Self {
+            width,
+            full_row: Row::MAX >> (MAX_WIDTH - width),
+            rows: vec![0; height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    pub fn row(&self, y: usize) -> Row {
+        self.rows[y]
+    }
+
+    pub fn is_occupied(&self, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.rows.len() {
+            return false;
+        }
+
+        self.rows[y as usize] & (1 << x) != 0
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, occupied: bool) {
+        if occupied {
+            self.rows[y] |= 1 << x;
+        } else {
+            self.rows[y] &= !(1 << x);
+        }
+    }
+
+    pub fn collides(&self, shape: &Shape, position: &Position) -> bool {
+        if position.x + shape.min_x < 0 || (position.x + shape.max_x) as usize >= self.width {
+            return true;
+        }
+
+        (shape.min_y..=shape.max_y).any(|row| {
+            let y = position.y + row;
+            if y < 0 {
+                return false;
+            }
+
+            match self.rows.get(y as usize) {
+                Some(board_row) => board_row & shift(shape.rows[row as usize], position.x) != 0,
+                None => true,
+            }
+        })
+    }
+
+    pub fn drop_distance(&self, shape: &Shape, position: &Position) -> i32 {
+        let mut distance = 0;
+        let mut below = *position;
+        below.move_down();
+
+        while !self.collides(shape, &below) {
+            distance += 1;
+            below.move_down();
+        }
+
+        distance
+    }
+
+    pub fn landing_height(&self, shape: &Shape, position: &Position) -> i32 {
+        let distance = self.drop_distance(shape, position);
+        self.rows.len() as i32 - (position.y + distance + shape.min_y)
+    }
+
+    pub fn place(&mut self, shape: &Shape, position: &Position) {
+        for row in shape.min_y..=shape.max_y {
+            let y = position.y + row;
+            if y >= 0 && (y as usize) < self.rows.len() {
+                self.rows[y as usize] |= shift(shape.rows[row as usize], position.x) & self.full_row;
+            }
+        }
+    }
+
+    pub fn is_row_full(&self, y: usize) -> bool {
+        self.rows[y] == self.full_row
+    }
+
+    pub fn full_rows(&self) -> Vec<usize> {
+        (0..self.rows.len()).filter(|&y| self.is_row_full(y)).collect()
+    }
+
+    pub fn clear_lines(&mut self) -> usize {
+        let height = self.rows.len();
+        let full_row = self.full_row;
+        self.rows.retain(|&row| row != full_row);
+
+        let cleared = height - self.rows.len();
+        let mut rows = vec![0; cleared];
+        rows.append(&mut self.rows);
+        self.rows = rows;
+        cleared
+    }
+
+    /// Pushes the stack up and fills the bottom with copies of the row. Returns `false` if occupied
+    /// rows were pushed out of the board.
+    pub fn insert_rows(&mut self, count: usize, row: Row) -> bool {
+        let count = count.min(self.rows.len());
+        let fits = self.rows[..count].iter().all(|&pushed_out| pushed_out == 0);
+        self.rows.drain(..count);
+        self.rows.extend(std::iter::repeat_n(row & self.full_row, count));
+        fits
+    }
+
+    pub fn column_height(&self, x: usize) -> usize {
+        let mask = 1 << x;
+        self.rows.iter()
+            .position(|row| row & mask != 0)
+            .map_or(0, |top| self.rows.len() - top)
+    }
+
+    pub fn column_heights(&self) -> Vec<usize> {
+        (0..self.width).map(|x| self.column_height(x)).collect()
+    }
+
+    pub fn holes(&self) -> u32 {
+        let mut covered = 0;
+        self.rows.iter().map(|&row| {
+            let holes = (covered & !row).count_ones();
+            covered |= row;
+            holes
+        }).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.iter().all(|&row| row == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::board::Board;
+    use crate::engine::stones::{Stone, StoneKind};
+    use crate::rendering::renderer::Position;
+
+    #[test]
+    fn collision_with_walls_and_floor() {
+        let board = Board::new(10, 20);
+        let stone = Stone::new(Position { x: 0, y: 0 }, StoneKind::T);
+
+        assert!(!board.collides(stone.shape(), &stone.position));
+        assert!(board.collides(stone.shape(), &Position { x: -1, y: 0 }));
+        assert!(board.collides(stone.shape(), &Position { x: 8, y: 0 }));
+        assert!(!board.collides(stone.shape(), &Position { x: 7, y: 18 }));
+        assert!(board.collides(stone.shape(), &Position { x: 7, y: 19 }));
+        assert!(!board.collides(stone.shape(), &Position { x: 0, y: -5 }));
+    }
+
+    #[test]
+    fn negative_offsets_inside_the_box() {
+        let board = Board::new(10, 20);
+        let mut stone = Stone::new(Position { x: 0, y: 0 }, StoneKind::I);
+        stone.rotation = 1;
+
+        assert!(!board.collides(stone.shape(), &Position { x: -2, y: 0 }));
+        assert!(board.collides(stone.shape(), &Position { x: -3, y: 0 }));
+    }
+
+    #[test]
+    fn drop_and_place() {
+        let mut board = Board::new(10, 20);
+        let stone = Stone::new(Position { x: 0, y: 0 }, StoneKind::O);
+
+        assert_eq!(board.drop_distance(stone.shape(), &stone.position), 18);
+        assert_eq!(board.landing_height(stone.shape(), &stone.position), 2);
+        board.place(stone.shape(), &Position { x: 0, y: 18 });
+        assert!(board.is_occupied(1, 19));
+        assert_eq!(board.drop_distance(stone.shape(), &stone.position), 16);
+        assert_eq!(board.column_heights()[..3], [2, 2, 0]);
+    }
+
+    #[test]
+    fn clear_lines_and_count_holes() {
+        let mut board = Board::new(4, 6);
+        (0..4).for_each(|x| board.set(x, 5, true));
+        (0..4).filter(|&x| x != 1).for_each(|x| board.set(x, 4, true));
+        board.set(1, 2, true);
+
+        assert_eq!(board.holes(), 2);
+        assert_eq!(board.full_rows(), vec![5]);
+        assert_eq!(board.clear_lines(), 1);
+        assert_eq!(board.rows()[5], 0b1101);
+        assert_eq!(board.rows()[3], 0b0010);
+        assert_eq!(board.holes(), 2);
+        assert!(!board.is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+/// How many garbage rows a dig race keeps on the field while there's garbage left to send.
+pub const DIG_ROWS: u32 = 10;
+
+/// The usual totals of garbage to dig through.
+pub const DIG_TARGETS: [u32; 3] = [10, 18, 100];
+
+/// Counts the garbage of a dig race: the field starts with `DIG_ROWS` rows and is topped up from
+/// below as they're cleared until the target total was sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigRace {
+    target: u32,
+    sent: u32,
+    on_field: u32,
+}
+
+impl DigRace {
+    pub fn new(target: u32) -> Self {
+        Self { target: target.max(1), sent: 0, on_field: 0 }
+    }
+
+    pub fn target(&self) -> u32 {
+        self.target
+    }
+
+    /// Takes the number of garbage rows left on the field and returns how many to push in.
+    pub fn refill(&mut self, on_field: usize) -> usize {
+        let on_field = (on_field as u32).min(self.sent);
+        let rows = DIG_ROWS.saturating_sub(on_field).min(self.target - self.sent);
+        self.sent += rows;
+        self.on_field = on_field + rows;
+        rows as usize
+    }
+
+    pub fn cleared(&self) -> u32 {
+        self.sent - self.on_field
+    }
+
+    pub fn left(&self) -> u32 {
+        self.target - self.cleared()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.left() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::mechanics::{DigRace, DIG_ROWS};
+
+    #[test]
+    fn garbage_is_topped_up_until_the_target() {
+        let mut race = DigRace::new(18);
+        assert_eq!(race.refill(0), DIG_ROWS as usize);
+        assert_eq!((race.cleared(), race.left()), (0, 18));
+
+        assert_eq!(race.refill(7), 3);
+        assert_eq!((race.cleared(), race.left()), (3, 15));
+        assert_eq!(race.refill(2), 5);
+        assert_eq!((race.cleared(), race.left()), (11, 7));
+        assert!(!race.is_finished());
+        assert_eq!(race.refill(0), 0);
+        assert!(race.is_finished());
+
+        let mut short = DigRace::new(4);
+        assert_eq!(short.refill(0), 4);
+        assert_eq!(short.refill(4), 0);
+        assert_eq!(short.left(), 4);
+    }
+}
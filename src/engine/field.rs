@@ -0,0 +1,341 @@
+use crate::rendering::renderer::{Texture, Dimensions, Position, Tile, Canvas, Theme};
+use crate::engine::board::Board;
+use crate::engine::mode::BoardLayout;
+use crate::engine::stones::{Stone, StoneKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Cell {
+    #[default]
+    Empty,
+    Piece(StoneKind),
+    Garbage,
+}
+
+impl Cell {
+    pub fn is_empty(self) -> bool {
+        self == Cell::Empty
+    }
+
+    /// `.` for empty cells, `G` for garbage and the letter of the piece otherwise.
+    pub fn to_char(self) -> char {
+        match self {
+            Cell::Empty => '.',
+            Cell::Piece(kind) => kind.letter(),
+            Cell::Garbage => 'G',
+        }
+    }
+
+    pub fn from_char(character: char) -> Option<Self> {
+        match character {
+            '.' => Some(Cell::Empty),
+            'G' | 'g' => Some(Cell::Garbage),
+            letter => StoneKind::from_letter(letter).map(Cell::Piece),
+        }
+    }
+
+    pub fn to_tile(self, theme: &Theme) -> Tile {
+        match self {
+            Cell::Empty => theme.empty_tile(),
+            Cell::Piece(kind) => theme.block_tile(kind.color(theme)),
+            Cell::Garbage => theme.block_tile(theme.garbage),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Field {
+    board: Board,
+    cells: Vec<Vec<Cell>>,
+    dimensions: Dimensions,
+    hidden_rows: usize,
+}
+
+impl Default for Field {
+    fn default() -> Self {
+        Self::new(BoardLayout { width: 10, visible_height: 20, hidden_rows: 0 })
+    }
+}
+
+impl Field {
+    pub fn new(layout: BoardLayout) -> Self {
+        let height = layout.visible_height + layout.hidden_rows;
+
+        Self {
+            board: Board::new(layout.width, height),
+            cells: vec![vec![Cell::Empty; layout.width]; height],
+            dimensions: Dimensions { width: layout.width, height: layout.visible_height },
+            hidden_rows: layout.hidden_rows,
+        }
+    }
+
+    pub fn render_at(&self, canvas: &mut Canvas, position: Position) {
+        let texture = self.to_texture(canvas.theme());
+        canvas.add_texture(texture, &position);
+    }
+
+    pub fn to_texture(&self, theme: &Theme) -> Texture {
+        Texture {
+            pixels: self.cells.iter().skip(self.hidden_rows).map(|row| {
+                row.iter().map(|cell| Some(cell.to_tile(theme))).collect()
+            }).collect(),
+            dimensions: self.dimensions,
+        }
+    }
+
+    pub fn hidden_rows(&self) -> usize {
+        self.hidden_rows
+    }
+
+    pub fn total_height(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn visible_offset(&self) -> Position {
+        Position { x: 0, y: -(self.hidden_rows as i32) }
+    }
+
+    pub fn spawn_position(&self, kind: StoneKind) -> Position {
+        let shape = kind.shape(0);
+        Position {
+            x: (self.dimensions.width as i32 - shape.size) / 2,
+            y: self.hidden_rows as i32 - shape.max_y,
+        }
+    }
+
+    pub fn can_spawn(&self, stone: &Stone) -> bool {
+        !self.board.collides(stone.shape(), &stone.position)
+    }
+
+    pub fn is_locked_out(&self, stone: &Stone) -> bool {
+        stone.cells().all(|position| position.y < self.hidden_rows as i32)
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn get_cell_at_pos(&self, position: &Position) -> Option<Cell> {
+        if position.y < 0 || position.x < 0 {
+            return None;
+        }
+
+        self.cells.get(position.y as usize)
+            .and_then(|row| row.get(position.x as usize))
+            .copied()
+    }
+
+    pub fn set_cell_at_pos(&mut self, position: &Position, cell: Cell) -> bool {
+        if position.y < 0 || position.x < 0 {
+            return false;
+        }
+
+        match self.cells.get_mut(position.y as usize).and_then(|row| row.get_mut(position.x as usize)) {
+            Some(current) => {
+                *current = cell;
+                self.board.set(position.x as usize, position.y as usize, !cell.is_empty());
+                true
+            }
+            None => false
+        }
+    }
+
+    pub fn all_positions_free(&self, positions: &[Position]) -> bool {
+        positions.iter().all(|position| self.position_free(position))
+    }
+
+    pub fn position_free(&self, position: &Position) -> bool {
+        !self.board.is_occupied(position.x, position.y)
+    }
+
+    pub fn try_delete_lines(&mut self) -> usize {
+        let full_rows = self.board.full_rows();
+        if full_rows.is_empty() {
+            return 0;
+        }
+
+        for &y in full_rows.iter().rev() {
+            self.cells.remove(y);
+        }
+        let mut cells = vec![vec![Cell::Empty; self.dimensions.width]; full_rows.len()];
+        cells.append(&mut self.cells);
+        self.cells = cells;
+
+        self.board.clear_lines()
+    }
+
+    /// Pushes the stack up by the given number of garbage rows that are open at the hole column,
+    /// columns past the right wall put the hole next to it. Returns `false` if blocks were pushed
+    /// out of the top of the field.
+    pub fn insert_garbage(&mut self, rows: usize, hole_column: usize) -> bool {
+        self.insert_garbage_rows(&vec![hole_column; rows])
+    }
+
+    /// Pushes in one garbage row per hole column from below, the first row ends up on top. Hole
+    /// columns past the right wall are moved next to it like for `insert_garbage`. Returns `false`
+    /// if blocks were pushed out of the top of the field.
+    pub fn insert_garbage_rows(&mut self, hole_columns: &[usize]) -> bool {
+        let rows = hole_columns.len().min(self.cells.len());
+        let mut fits = true;
+        for &hole_column in &hole_columns[hole_columns.len() - rows..] {
+            let hole_column = hole_column.min(self.dimensions.width - 1);
+            let mut garbage_row = vec![Cell::Garbage; self.dimensions.width];
+            garbage_row[hole_column] = Cell::Empty;
+
+            self.cells.remove(0);
+            self.cells.push(garbage_row);
+            fits &= self.board.insert_rows(1, !(1 << hole_column));
+        }
+        fits
+    }
+
+    /// How many rows hold garbage, pieces placed on top of a garbage row's hole don't change it.
+    pub fn garbage_rows(&self) -> usize {
+        self.cells.iter().filter(|row| row.contains(&Cell::Garbage)).count()
+    }
+
+    pub fn add_stone(&mut self, stone: &Stone) {
+        for position in stone.cells() {
+            self.set_cell_at_pos(&position, Cell::Piece(stone.kind));
+        }
+    }
+
+    pub fn dimensions(&self) -> &Dimensions {
+        &self.dimensions
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::field::{Field, Cell};
+    use crate::rendering::renderer::{Position, Theme};
+    use crate::engine::mode::Mode;
+    use crate::engine::stones::{Stone, StoneKind};
+
+    #[test]
+    fn get_cell_at_pos() {
+        let mut field = Field::default();
+        assert!(field.set_cell_at_pos(&Position{x: 2, y: 3}, Cell::Piece(StoneKind::Z)));
+        assert_eq!(field.get_cell_at_pos(&Position{x: 2, y: 3}), Some(Cell::Piece(StoneKind::Z)));
+        assert!(!field.all_positions_free(&[Position{x: 2, y: 3}]));
+        assert!(field.all_positions_free(&[Position{x: 3, y: 3}]));
+    }
+
+    #[test]
+    fn add_stone() {
+        let mut field = Field::default();
+        let stone = Stone::new(Position{y: 4, x: 2}, StoneKind::T);
+        field.add_stone(&stone);
+        assert!(field.position_free(&Position{y: 4, x: 2}));
+        assert!(!field.position_free(&Position{y: 4, x: 3}));
+        assert!(field.position_free(&Position{y: 4, x: 4}));
+        assert!(!field.position_free(&Position{y: 5, x: 2}));
+        assert!(!field.position_free(&Position{y: 5, x: 3}));
+        assert!(!field.position_free(&Position{y: 5, x: 4}));
+    }
+
+    #[test]
+    fn occupancy_does_not_depend_on_theme_colors() {
+        let mut theme = Theme::classic();
+        theme.pieces.o = theme.empty;
+        let mut field = Field::default();
+        field.add_stone(&Stone::new(Position{y: 18, x: 0}, StoneKind::O));
+        assert!(field.board().is_occupied(0, 18));
+        assert!(!field.position_free(&Position{y: 18, x: 0}));
+        assert_eq!(field.to_texture(&theme).pixels[18][0].unwrap().background, theme.empty);
+    }
+
+    #[test]
+    fn delete_full_lines() {
+        let mut field = Field::default();
+        for x in 0..10 {
+            field.set_cell_at_pos(&Position{x, y: 19}, Cell::Garbage);
+        }
+        field.set_cell_at_pos(&Position{x: 4, y: 18}, Cell::Garbage);
+
+        assert_eq!(field.try_delete_lines(), 1);
+        assert_eq!(field.get_cell_at_pos(&Position{x: 4, y: 19}), Some(Cell::Garbage));
+        assert!(field.position_free(&Position{x: 4, y: 18}));
+        assert!(field.position_free(&Position{x: 0, y: 19}));
+    }
+
+    #[test]
+    fn garbage_pushes_the_stack_up() {
+        let mut field = Field::default();
+        field.set_cell_at_pos(&Position{x: 0, y: 19}, Cell::Piece(StoneKind::I));
+
+        assert!(field.insert_garbage(2, 3));
+        assert_eq!(field.get_cell_at_pos(&Position{x: 0, y: 17}), Some(Cell::Piece(StoneKind::I)));
+        assert_eq!(field.get_cell_at_pos(&Position{x: 0, y: 19}), Some(Cell::Garbage));
+        assert!(field.position_free(&Position{x: 3, y: 18}));
+        assert!(field.position_free(&Position{x: 3, y: 19}));
+        assert!(!field.position_free(&Position{x: 4, y: 19}));
+
+        assert!(!field.insert_garbage(18, 3));
+    }
+
+    #[test]
+    fn counts_rows_with_garbage() {
+        let mut field = Field::default();
+        field.insert_garbage_rows(&[1, 2, 3]);
+        field.set_cell_at_pos(&Position{x: 0, y: 16}, Cell::Piece(StoneKind::I));
+        assert_eq!(field.garbage_rows(), 3);
+        (0..10).for_each(|x| { field.set_cell_at_pos(&Position{x, y: 17}, Cell::Piece(StoneKind::I)); });
+        assert_eq!(field.garbage_rows(), 2);
+    }
+
+    #[test]
+    fn holes_past_the_wall_stay_on_the_field() {
+        let mut field = Field::default();
+        let width = field.dimensions().width;
+        assert!(field.insert_garbage(1, 40));
+        assert!(field.insert_garbage_rows(&[width, usize::MAX]));
+        for y in 17..20 {
+            assert!(field.position_free(&Position{x: width as i32 - 1, y}));
+            assert!(!field.board().is_row_full(y as usize));
+        }
+        assert_eq!(field.garbage_rows(), 3);
+    }
+
+    #[test]
+    fn garbage_rows_with_their_own_holes() {
+        let mut field = Field::default();
+        assert!(field.insert_garbage_rows(&[1, 2, 3]));
+        assert!(field.position_free(&Position{x: 1, y: 17}));
+        assert!(field.position_free(&Position{x: 2, y: 18}));
+        assert!(field.position_free(&Position{x: 3, y: 19}));
+        assert!(!field.position_free(&Position{x: 3, y: 17}));
+        assert_eq!(field.board().holes(), 2);
+
+        let mut stone = Stone::new(Position{x: 0, y: 15}, StoneKind::O);
+        field.add_stone(&stone);
+        assert!(!field.insert_garbage_rows(&[0; 17]));
+
+        let mut field = Field::default();
+        field.insert_garbage(2, 0);
+        assert!(field.board().collides(stone.shape(), &Position{x: 0, y: 17}));
+        stone.position.y = 17;
+        assert!(stone.push_up(&field));
+        assert_eq!(stone.position.y, 16);
+    }
+
+    #[test]
+    fn hidden_rows_and_spawn() {
+        let field = Field::new(Mode::Marathon.layout());
+        assert_eq!(field.total_height(), 40);
+        assert_eq!(field.dimensions().height, 20);
+        assert_eq!(field.to_texture(&Theme::classic()).pixels.len(), 20);
+
+        let t_spawn = field.spawn_position(StoneKind::T);
+        assert_eq!(t_spawn, Position{x: 3, y: 19});
+        assert_eq!(field.spawn_position(StoneKind::I), Position{x: 3, y: 19});
+        assert_eq!(field.spawn_position(StoneKind::O), Position{x: 4, y: 19});
+        assert!(field.is_locked_out(&Stone::new(Position{x: 3, y: 17}, StoneKind::T)));
+        assert!(!field.is_locked_out(&Stone::new(t_spawn, StoneKind::T)));
+
+        let four_wide = Field::new(Mode::FourWide.layout());
+        assert_eq!(four_wide.spawn_position(StoneKind::I).x, 0);
+        assert_eq!(four_wide.spawn_position(StoneKind::T).x, 0);
+        assert_eq!(four_wide.spawn_position(StoneKind::O).x, 1);
+    }
+}
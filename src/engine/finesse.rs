@@ -0,0 +1,262 @@
+use crate::engine::field::Field;
+use crate::engine::stones::Stone;
+use crate::rendering::renderer::Position;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// A single key press while placing a piece. Holding a direction until the piece reaches the wall
+/// counts as one input, the final hard drop isn't counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Input {
+    Left,
+    Right,
+    DasLeft,
+    DasRight,
+    RotateCW,
+    RotateCCW,
+    Rotate180,
+}
+
+impl Input {
+    pub const ALL: [Input; 7] = [
+        Input::Left,
+        Input::Right,
+        Input::DasLeft,
+        Input::DasRight,
+        Input::RotateCW,
+        Input::RotateCCW,
+        Input::Rotate180,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Input::Left => "left",
+            Input::Right => "right",
+            Input::DasLeft => "das left",
+            Input::DasRight => "das right",
+            Input::RotateCW => "cw",
+            Input::RotateCCW => "ccw",
+            Input::Rotate180 => "180",
+        }
+    }
+
+    /// Returns whether the input changed the stone.
+    pub fn apply(self, stone: &mut Stone, field: &Field) -> bool {
+        match self {
+            Input::Left => stone.move_left(field),
+            Input::Right => stone.move_right(field),
+            Input::DasLeft => {
+                let moved = stone.move_left(field);
+                while stone.move_left(field) {}
+                moved
+            }
+            Input::DasRight => {
+                let moved = stone.move_right(field);
+                while stone.move_right(field) {}
+                moved
+            }
+            Input::RotateCW => stone.rotate(field),
+            Input::RotateCCW => stone.rotate_counterclockwise(field),
+            Input::Rotate180 => stone.rotate_180(field),
+        }
+    }
+}
+
+pub fn describe(inputs: &[Input]) -> String {
+    if inputs.is_empty() {
+        return "drop".to_string();
+    }
+    inputs.iter().map(|input| input.name()).collect::<Vec<_>>().join(", ")
+}
+
+/// The cells the stone ends up on after a hard drop, sorted so placements can be compared
+/// regardless of the rotation that produced them.
+pub fn landing_cells(stone: &Stone, field: &Field) -> Vec<Position> {
+    let mut landed = *stone;
+    landed.position.y += field.board().drop_distance(stone.shape(), &stone.position);
+    let mut cells: Vec<_> = landed.cells().collect();
+    cells.sort_by_key(|cell| (cell.y, cell.x));
+    cells
+}
+
+/// Every placement that can be reached from the start without soft dropping, together with one of
+/// the shortest input sequences leading to it. Placements are dropped stones.
+pub fn placements(field: &Field, start: &Stone) -> Vec<(Stone, Vec<Input>)> {
+    let mut seen_states = HashSet::new();
+    let mut seen_landings = HashSet::new();
+    let mut queue = VecDeque::new();
+    let mut placements = Vec::new();
+
+    seen_states.insert((start.position.x, start.rotation));
+    queue.push_back((*start, Vec::new()));
+
+    while let Some((stone, inputs)) = queue.pop_front() {
+        if seen_landings.insert(landing_cells(&stone, field)) {
+            let mut landed = stone;
+            landed.position.y += field.board().drop_distance(stone.shape(), &stone.position);
+            placements.push((landed, inputs.clone()));
+        }
+
+        for &input in Input::ALL.iter() {
+            let mut next = stone;
+            if input.apply(&mut next, field) && seen_states.insert((next.position.x, next.rotation)) {
+                let mut next_inputs = inputs.clone();
+                next_inputs.push(input);
+                queue.push_back((next, next_inputs));
+            }
+        }
+    }
+
+    placements
+}
+
+/// One of the shortest input sequences that moves the stone from the start to a placement covering
+/// the target cells, `None` if that needs a soft drop.
+pub fn finesse_path(field: &Field, start: &Stone, target: &[Position]) -> Option<Vec<Input>> {
+    let mut target: Vec<_> = target.to_vec();
+    target.sort_by_key(|cell| (cell.y, cell.x));
+
+    let mut paths: HashMap<(i32, u8), Vec<Input>> = HashMap::new();
+    let mut queue = VecDeque::new();
+    paths.insert((start.position.x, start.rotation), Vec::new());
+    queue.push_back(*start);
+
+    while let Some(stone) = queue.pop_front() {
+        let inputs = paths[&(stone.position.x, stone.rotation)].clone();
+        if landing_cells(&stone, field) == target {
+            return Some(inputs);
+        }
+
+        for &input in Input::ALL.iter() {
+            let mut next = stone;
+            let key = (next.position.x, next.rotation);
+            if input.apply(&mut next, field) && !paths.contains_key(&(next.position.x, next.rotation)) {
+                let mut next_inputs = paths[&key].clone();
+                next_inputs.push(input);
+                paths.insert((next.position.x, next.rotation), next_inputs);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+/// Counts the inputs spent on the current piece to compare them with the shortest sequence once the
+/// piece is placed.
+#[derive(Debug, Clone)]
+pub struct FinesseTracker {
+    start: Stone,
+    inputs: u32,
+    soft_dropped: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FinesseResult {
+    pub inputs: u32,
+    pub optimal: Vec<Input>,
+}
+
+impl FinesseResult {
+    pub fn is_fault(&self) -> bool {
+        self.inputs as usize > self.optimal.len()
+    }
+}
+
+impl FinesseTracker {
+    pub fn new(start: Stone) -> Self {
+        Self { start, inputs: 0, soft_dropped: false }
+    }
+
+    pub fn reset(&mut self, start: Stone) {
+        *self = Self::new(start);
+    }
+
+    pub fn start(&self) -> &Stone {
+        &self.start
+    }
+
+    pub fn inputs(&self) -> u32 {
+        self.inputs
+    }
+
+    pub fn record_input(&mut self) {
+        self.inputs += 1;
+    }
+
+    pub fn record_soft_drop(&mut self) {
+        self.soft_dropped = true;
+    }
+
+    /// Has to be called before the stone is added to the field. Placements that needed a soft drop
+    /// aren't judged.
+    pub fn evaluate(&self, field: &Field, placed: &Stone) -> Option<FinesseResult> {
+        if self.soft_dropped {
+            return None;
+        }
+
+        let target = landing_cells(placed, field);
+        finesse_path(field, &self.start, &target).map(|optimal| FinesseResult { inputs: self.inputs, optimal })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::field::Field;
+    use crate::engine::finesse::{finesse_path, landing_cells, placements, FinesseTracker, Input};
+    use crate::engine::stones::{Stone, StoneKind};
+
+    fn spawned(field: &Field, kind: StoneKind) -> Stone {
+        Stone::new(field.spawn_position(kind), kind)
+    }
+
+    fn target(field: &Field, kind: StoneKind, rotation: u8, x: i32) -> Vec<crate::rendering::renderer::Position> {
+        let mut stone = spawned(field, kind);
+        stone.rotation = rotation;
+        stone.position.x = x;
+        landing_cells(&stone, field)
+    }
+
+    #[test]
+    fn shortest_paths() {
+        let field = Field::default();
+        let t_stone = spawned(&field, StoneKind::T);
+
+        assert_eq!(finesse_path(&field, &t_stone, &target(&field, StoneKind::T, 0, 3)), Some(vec![]));
+        assert_eq!(finesse_path(&field, &t_stone, &target(&field, StoneKind::T, 0, 0)), Some(vec![Input::DasLeft]));
+        assert_eq!(finesse_path(&field, &t_stone, &target(&field, StoneKind::T, 2, 7)).map(|path| path.len()), Some(2));
+
+        let i_stone = spawned(&field, StoneKind::I);
+        let vertical_at_left_wall = target(&field, StoneKind::I, 1, -2);
+        assert_eq!(finesse_path(&field, &i_stone, &vertical_at_left_wall).map(|path| path.len()), Some(2));
+    }
+
+    #[test]
+    fn symmetric_pieces_have_fewer_placements() {
+        let field = Field::default();
+        assert_eq!(placements(&field, &spawned(&field, StoneKind::O)).len(), 9);
+        assert_eq!(placements(&field, &spawned(&field, StoneKind::I)).len(), 17);
+        assert_eq!(placements(&field, &spawned(&field, StoneKind::T)).len(), 34);
+    }
+
+    #[test]
+    fn tracker_detects_faults() {
+        let field = Field::default();
+        let start = spawned(&field, StoneKind::T);
+        let mut tracker = FinesseTracker::new(start);
+
+        let mut stone = start;
+        for _ in 0..3 {
+            stone.move_left(&field);
+            tracker.record_input();
+        }
+        assert!(tracker.evaluate(&field, &stone).unwrap().is_fault());
+
+        tracker.reset(start);
+        Input::DasLeft.apply(&mut stone, &field);
+        tracker.record_input();
+        assert!(!tracker.evaluate(&field, &stone).unwrap().is_fault());
+
+        tracker.record_soft_drop();
+        assert_eq!(tracker.evaluate(&field, &stone), None);
+    }
+}
@@ -0,0 +1,617 @@
+use crate::engine::field::{Cell, Field};
+use crate::engine::mode::Mode;
+use crate::engine::stones::{Stone, StoneKind};
+use crate::rendering::renderer::Position;
+use std::convert::{TryFrom, TryInto};
+
+const WIDTH: usize = 10;
+/// Rows of the field, below them is the garbage row that rises into the field on pages with `rise`.
+const FIELD_TOP: usize = 23;
+const BLOCKS: usize = (FIELD_TOP + 1) * WIDTH;
+const TABLE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const COMMENT_TABLE: &[u8] = b" !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+const COMMENT_BASE: u32 = COMMENT_TABLE.len() as u32 + 1;
+const MAX_COMMENT_LENGTH: usize = 4095;
+/// A field where every block stays the same.
+const UNCHANGED: u32 = (8 * BLOCKS + BLOCKS - 1) as u32;
+
+/// The blocks of a fumen from the top left, the last row is the garbage row. Blocks are 0 for
+/// empty, 1 to 7 for I, L, O, Z, T, J and S and 8 for garbage.
+type Grid = [u8; BLOCKS];
+
+/// One page of a fumen: a board and the piece placed on it.
+#[derive(Clone)]
+pub struct FumenPage {
+    pub field: Field,
+    pub piece: Option<Stone>,
+    pub comment: String,
+    /// Whether the piece is added and full lines are cleared before the next page.
+    pub lock: bool,
+    /// Whether the garbage row rises into the field after the piece is locked.
+    pub rise: bool,
+    /// Whether the field is mirrored after the piece is locked.
+    pub mirror: bool,
+}
+
+impl FumenPage {
+    pub fn new(field: Field, piece: Option<Stone>) -> Self {
+        Self { field, piece, comment: String::new(), lock: true, rise: false, mirror: false }
+    }
+
+    /// The field after the piece is locked and full lines are cleared.
+    pub fn locked_field(&self) -> Field {
+        let mut field = self.field.clone();
+        if let Some(piece) = &self.piece {
+            field.add_stone(piece);
+        }
+        field.try_delete_lines();
+        field
+    }
+}
+
+/// Decodes a v115 fumen, with or without the address of the fumen site in front.
+pub fn decode_fumen(code: &str) -> Result<Vec<FumenPage>, String> {
+    let code: String = code.chars().filter(|character| !character.is_whitespace()).collect();
+    let start = code.find("115@").filter(|&start| code[..start].ends_with(&['v', 'm', 'd'][..]))
+        .ok_or("only v115 fumens are supported")?;
+    let mut reader = Reader::new(&code[start + 4..])?;
+
+    let mut pages = Vec::new();
+    let mut previous: Grid = [0; BLOCKS];
+    let mut repeat = 0;
+    let mut comment = String::new();
+    while !reader.is_empty() {
+        let mut grid = previous;
+        if repeat > 0 {
+            repeat -= 1;
+        } else if !reader.read_field(&mut grid)? {
+            repeat = reader.poll(1)?;
+        }
+
+        let mut action = reader.poll(3)?;
+        let piece = action % 8;
+        action /= 8;
+        let rotation = action % 4;
+        action /= 4;
+        let position = action as usize % BLOCKS;
+        action /= BLOCKS as u32;
+        let (rise, mirror, has_comment, lock) = (action & 1 != 0, action & 2 != 0, action & 8 != 0, action & 16 == 0);
+
+        let operation = match piece {
+            0 => None,
+            number => {
+                let kind = kind_of(number as u8).ok_or("invalid piece in the fumen")?;
+                let (dx, dy) = correction(kind, rotation);
+                let center = ((position % WIDTH) as i32 + dx, (FIELD_TOP - 1 - position / WIDTH) as i32 + dy);
+                Some(Operation { kind, rotation, center })
+            }
+        };
+        if has_comment {
+            comment = reader.read_comment()?;
+        }
+
+        let field = to_field(&grid);
+        let piece = operation.map(|operation| operation.stone(&field)).transpose()?;
+        pages.push(FumenPage { field, piece, comment: comment.clone(), lock, rise, mirror });
+
+        if lock {
+            if let Some(operation) = operation {
+                operation.fill(&mut grid)?;
+            }
+            clear_lines(&mut grid);
+            if rise {
+                rise_garbage(&mut grid);
+            }
+            if mirror {
+                mirror_field(&mut grid);
+            }
+        }
+        previous = grid;
+    }
+
+    if pages.is_empty() {
+        return Err("the fumen has no pages".to_string());
+    }
+    Ok(pages)
+}
+
+/// Encodes the pages as a v115 fumen. The fields have to be 10 wide and at most 23 rows high.
+pub fn encode_fumen(pages: &[FumenPage]) -> Result<String, String> {
+    let mut values = Vec::new();
+    let mut previous: Grid = [0; BLOCKS];
+    let mut repeat_at: Option<usize> = None;
+    let mut comment = String::new();
+
+    for (index, page) in pages.iter().enumerate() {
+        let mut grid = to_grid(&page.field)?;
+        grid[BLOCKS - WIDTH..].copy_from_slice(&previous[BLOCKS - WIDTH..]);
+
+        let field_values = field_diff(&previous, &grid);
+        match repeat_at {
+            _ if field_values != [UNCHANGED] => {
+                field_values.iter().for_each(|&value| push(&mut values, value, 2));
+                repeat_at = None;
+            }
+            Some(at) if values[at] < TABLE.len() as u8 - 1 => values[at] += 1,
+            _ => {
+                push(&mut values, UNCHANGED, 2);
+                values.push(0);
+                repeat_at = Some(values.len() - 1);
+            }
+        }
+
+        let operation = page.piece.as_ref().map(|stone| Operation::of(stone, &page.field)).transpose()?;
+        let has_comment = page.comment != comment;
+        let flags = [page.rise, page.mirror, index == 0, has_comment, !page.lock];
+        let mut action = flags.iter().rev().fold(0, |value, &flag| value * 2 + flag as u32);
+        action = action * BLOCKS as u32 + operation.as_ref().map_or(0, Operation::position);
+        action = action * 4 + operation.as_ref().map_or(0, |operation| operation.rotation);
+        action = action * 8 + operation.as_ref().map_or(0, |operation| kind_number(operation.kind) as u32);
+        push(&mut values, action, 3);
+
+        if has_comment {
+            let escaped: Vec<u8> = escape(&page.comment).bytes().take(MAX_COMMENT_LENGTH).collect();
+            push(&mut values, escaped.len() as u32, 2);
+            for chunk in escaped.chunks(4) {
+                let value = chunk.iter().rev().fold(0, |value, &character| {
+                    let index = COMMENT_TABLE.iter().position(|&other| other == character).unwrap_or(0);
+                    value * COMMENT_BASE + index as u32
+                });
+                push(&mut values, value, 5);
+            }
+            comment = page.comment.clone();
+        }
+
+        if page.lock {
+            if let Some(operation) = operation {
+                operation.fill(&mut grid)?;
+            }
+            clear_lines(&mut grid);
+            if page.rise {
+                rise_garbage(&mut grid);
+            }
+            if page.mirror {
+                mirror_field(&mut grid);
+            }
+        }
+        previous = grid;
+    }
+
+    let data: String = values.iter().map(|&value| TABLE[value as usize] as char).collect();
+    let mut chunks = vec![&data[..data.len().min(42)]];
+    if data.len() > 42 {
+        chunks.extend(data.as_bytes()[42..].chunks(47).map(|chunk| std::str::from_utf8(chunk).expect("the table is ASCII")));
+    }
+    Ok(format!("v115@{}", chunks.join("?")))
+}
+
+/// The hold, current and next pieces of a quiz comment like `#Q=[S](T)IOZ`, the current piece comes
+/// first.
+pub fn quiz_pieces(comment: &str) -> Option<(Option<StoneKind>, Vec<StoneKind>)> {
+    let quiz = comment.trim().strip_prefix("#Q=[")?;
+    let (hold, rest) = quiz.split_at(quiz.find(']')?);
+    let rest = rest.strip_prefix("](")?;
+    let (current, next) = rest.split_at(rest.find(')')?);
+
+    let hold = hold.chars().next().map(StoneKind::from_letter);
+    let pieces: Option<Vec<StoneKind>> = current.chars().chain(next[1..].chars().take_while(|character| character.is_alphabetic()))
+        .map(StoneKind::from_letter)
+        .collect();
+    match hold {
+        Some(None) => None,
+        hold => Some((hold.flatten(), pieces?)),
+    }
+}
+
+/// A quiz comment for the pieces, see `quiz_pieces`.
+pub fn quiz_comment(hold: Option<StoneKind>, pieces: &[StoneKind]) -> String {
+    let letters = |kinds: &[StoneKind]| kinds.iter().map(|kind| kind.letter()).collect::<String>();
+    let (current, next) = pieces.split_at(pieces.len().min(1));
+    format!("#Q=[{}]({}){}", hold.map(|kind| kind.letter().to_string()).unwrap_or_default(), letters(current), letters(next))
+}
+
+struct Reader {
+    values: Vec<u8>,
+    index: usize,
+}
+
+impl Reader {
+    fn new(data: &str) -> Result<Self, String> {
+        let values = data.chars().filter(|&character| character != '?')
+            .map(|character| TABLE.iter().position(|&other| other as char == character).map(|value| value as u8))
+            .collect::<Option<Vec<_>>>()
+            .ok_or("the fumen contains invalid characters")?;
+        Ok(Self { values, index: 0 })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.index >= self.values.len()
+    }
+
+    /// Reads a number of the given count of digits, the lowest digit comes first.
+    fn poll(&mut self, digits: usize) -> Result<u32, String> {
+        let end = self.index + digits;
+        let values = self.values.get(self.index..end).ok_or("the fumen ends too early")?;
+        self.index = end;
+        Ok(values.iter().rev().fold(0, |value, &digit| value * TABLE.len() as u32 + digit as u32))
+    }
+
+    /// Applies the differences to the previous field, returns `false` if nothing changed.
+    fn read_field(&mut self, grid: &mut Grid) -> Result<bool, String> {
+        let mut changed = true;
+        let mut index = 0;
+        while index < BLOCKS {
+            let value = self.poll(2)?;
+            if value == UNCHANGED {
+                changed = false;
+            }
+            let (difference, count) = ((value as usize / BLOCKS) as i32 - 8, value as usize % BLOCKS + 1);
+            for block in grid.get_mut(index..index + count).ok_or("invalid field in the fumen")? {
+                *block = u8::try_from(*block as i32 + difference).ok().filter(|&block| block <= 8).ok_or("invalid field in the fumen")?;
+            }
+            index += count;
+        }
+        Ok(changed)
+    }
+
+    fn read_comment(&mut self) -> Result<String, String> {
+        let length = self.poll(2)? as usize;
+        let mut escaped = String::with_capacity(length + 3);
+        for _ in 0..length.div_ceil(4) {
+            let mut value = self.poll(5)?;
+            for _ in 0..4 {
+                let character = COMMENT_TABLE.get((value % COMMENT_BASE) as usize).ok_or("invalid comment in the fumen")?;
+                escaped.push(*character as char);
+                value /= COMMENT_BASE;
+            }
+        }
+        escaped.truncate(length);
+        Ok(unescape(&escaped))
+    }
+}
+
+fn push(values: &mut Vec<u8>, mut value: u32, digits: usize) {
+    for _ in 0..digits {
+        values.push((value % TABLE.len() as u32) as u8);
+        value /= TABLE.len() as u32;
+    }
+}
+
+/// Runs of equal differences, each as the difference and the length of the run.
+fn field_diff(previous: &Grid, grid: &Grid) -> Vec<u32> {
+    let differences: Vec<u32> = previous.iter().zip(grid.iter()).map(|(&before, &after)| (after as i32 - before as i32 + 8) as u32).collect();
+    let mut values = Vec::new();
+    let mut start = 0;
+    for index in 1..=BLOCKS {
+        if index == BLOCKS || differences[index] != differences[start] {
+            values.push(differences[start] * BLOCKS as u32 + (index - start - 1) as u32);
+            start = index;
+        }
+    }
+    values
+}
+
+/// A piece as fumen stores it: the center of rotation with the y axis pointing up and the rotation
+/// counted from upside down (0) clockwise.
+#[derive(Debug, Clone, Copy)]
+struct Operation {
+    kind: StoneKind,
+    rotation: u32,
+    center: (i32, i32),
+}
+
+impl Operation {
+    fn of(stone: &Stone, field: &Field) -> Result<Self, String> {
+        let top = field.total_height() as i32 - 1;
+        let rotation = [2, 1, 0, 3][stone.rotation as usize % 4];
+        let mut cells: Vec<(i32, i32)> = stone.cells().map(|cell| (cell.x, top - cell.y)).collect();
+        let mut offsets = offsets(stone.kind, rotation);
+        cells.sort_unstable();
+        offsets.sort_unstable();
+        let center = (cells[0].0 - offsets[0].0, cells[0].1 - offsets[0].1);
+
+        let operation = Self { kind: stone.kind, rotation, center };
+        if operation.cells().iter().any(|&(x, y)| x < 0 || x >= WIDTH as i32 || y < 0 || y >= FIELD_TOP as i32) {
+            return Err("the piece is outside of the fumen field".to_string());
+        }
+        Ok(operation)
+    }
+
+    fn cells(&self) -> [(i32, i32); 4] {
+        offsets(self.kind, self.rotation).map(|(x, y)| (self.center.0 + x, self.center.1 + y))
+    }
+
+    fn position(&self) -> u32 {
+        let (dx, dy) = correction(self.kind, self.rotation);
+        let (x, y) = (self.center.0 - dx, self.center.1 - dy);
+        ((FIELD_TOP as i32 - y - 1) * WIDTH as i32 + x) as u32
+    }
+
+    fn stone(&self, field: &Field) -> Result<Stone, String> {
+        let top = field.total_height() as i32 - 1;
+        let cells: Vec<Position> = self.cells().iter().map(|&(x, y)| Position { x, y: top - y }).collect();
+        Stone::covering(self.kind, [2, 1, 0, 3][self.rotation as usize], &cells)
+            .filter(|stone| stone.cells().all(|cell| cell.x >= 0 && cell.x < WIDTH as i32 && cell.y >= 0 && cell.y <= top))
+            .ok_or_else(|| "a piece of the fumen is outside of the field".to_string())
+    }
+
+    fn fill(&self, grid: &mut Grid) -> Result<(), String> {
+        for (x, y) in self.cells().iter() {
+            let index = grid_index(*x, *y).ok_or("a piece of the fumen is outside of the field")?;
+            grid[index] = kind_number(self.kind);
+        }
+        Ok(())
+    }
+}
+
+/// The blocks around the center of rotation.
+fn offsets(kind: StoneKind, rotation: u32) -> [(i32, i32); 4] {
+    let spawn = match kind {
+        StoneKind::I => [(0, 0), (-1, 0), (1, 0), (2, 0)],
+        StoneKind::T => [(0, 0), (-1, 0), (1, 0), (0, 1)],
+        StoneKind::O => [(0, 0), (1, 0), (0, 1), (1, 1)],
+        StoneKind::L => [(0, 0), (-1, 0), (1, 0), (1, 1)],
+        StoneKind::J => [(0, 0), (-1, 0), (1, 0), (-1, 1)],
+        StoneKind::S => [(0, 0), (-1, 0), (0, 1), (1, 1)],
+        StoneKind::Z => [(0, 0), (1, 0), (0, 1), (-1, 1)],
+    };
+    match rotation {
+        0 => spawn.map(|(x, y)| (-x, -y)),
+        1 => spawn.map(|(x, y)| (y, -x)),
+        3 => spawn.map(|(x, y)| (-y, x)),
+        _ => spawn,
+    }
+}
+
+/// Fumen stores some rotations of O, I, S and Z one block off their center.
+fn correction(kind: StoneKind, rotation: u32) -> (i32, i32) {
+    match (kind, rotation) {
+        (StoneKind::O, 3) => (1, -1),
+        (StoneKind::O, 0) | (StoneKind::I, 0) | (StoneKind::Z, 3) => (1, 0),
+        (StoneKind::O, 2) | (StoneKind::I, 3) | (StoneKind::S, 2) | (StoneKind::Z, 2) => (0, -1),
+        (StoneKind::S, 1) => (-1, 0),
+        _ => (0, 0),
+    }
+}
+
+/// The index of a block with the y axis pointing up from the lowest row of the field, the garbage
+/// row is -1.
+fn grid_index(x: i32, y: i32) -> Option<usize> {
+    if x < 0 || x >= WIDTH as i32 || y < -1 || y >= FIELD_TOP as i32 {
+        return None;
+    }
+    Some((FIELD_TOP as i32 - 1 - y) as usize * WIDTH + x as usize)
+}
+
+fn clear_lines(grid: &mut Grid) {
+    let rows: Vec<[u8; WIDTH]> = grid[..BLOCKS - WIDTH].chunks(WIDTH)
+        .filter(|row| row.contains(&0))
+        .map(|row| row.try_into().expect("rows are as wide as the field"))
+        .collect();
+    let cleared = FIELD_TOP - rows.len();
+    grid[..cleared * WIDTH].fill(0);
+    for (index, row) in rows.iter().enumerate() {
+        grid[(cleared + index) * WIDTH..(cleared + index + 1) * WIDTH].copy_from_slice(row);
+    }
+}
+
+fn rise_garbage(grid: &mut Grid) {
+    grid.copy_within(WIDTH.., 0);
+    grid[BLOCKS - WIDTH..].fill(0);
+}
+
+fn mirror_field(grid: &mut Grid) {
+    grid[..BLOCKS - WIDTH].chunks_mut(WIDTH).for_each(|row| row.reverse());
+}
+
+fn to_field(grid: &Grid) -> Field {
+    let mut field = Field::new(Mode::Marathon.layout());
+    let top = field.total_height() as i32 - 1;
+    for y in 0..FIELD_TOP as i32 {
+        for x in 0..WIDTH as i32 {
+            let block = grid[grid_index(x, y).expect("the position is on the field")];
+            field.set_cell_at_pos(&Position { x, y: top - y }, cell_of(block));
+        }
+    }
+    field
+}
+
+fn to_grid(field: &Field) -> Result<Grid, String> {
+    if field.dimensions().width != WIDTH {
+        return Err(format!("fumen boards are {} wide", WIDTH));
+    }
+    let mut grid = [0; BLOCKS];
+    let top = field.total_height() as i32 - 1;
+    for row in 0..field.total_height() as i32 {
+        for x in 0..WIDTH as i32 {
+            let cell = field.get_cell_at_pos(&Position { x, y: row }).unwrap_or_default();
+            match grid_index(x, top - row) {
+                Some(index) if top - row >= 0 => grid[index] = block_number(cell),
+                _ if cell.is_empty() => {}
+                _ => return Err(format!("the board is higher than the {} rows of a fumen", FIELD_TOP)),
+            }
+        }
+    }
+    Ok(grid)
+}
+
+fn kind_number(kind: StoneKind) -> u8 {
+    match kind {
+        StoneKind::I => 1,
+        StoneKind::L => 2,
+        StoneKind::O => 3,
+        StoneKind::Z => 4,
+        StoneKind::T => 5,
+        StoneKind::J => 6,
+        StoneKind::S => 7,
+    }
+}
+
+fn kind_of(number: u8) -> Option<StoneKind> {
+    StoneKind::ALL.iter().copied().find(|&kind| kind_number(kind) == number)
+}
+
+fn block_number(cell: Cell) -> u8 {
+    match cell {
+        Cell::Empty => 0,
+        Cell::Piece(kind) => kind_number(kind),
+        Cell::Garbage => 8,
+    }
+}
+
+fn cell_of(block: u8) -> Cell {
+    match block {
+        0 => Cell::Empty,
+        8 => Cell::Garbage,
+        number => kind_of(number).map_or(Cell::Garbage, Cell::Piece),
+    }
+}
+
+/// Comments are stored the way JavaScript's `escape` writes them.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for unit in text.encode_utf16() {
+        match char::from_u32(unit as u32) {
+            Some(character) if character.is_ascii_alphanumeric() || "@*_+-./".contains(character) => escaped.push(character),
+            _ if unit < 256 => escaped.push_str(&format!("%{:02X}", unit)),
+            _ => escaped.push_str(&format!("%u{:04X}", unit)),
+        }
+    }
+    escaped
+}
+
+fn unescape(text: &str) -> String {
+    let mut units = Vec::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(character) = rest.chars().next() {
+        let (unit, length) = match character {
+            '%' if rest.starts_with("%u") => (rest.get(2..6).and_then(|hex| u16::from_str_radix(hex, 16).ok()), 6),
+            '%' => (rest.get(1..3).and_then(|hex| u16::from_str_radix(hex, 16).ok()), 3),
+            _ => (None, 0),
+        };
+        match unit {
+            Some(unit) => {
+                units.push(unit);
+                rest = &rest[length..];
+            }
+            None => {
+                let mut buffer = [0; 2];
+                units.extend_from_slice(character.encode_utf16(&mut buffer));
+                rest = &rest[character.len_utf8()..];
+            }
+        }
+    }
+    String::from_utf16_lossy(&units)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::mechanics::{decode_fumen, encode_fumen, quiz_comment, quiz_pieces, Cell, Field, FumenPage, Mode, Stone, StoneKind};
+    use crate::rendering::renderer::Position;
+
+    fn rows(field: &Field, count: usize) -> Vec<String> {
+        let height = field.total_height() as i32;
+        (height - count as i32..height)
+            .map(|y| (0..10).map(|x| field.get_cell_at_pos(&Position { x, y }).unwrap().to_char()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn empty_fumen() {
+        let pages = decode_fumen("v115@vhAAgH").unwrap();
+        assert_eq!(pages.len(), 1);
+        assert!(rows(&pages[0].field, 23).iter().all(|row| row == ".........."));
+        assert!(pages[0].piece.is_none());
+
+        let field = Field::new(Mode::Marathon.layout());
+        assert_eq!(encode_fumen(&[FumenPage::new(field, None)]).unwrap(), "v115@vhAAgH");
+        assert!(decode_fumen("v110@vhAAgH").is_err());
+
+        let pages = decode_fumen("https://harddrop.com/fumen/?v115@vhAVQJ").unwrap();
+        let piece = pages[0].piece.unwrap();
+        let bottom = pages[0].field.total_height() as i32 - 1;
+        let mut cells: Vec<_> = piece.cells().map(|cell| (cell.x, bottom - cell.y)).collect();
+        cells.sort_unstable();
+        assert_eq!((piece.kind, piece.rotation), (StoneKind::T, 0));
+        assert_eq!(cells, vec![(3, 0), (4, 0), (4, 1), (5, 0)]);
+        assert_eq!(encode_fumen(&pages).unwrap(), "v115@vhAVQJ");
+        assert!(decode_fumen("v115@vh").is_err());
+        assert!(decode_fumen("ä115@vhAAgH").is_err());
+        assert!(decode_fumen("115@vhAAgH").is_err());
+        assert!(decode_fumen("v115@vhAAgHä").is_err());
+    }
+
+    #[test]
+    fn pages_round_trip() {
+        let mut field = Field::new(Mode::Marathon.layout());
+        let bottom = field.total_height() as i32 - 1;
+        for x in 0..9 {
+            field.set_cell_at_pos(&Position { x, y: bottom }, Cell::Garbage);
+        }
+        field.set_cell_at_pos(&Position { x: 0, y: bottom - 1 }, Cell::Piece(StoneKind::L));
+
+        let mut pages = Vec::new();
+        let mut first = FumenPage::new(field.clone(), None);
+        first.comment = "#Q=[S](T)IOZ ünïcode".to_string();
+        pages.push(first);
+        for (index, kind) in StoneKind::ALL.iter().enumerate() {
+            let mut stone = Stone::new(field.spawn_position(*kind), *kind);
+            stone.rotation = index as u8 % 4;
+            while stone.move_down(&field) {}
+            let page = FumenPage::new(field.clone(), Some(stone));
+            field = page.locked_field();
+            pages.push(page);
+        }
+        pages.push(FumenPage::new(field.clone(), None));
+        pages.push(FumenPage::new(field.clone(), None));
+
+        let code = encode_fumen(&pages).unwrap();
+        assert!(code.starts_with("v115@") && code.contains('?'));
+        let decoded = decode_fumen(&code).unwrap();
+        assert_eq!(decoded.len(), pages.len());
+        for (page, original) in decoded.iter().zip(pages.iter()) {
+            assert_eq!(rows(&page.field, 23), rows(&original.field, 23));
+            assert_eq!(page.piece, original.piece.map(|stone| Stone::covering(stone.kind, stone.rotation, &stone.cells().collect::<Vec<_>>()).unwrap()));
+            assert_eq!(page.comment, original.comment);
+        }
+        assert_eq!(encode_fumen(&decoded).unwrap(), code);
+        assert_eq!(quiz_pieces(&decoded[0].comment), Some((Some(StoneKind::S), vec![StoneKind::T, StoneKind::I, StoneKind::O, StoneKind::Z])));
+    }
+
+    #[test]
+    fn pieces_lock_and_mirror_between_pages() {
+        let mut field = Field::new(Mode::Marathon.layout());
+        let bottom = field.total_height() as i32 - 1;
+        for x in 0..6 {
+            field.set_cell_at_pos(&Position { x, y: bottom }, Cell::Piece(StoneKind::J));
+        }
+        let mut stone = Stone::new(Position { x: 6, y: bottom - 1 }, StoneKind::I);
+        stone.rotation = 0;
+        let mut page = FumenPage::new(field.clone(), Some(stone));
+        page.mirror = true;
+        let code = encode_fumen(&[page, FumenPage::new(Field::new(Mode::Marathon.layout()), None)]).unwrap();
+
+        assert!(code.ends_with("vhAAAA"), "the second page repeats the cleared field: {}", code);
+        let decoded = decode_fumen(&code).unwrap();
+        assert!(rows(&decoded[1].field, 23).iter().all(|row| row == ".........."));
+        assert!(decoded[0].mirror);
+
+        let mut page = FumenPage::new(field, None);
+        page.mirror = true;
+        let mut mirrored = Field::new(Mode::Marathon.layout());
+        for x in 4..10 {
+            mirrored.set_cell_at_pos(&Position { x, y: bottom }, Cell::Piece(StoneKind::J));
+        }
+        let code = encode_fumen(&[page, FumenPage::new(mirrored, None)]).unwrap();
+        assert!(code.ends_with("vhAAAA"), "the second page repeats the mirrored field: {}", code);
+        assert_eq!(rows(&decode_fumen(&code).unwrap()[1].field, 1), vec!["....JJJJJJ"]);
+    }
+
+    #[test]
+    fn quiz_comments() {
+        assert_eq!(quiz_comment(None, &[StoneKind::T, StoneKind::I]), "#Q=[](T)I");
+        assert_eq!(quiz_pieces("#Q=[](T)I"), Some((None, vec![StoneKind::T, StoneKind::I])));
+        assert_eq!(quiz_pieces("#Q=[X](T)I"), None);
+        assert_eq!(quiz_pieces("just a comment"), None);
+    }
+}
@@ -0,0 +1,150 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Garbage sent by an opponent that didn't enter the field yet, one entry per attack.
+#[derive(Debug, Clone, Default)]
+pub struct GarbageQueue {
+    attacks: VecDeque<u32>,
+}
+
+impl GarbageQueue {
+    pub fn receive(&mut self, lines: u32) {
+        if lines > 0 {
+            self.attacks.push_back(lines);
+        }
+    }
+
+    pub fn pending(&self) -> u32 {
+        self.attacks.iter().sum()
+    }
+
+    /// Cancels the oldest pending garbage with an attack and returns what's left of the attack.
+    pub fn cancel(&mut self, mut attack: u32) -> u32 {
+        while attack > 0 {
+            match self.attacks.front_mut() {
+                Some(lines) if *lines > attack => {
+                    *lines -= attack;
+                    attack = 0;
+                }
+                Some(lines) => {
+                    attack -= *lines;
+                    self.attacks.pop_front();
+                }
+                None => break,
+            }
+        }
+        attack
+    }
+
+    /// Removes all pending attacks so they can enter the field.
+    pub fn take(&mut self) -> Vec<u32> {
+        self.attacks.drain(..).collect()
+    }
+}
+
+/// When the hole of incoming garbage may move to another column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HoleSwitch {
+    /// Every row may get a new hole.
+    PerRow,
+    /// All rows of an attack share the hole, the next attack may get a new one.
+    PerAttack,
+}
+
+/// Chooses the hole columns of garbage rows. At every switch point the hole moves to another
+/// column with the change probability, 0 gives clean garbage with one long well, 1 messy garbage.
+#[derive(Debug, Clone)]
+pub struct GarbageHoles {
+    switch: HoleSwitch,
+    change_probability: f64,
+    rng: StdRng,
+    column: Option<usize>,
+}
+
+impl GarbageHoles {
+    pub fn new(switch: HoleSwitch, change_probability: f64, seed: u64) -> Self {
+        Self {
+            switch,
+            change_probability: change_probability.clamp(0.0, 1.0),
+            rng: StdRng::seed_from_u64(seed),
+            column: None,
+        }
+    }
+
+    pub fn switch(&self) -> HoleSwitch {
+        self.switch
+    }
+
+    pub fn change_probability(&self) -> f64 {
+        self.change_probability
+    }
+
+    /// The hole columns of one attack, in the order the rows are inserted.
+    pub fn attack(&mut self, rows: usize, width: usize) -> Vec<usize> {
+        let mut columns = Vec::with_capacity(rows);
+        for row in 0..rows {
+            if row == 0 || self.switch == HoleSwitch::PerRow {
+                self.next_column(width);
+            }
+            columns.extend(self.column);
+        }
+        columns
+    }
+
+    fn next_column(&mut self, width: usize) {
+        self.column = match self.column {
+            Some(column) if column < width && (width == 1 || !self.rng.gen_bool(self.change_probability)) => Some(column),
+            Some(column) if column < width => {
+                let other = self.rng.gen_range(0, width - 1);
+                Some(if other >= column { other + 1 } else { other })
+            }
+            _ => Some(self.rng.gen_range(0, width)),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::mechanics::{GarbageHoles, GarbageQueue, HoleSwitch};
+
+    #[test]
+    fn attacks_cancel_garbage() {
+        let mut queue = GarbageQueue::default();
+        queue.receive(2);
+        queue.receive(0);
+        queue.receive(4);
+        assert_eq!(queue.pending(), 6);
+
+        assert_eq!(queue.cancel(3), 0);
+        assert_eq!(queue.pending(), 3);
+        assert_eq!(queue.cancel(5), 2);
+        assert_eq!(queue.pending(), 0);
+
+        queue.receive(1);
+        queue.receive(2);
+        assert_eq!(queue.take(), vec![1, 2]);
+        assert_eq!(queue.pending(), 0);
+    }
+
+    #[test]
+    fn hole_policies() {
+        let mut clean = GarbageHoles::new(HoleSwitch::PerRow, 0.0, 1);
+        let columns: Vec<_> = (0..5).flat_map(|_| clean.attack(2, 10)).collect();
+        assert!(columns.iter().all(|&column| column == columns[0]));
+
+        let mut messy = GarbageHoles::new(HoleSwitch::PerRow, 1.0, 1);
+        let columns = messy.attack(20, 10);
+        assert!(columns.windows(2).all(|pair| pair[0] != pair[1]));
+        assert!(columns.iter().all(|&column| column < 10));
+
+        let mut per_attack = GarbageHoles::new(HoleSwitch::PerAttack, 1.0, 1);
+        let first = per_attack.attack(3, 10);
+        let second = per_attack.attack(3, 10);
+        assert!(first.iter().all(|&column| column == first[0]));
+        assert!(second.iter().all(|&column| column == second[0]));
+        assert_ne!(first[0], second[0]);
+    }
+}
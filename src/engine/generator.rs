@@ -0,0 +1,88 @@
+use crate::engine::stones::StoneKind;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::VecDeque;
+
+/// Deals the pieces in shuffled bags of all seven kinds. The same seed always produces the same
+/// sequence, so a game can be replayed from its seed.
+#[derive(Debug, Clone)]
+pub struct PieceGenerator {
+    seed: u64,
+    rng: StdRng,
+    bag: Vec<StoneKind>,
+    preset: VecDeque<StoneKind>,
+}
+
+impl PieceGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            bag: Vec::with_capacity(StoneKind::ALL.len()),
+            preset: VecDeque::new(),
+        }
+    }
+
+    /// Deals the given pieces before the first bag.
+    pub fn with_preset(self, pieces: &[StoneKind]) -> Self {
+        Self { preset: pieces.iter().copied().collect(), ..self }
+    }
+
+    pub fn random() -> Self {
+        Self::new(rand::thread_rng().gen())
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl Iterator for PieceGenerator {
+    type Item = StoneKind;
+
+    fn next(&mut self) -> Option<StoneKind> {
+        if let Some(kind) = self.preset.pop_front() {
+            return Some(kind);
+        }
+        if self.bag.is_empty() {
+            self.bag.extend_from_slice(&StoneKind::ALL);
+            self.bag.shuffle(&mut self.rng);
+        }
+        self.bag.pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::generator::PieceGenerator;
+    use crate::engine::stones::StoneKind;
+    use std::collections::HashSet;
+
+    #[test]
+    fn every_bag_holds_all_kinds() {
+        let pieces: Vec<_> = PieceGenerator::new(7).take(21).collect();
+        for bag in pieces.chunks(7) {
+            let kinds: HashSet<StoneKind> = bag.iter().copied().collect();
+            assert_eq!(kinds.len(), 7);
+        }
+    }
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let first: Vec<_> = PieceGenerator::new(42).take(30).collect();
+        let second: Vec<_> = PieceGenerator::new(42).take(30).collect();
+        let other: Vec<_> = PieceGenerator::new(43).take(30).collect();
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn preset_pieces_come_first() {
+        let preset = [StoneKind::O, StoneKind::O, StoneKind::T];
+        let pieces: Vec<_> = PieceGenerator::new(42).with_preset(&preset).take(10).collect();
+        let bag: Vec<_> = PieceGenerator::new(42).take(7).collect();
+        assert_eq!(&pieces[..3], &preset);
+        assert_eq!(&pieces[3..], &bag[..]);
+    }
+}
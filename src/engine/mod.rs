@@ -0,0 +1,29 @@
+mod attack;
+mod board;
+mod dig;
+mod field;
+mod finesse;
+mod fumen;
+mod garbage;
+mod generator;
+mod mode;
+mod queue;
+mod score;
+mod setup;
+mod stones;
+
+pub mod mechanics {
+    pub use super::attack::{line_clear_attack, t_spin, AttackTable, Clear, TSpin, BACK_TO_BACK_BONUS, COMBO_ATTACK, PERFECT_CLEAR_ATTACK};
+    pub use super::board::{Board, Row, MAX_WIDTH};
+    pub use super::dig::{DigRace, DIG_ROWS, DIG_TARGETS};
+    pub use super::field::{Field, Cell};
+    pub use super::finesse::{describe, finesse_path, landing_cells, placements, FinesseResult, FinesseTracker, Input};
+    pub use super::fumen::{decode_fumen, encode_fumen, quiz_comment, quiz_pieces, FumenPage};
+    pub use super::garbage::{GarbageHoles, GarbageQueue, HoleSwitch};
+    pub use super::generator::PieceGenerator;
+    pub use super::mode::{Mode, BoardLayout};
+    pub use super::queue::NextQueue;
+    pub use super::score::Score;
+    pub use super::setup::Setup;
+    pub use super::stones::{Stone, StoneKind, Shape};
+}
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardLayout {
+    pub width: usize,
+    pub visible_height: usize,
+    pub hidden_rows: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Mode {
+    #[default]
+    Marathon,
+    FourWide,
+    Big,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 3] = [Mode::Marathon, Mode::FourWide, Mode::Big];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Marathon => "marathon",
+            Mode::FourWide => "four-wide",
+            Mode::Big => "big",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|mode| mode.name() == name)
+    }
+
+    pub fn layout(self) -> BoardLayout {
+        match self {
+            Mode::Marathon => BoardLayout { width: 10, visible_height: 20, hidden_rows: 20 },
+            Mode::FourWide => BoardLayout { width: 4, visible_height: 20, hidden_rows: 20 },
+            Mode::Big => BoardLayout { width: 16, visible_height: 24, hidden_rows: 24 },
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use crate::engine::generator::PieceGenerator;
+use crate::engine::stones::StoneKind;
+use std::collections::VecDeque;
+
+/// The upcoming pieces. The queue always holds `capacity` pieces, how many of them are shown is up
+/// to the caller.
+#[derive(Debug, Clone)]
+pub struct NextQueue {
+    generator: PieceGenerator,
+    pieces: VecDeque<StoneKind>,
+    capacity: usize,
+}
+
+impl NextQueue {
+    pub fn new(generator: PieceGenerator, capacity: usize) -> Self {
+        let mut queue = Self {
+            generator,
+            pieces: VecDeque::with_capacity(capacity + 1),
+            capacity: capacity.max(1),
+        };
+        queue.fill();
+        queue
+    }
+
+    fn fill(&mut self) {
+        while self.pieces.len() < self.capacity {
+            let next = self.generator.next().expect("the generator never runs out of pieces");
+            self.pieces.push_back(next);
+        }
+    }
+
+    pub fn pop(&mut self) -> StoneKind {
+        let next = self.pieces.pop_front().expect("the queue is never empty");
+        self.fill();
+        next
+    }
+
+    pub fn peek(&self) -> StoneKind {
+        self.pieces[0]
+    }
+
+    pub fn preview(&self, length: usize) -> impl Iterator<Item=StoneKind> + '_ {
+        self.pieces.iter().copied().take(length)
+    }
+
+    pub fn pieces(&self) -> Vec<StoneKind> {
+        self.pieces.iter().copied().collect()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.generator.seed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::generator::PieceGenerator;
+    use crate::engine::queue::NextQueue;
+
+    #[test]
+    fn queue_follows_the_generator() {
+        let expected: Vec<_> = PieceGenerator::new(3).take(10).collect();
+        let mut queue = NextQueue::new(PieceGenerator::new(3), 5);
+
+        assert_eq!(queue.pieces(), expected[..5].to_vec());
+        assert_eq!(queue.preview(2).collect::<Vec<_>>(), expected[..2].to_vec());
+        assert_eq!(queue.pop(), expected[0]);
+        assert_eq!(queue.peek(), expected[1]);
+        assert_eq!(queue.pieces(), expected[1..6].to_vec());
+        assert_eq!(queue.seed(), 3);
+    }
+}
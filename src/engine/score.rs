@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+/// Points, cleared lines and the level they lead to, every 10 lines raise the level by one up to 255.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Score {
+    pub points: u64,
+    pub lines: u64,
+    pub level: u8,
+}
+
+impl Default for Score {
+    fn default() -> Self {
+        Self { points: 0, lines: 0, level: 1 }
+    }
+}
+
+impl Score {
+    /// Counts the lines a locked piece cleared, clearing more at once and higher levels pay more.
+    pub fn record(&mut self, lines: usize) {
+        if lines == 0 {
+            return;
+        }
+
+        self.lines += lines as u64;
+        self.points += 2u64.pow(lines as u32) * self.level as u64;
+        self.level = (1 + self.lines / 10).min(u8::MAX as u64) as u8;
+    }
+
+    /// How long a piece takes to fall one row at the current level, never faster than `minimum`.
+    pub fn fall_interval(&self, minimum: Duration) -> Duration {
+        let interval = Duration::from_millis((1000.0 * 0.75f32.powi(self.level as i32)) as u64);
+        std::cmp::max(interval, minimum)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::mechanics::Score;
+
+    #[test]
+    fn levels_raise_the_points() {
+        let mut score = Score::default();
+        score.record(4);
+        score.record(0);
+        assert_eq!(score, Score { points: 16, lines: 4, level: 1 });
+
+        score.record(4);
+        score.record(2);
+        assert_eq!(score, Score { points: 36, lines: 10, level: 2 });
+        score.record(1);
+        assert_eq!(score.points, 40);
+
+        let mut score = Score { points: 0, lines: 2549, level: 255 };
+        score.record(4);
+        assert_eq!(score.level, 255);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::engine::field::{Cell, Field};
+use crate::engine::fumen::{decode_fumen, encode_fumen, quiz_comment, quiz_pieces, FumenPage};
+use crate::engine::mode::Mode;
+use crate::engine::stones::StoneKind;
+use crate::rendering::renderer::Position;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SetupFile {
+    #[serde(default)]
+    board: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    hold: String,
+    #[serde(default)]
+    queue: String,
+}
+
+/// A position to start from: a marathon field, the held piece and the first pieces to be dealt.
+#[derive(Clone)]
+pub struct Setup {
+    pub field: Field,
+    pub hold: Option<StoneKind>,
+    pub queue: Vec<StoneKind>,
+}
+
+impl Default for Setup {
+    fn default() -> Self {
+        Self { field: Field::new(Mode::Marathon.layout()), hold: None, queue: Vec::new() }
+    }
+}
+
+impl Setup {
+    /// The rows are given from the top down and end at the bottom of the field, `.` is an empty
+    /// cell, `G` garbage and a letter a cell of that piece.
+    pub fn parse(board: &[String], hold: &str, queue: &str) -> Result<Self, String> {
+        let mut setup = Self::default();
+        let layout = Mode::Marathon.layout();
+        if board.len() > setup.field.total_height() {
+            return Err(format!("the board can have at most {} rows", setup.field.total_height()));
+        }
+        let top = setup.field.total_height() - board.len();
+        for (y, row) in board.iter().enumerate() {
+            if row.chars().count() != layout.width {
+                return Err(format!("the rows of the board have to be {} cells wide", layout.width));
+            }
+            for (x, cell) in row.chars().enumerate() {
+                let cell = Cell::from_char(cell).ok_or(format!("unknown cell '{}' on the board", cell))?;
+                setup.field.set_cell_at_pos(&Position { x: x as i32, y: (top + y) as i32 }, cell);
+            }
+        }
+
+        let pieces = |text: &str| text.chars().filter(|letter| !letter.is_whitespace())
+            .map(|letter| StoneKind::from_letter(letter).ok_or(format!("unknown piece '{}'", letter)))
+            .collect::<Result<Vec<_>, _>>();
+        let hold = pieces(hold)?;
+        if hold.len() > 1 {
+            return Err("only one piece can be held".to_string());
+        }
+        setup.hold = hold.first().copied();
+        setup.queue = pieces(queue)?;
+        Ok(setup)
+    }
+
+    /// The rows from the highest one with a block down to the bottom, as `parse` reads them.
+    pub fn rows(&self) -> Vec<String> {
+        let width = self.field.dimensions().width as i32;
+        (0..self.field.total_height() as i32)
+            .map(|y| (0..width).map(|x| self.field.get_cell_at_pos(&Position { x, y }).unwrap_or_default().to_char()).collect::<String>())
+            .skip_while(|row| row.chars().all(|cell| cell == '.'))
+            .collect()
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, String> {
+        let file: SetupFile = toml::from_str(content).map_err(|error| error.to_string())?;
+        Self::parse(&file.board, &file.hold, &file.queue)
+    }
+
+    pub fn to_toml(&self) -> String {
+        let file = SetupFile {
+            board: self.rows(),
+            hold: self.hold.map(|kind| kind.letter().to_string()).unwrap_or_default(),
+            queue: self.queue.iter().map(|kind| kind.letter()).collect(),
+        };
+        toml::to_string_pretty(&file).expect("setups can always be serialized")
+    }
+
+    /// The field of the page with the pieces of its quiz comment, if there is one.
+    pub fn from_fumen_page(page: &FumenPage) -> Self {
+        let (hold, queue) = quiz_pieces(&page.comment).unwrap_or_default();
+        Self { field: page.field.clone(), hold, queue }
+    }
+
+    pub fn to_fumen_page(&self) -> FumenPage {
+        let mut page = FumenPage::new(self.field.clone(), None);
+        if self.hold.is_some() || !self.queue.is_empty() {
+            page.comment = quiz_comment(self.hold, &self.queue);
+        }
+        page
+    }
+
+    /// Reads a fumen, using its first page, or a TOML setup.
+    pub fn from_text(content: &str) -> Result<Self, String> {
+        if content.contains("115@") {
+            decode_fumen(content).map(|pages| Self::from_fumen_page(&pages[0]))
+        } else {
+            Self::from_toml(content)
+        }
+    }
+
+    /// Whether the file at the path holds a fumen rather than a TOML setup.
+    pub fn is_fumen_path(path: &Path) -> bool {
+        path.extension().is_some_and(|extension| extension == "fumen" || extension == "txt")
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path).map_err(|error| format!("could not read {}: {}", path.display(), error))?;
+        Self::from_text(&content).map_err(|error| format!("invalid board {}: {}", path.display(), error))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = if Self::is_fumen_path(path) {
+            encode_fumen(&[self.to_fumen_page()])? + "\n"
+        } else {
+            self.to_toml()
+        };
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory).map_err(|error| format!("could not create {}: {}", directory.display(), error))?;
+        }
+        std::fs::write(path, content).map_err(|error| format!("could not write {}: {}", path.display(), error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::mechanics::{encode_fumen, Setup, StoneKind};
+
+    #[test]
+    fn toml_and_fumen_round_trip() {
+        let board = ["T.........", "TTGG..IIII", "TJJJJ.OOSZ"].iter().map(|row| row.to_string()).collect::<Vec<_>>();
+        let setup = Setup::parse(&board, "s", "TIO").unwrap();
+        assert_eq!(setup.hold, Some(StoneKind::S));
+        assert_eq!(setup.queue, vec![StoneKind::T, StoneKind::I, StoneKind::O]);
+        assert_eq!(setup.rows(), board);
+
+        let from_toml = Setup::from_text(&setup.to_toml()).unwrap();
+        assert_eq!((from_toml.rows(), from_toml.hold, &from_toml.queue), (board.clone(), setup.hold, &setup.queue));
+        let from_fumen = Setup::from_text(&encode_fumen(&[setup.to_fumen_page()]).unwrap()).unwrap();
+        assert_eq!((from_fumen.rows(), from_fumen.hold, &from_fumen.queue), (board, setup.hold, &setup.queue));
+
+        assert!(Setup::from_toml("board = [\"..........\"]\nhold = \"IO\"").is_err());
+        assert!(Setup::from_toml("board = [\"...\"]").is_err());
+        assert!(Setup::from_toml("board = [\"X.........\"]").is_err());
+    }
+}
@@ -0,0 +1,326 @@
+use rand::{thread_rng, Rng};
+use crate::engine::board::Row;
+use crate::engine::field::Field;
+use crate::rendering::renderer::{Texture, Position, Canvas, Color, Dimensions, Theme, Tile};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StoneKind {
+    I,
+    J,
+    L,
+    O,
+    S,
+    T,
+    Z,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shape {
+    pub cells: [(i32, i32); 4],
+    pub rows: [Row; 4],
+    pub size: i32,
+    pub min_x: i32,
+    pub max_x: i32,
+    pub min_y: i32,
+    pub max_y: i32,
+}
+
+impl Shape {
+    const fn new(spawn_cells: [(i32, i32); 4], size: i32, rotation: usize) -> Self {
+        let mut cells = spawn_cells;
+        let mut turn = 0;
+        while turn < rotation {
+            let mut index = 0;
+            while index < 4 {
+                let (x, y) = cells[index];
+                cells[index] = (size - 1 - y, x);
+                index += 1;
+            }
+            turn += 1;
+        }
+
+        let mut shape = Shape { cells, rows: [0; 4], size, min_x: size, max_x: 0, min_y: size, max_y: 0 };
+        let mut index = 0;
+        while index < 4 {
+            let (x, y) = cells[index];
+            shape.rows[y as usize] |= 1 << x;
+            if x < shape.min_x { shape.min_x = x; }
+            if x > shape.max_x { shape.max_x = x; }
+            if y < shape.min_y { shape.min_y = y; }
+            if y > shape.max_y { shape.max_y = y; }
+            index += 1;
+        }
+
+        shape
+    }
+
+    const fn rotations(spawn_cells: [(i32, i32); 4], size: i32) -> [Shape; 4] {
+        [
+            Shape::new(spawn_cells, size, 0),
+            Shape::new(spawn_cells, size, 1),
+            Shape::new(spawn_cells, size, 2),
+            Shape::new(spawn_cells, size, 3),
+        ]
+    }
+
+    pub fn dimensions(&self) -> Dimensions {
+        Dimensions { width: self.size as usize, height: self.size as usize }
+    }
+}
+
+static SHAPES: [[Shape; 4]; 7] = [
+    Shape::rotations([(0, 1), (1, 1), (2, 1), (3, 1)], 4),
+    Shape::rotations([(0, 0), (0, 1), (1, 1), (2, 1)], 3),
+    Shape::rotations([(2, 0), (0, 1), (1, 1), (2, 1)], 3),
+    Shape::rotations([(0, 0), (1, 0), (0, 1), (1, 1)], 2),
+    Shape::rotations([(1, 0), (2, 0), (0, 1), (1, 1)], 3),
+    Shape::rotations([(1, 0), (0, 1), (1, 1), (2, 1)], 3),
+    Shape::rotations([(0, 0), (1, 0), (1, 1), (2, 1)], 3),
+];
+
+impl StoneKind {
+    pub const ALL: [StoneKind; 7] = [StoneKind::I, StoneKind::J, StoneKind::L, StoneKind::O, StoneKind::S, StoneKind::T, StoneKind::Z];
+
+    pub fn random() -> Self {
+        let mut rng = thread_rng();
+        Self::ALL[rng.gen_range(0, Self::ALL.len())]
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn letter(self) -> char {
+        b"IJLOSTZ"[self.index()] as char
+    }
+
+    pub fn from_letter(letter: char) -> Option<Self> {
+        Self::ALL.iter().copied().find(|kind| kind.letter() == letter.to_ascii_uppercase())
+    }
+
+    pub fn shape(self, rotation: u8) -> &'static Shape {
+        &SHAPES[self.index()][rotation as usize % 4]
+    }
+
+    pub fn color(self, theme: &Theme) -> Color {
+        match self {
+            StoneKind::I => theme.pieces.i,
+            StoneKind::J => theme.pieces.j,
+            StoneKind::L => theme.pieces.l,
+            StoneKind::O => theme.pieces.o,
+            StoneKind::S => theme.pieces.s,
+            StoneKind::T => theme.pieces.t,
+            StoneKind::Z => theme.pieces.z,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stone {
+    pub kind: StoneKind,
+    pub rotation: u8,
+    pub position: Position,
+}
+
+impl Stone {
+    pub fn new(position: Position, kind: StoneKind) -> Self {
+        Self {
+            kind,
+            rotation: 0,
+            position,
+        }
+    }
+
+    /// The stone in the given rotation whose cells are exactly the given positions.
+    pub fn covering(kind: StoneKind, rotation: u8, positions: &[Position]) -> Option<Self> {
+        let mut positions = positions.to_vec();
+        positions.sort_by_key(|position| (position.y, position.x));
+        let first = *positions.first()?;
+        let &(x, y) = kind.shape(rotation).cells.iter().min_by_key(|&&(x, y)| (y, x))?;
+
+        let stone = Stone { kind, rotation: rotation % 4, position: Position { x: first.x - x, y: first.y - y } };
+        let mut cells: Vec<Position> = stone.cells().collect();
+        cells.sort_by_key(|position| (position.y, position.x));
+        Some(stone).filter(|_| cells == positions)
+    }
+
+    pub fn shape(&self) -> &'static Shape {
+        self.kind.shape(self.rotation)
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item=Position> + '_ {
+        self.shape().cells.iter().map(move |&(x, y)| Position {
+            x: self.position.x + x,
+            y: self.position.y + y,
+        })
+    }
+
+    pub fn texture(&self, tile: Tile) -> Texture {
+        let mut texture = Texture::new(self.shape().dimensions());
+        for &(x, y) in self.shape().cells.iter() {
+            texture.pixels[y as usize][x as usize] = Some(tile);
+        }
+        texture
+    }
+
+    pub fn render_at(&self, canvas: &mut Canvas, offset: &Position) {
+        let tile = canvas.theme().block_tile(self.kind.color(canvas.theme()));
+        canvas.add_texture(self.texture(tile), &self.position.offset_by(offset));
+    }
+
+    pub fn render_ghost_at(&self, canvas: &mut Canvas, offset: &Position, field: &Field) {
+        let mut ghost = *self;
+        ghost.position.y += field.board().drop_distance(self.shape(), &self.position);
+        let tile = canvas.theme().ghost_tile();
+        canvas.add_texture(ghost.texture(tile), &ghost.position.offset_by(offset));
+    }
+
+    fn fits_at(&self, field: &Field, position: &Position) -> bool {
+        !field.board().collides(self.shape(), position)
+    }
+
+    fn can_move_left(&self, field: &Field) -> bool {
+        let mut position = self.position;
+        position.move_left();
+        self.fits_at(field, &position)
+    }
+
+    fn can_move_right(&self, field: &Field) -> bool {
+        let mut position = self.position;
+        position.move_right();
+        self.fits_at(field, &position)
+    }
+
+    pub fn can_move_down(&self, field: &Field) -> bool {
+        let mut position = self.position;
+        position.move_down();
+        self.fits_at(field, &position)
+    }
+
+    pub fn move_down(&mut self, field: &Field) -> bool {
+        if self.can_move_down(field) {
+            self.position.move_down();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn move_left(&mut self, field: &Field) -> bool {
+        if self.can_move_left(field) {
+            self.position.move_left();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn move_right(&mut self, field: &Field) -> bool {
+        if self.can_move_right(field) {
+            self.position.move_right();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn rotate(&mut self, field: &Field) -> bool {
+        self.rotate_by(field, 1)
+    }
+
+    pub fn rotate_counterclockwise(&mut self, field: &Field) -> bool {
+        self.rotate_by(field, 3)
+    }
+
+    pub fn rotate_180(&mut self, field: &Field) -> bool {
+        self.rotate_by(field, 2)
+    }
+
+    /// Moves the stone up until it no longer overlaps the stack, e.g. after garbage rose into it.
+    /// Returns `false` if that pushed it out of the top of the field or it doesn't fit at any height.
+    pub fn push_up(&mut self, field: &Field) -> bool {
+        for _ in 0..field.total_height() {
+            if self.fits_at(field, &self.position) {
+                return self.position.y + self.shape().min_y >= 0;
+            }
+            self.position.y -= 1;
+        }
+        false
+    }
+
+    /// Rotates clockwise by the given number of quarter turns.
+    pub fn rotate_by(&mut self, field: &Field, quarter_turns: u8) -> bool {
+        let rotated = Stone { rotation: (self.rotation + quarter_turns) % 4, ..*self };
+        if rotated.fits_at(field, &rotated.position) {
+            *self = rotated;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::stones::{Stone, StoneKind};
+    use crate::engine::field::{Field, Cell};
+    use crate::rendering::renderer::Position;
+
+    #[test]
+    fn rotation_states() {
+        let mut t_stone = Stone::new(Position{x: 2, y: -1}, StoneKind::T);
+        t_stone.rotation = 1;
+        let cells: Vec<_> = t_stone.cells().collect();
+        let expected = vec![Position{x: 4, y: 0}, Position{x: 3, y: -1}, Position{x: 3, y: 0}, Position{x: 3, y: 1}];
+        assert_eq!(expected, cells);
+
+        let i_shape = StoneKind::I.shape(1);
+        assert_eq!((i_shape.min_x, i_shape.max_x, i_shape.min_y, i_shape.max_y), (2, 2, 0, 3));
+        assert_eq!(StoneKind::O.shape(3).rows, StoneKind::O.shape(0).rows);
+    }
+
+    #[test]
+    fn blocked_by_cells() {
+        let mut field = Field::default();
+        field.set_cell_at_pos(&Position{x: 1, y: 5}, Cell::Garbage);
+        field.set_cell_at_pos(&Position{x: 5, y: 5}, Cell::Garbage);
+        field.set_cell_at_pos(&Position{x: 3, y: 7}, Cell::Garbage);
+
+        let mut t_stone = Stone::new(Position{x: 2, y: 4}, StoneKind::T);
+        assert!(!t_stone.move_left(&field));
+        assert!(!t_stone.move_right(&field));
+        assert!(t_stone.move_down(&field));
+        assert!(!t_stone.move_down(&field));
+    }
+
+    #[test]
+    fn rotation_is_blocked_by_cells() {
+        let mut field = Field::default();
+        field.set_cell_at_pos(&Position{x: 3, y: 2}, Cell::Garbage);
+
+        let mut t_stone = Stone::new(Position{x: 2, y: 0}, StoneKind::T);
+        assert!(!t_stone.rotate(&field));
+        assert_eq!(t_stone.rotation, 0);
+        t_stone.position.x -= 1;
+        assert!(t_stone.rotate(&field));
+        assert_eq!(t_stone.rotation, 1);
+        assert!(t_stone.rotate_counterclockwise(&field));
+        assert_eq!(t_stone.rotation, 0);
+        assert!(t_stone.rotate_180(&field));
+        assert_eq!(t_stone.rotation, 2);
+    }
+
+    #[test]
+    fn pushing_up_gives_up() {
+        let mut field = Field::default();
+        field.set_cell_at_pos(&Position{x: 3, y: 6}, Cell::Garbage);
+
+        let mut t_stone = Stone::new(Position{x: 2, y: 5}, StoneKind::T);
+        assert!(t_stone.push_up(&field));
+        assert_eq!(t_stone.position.y, 4);
+
+        let mut outside = Stone::new(Position{x: -5, y: 5}, StoneKind::T);
+        assert!(!outside.push_up(&field));
+    }
+}
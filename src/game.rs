@@ -1,21 +1,31 @@
-use std::time::{Duration};
+use std::time::{Duration, Instant};
 use tokio::time::interval;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::io::AsyncWriteExt;
-use crate::rendering::renderer::{Canvas, Position};
-use crate::views::views::PlayView;
+use crate::config::settings::Config;
+use crate::net::network::StateStream;
+use crate::rendering::renderer::{Canvas, Position, Theme};
+use crate::stats::statistics::StatsHistory;
+use crate::views::views::{Context, Transition, View};
 
 pub struct Game {
-    pub current_view: PlayView,
+    pub views: Vec<Box<dyn View>>,
+    pub context: Context,
     pub running: bool,
+    /// Spectators watching the game, see `tet-rs --stream`.
+    pub stream: Option<StateStream>,
 }
 
 impl Game {
-    pub fn create() -> Arc<Mutex<Game>> {
+    pub fn create(config: Config, themes: Vec<Theme>, stats: Option<StatsHistory>, stream: Option<StateStream>, first_view: Box<dyn View>) -> Arc<Mutex<Game>> {
+        let context = Context::new(config, themes, stats);
+
         Arc::new(Mutex::new(Game {
-            current_view: PlayView::default(),
+            views: vec![first_view],
+            context,
             running: true,
+            stream,
         }))
     }
 
@@ -23,27 +33,36 @@ impl Game {
         let read_input = Self::read_input(game_handle.clone());
         let render = Self::render(game_handle.clone());
         tokio::spawn(render);
-        {
-            let mut game = game_handle.lock().await;
-            game.current_view.on_create(game_handle.clone());
-        }
         read_input.await;
     }
 
     pub async fn render(state_handle: Arc<Mutex<Game>>) -> tokio::io::Result<()> {
         let mut screen = tokio::io::stdout();
-        let mut render_loop = interval(Duration::from_millis(1000 / 30));
+        let mut frame_duration = Duration::from_millis(1000 / 30);
+        let mut render_loop = interval(frame_duration);
         let mut canvas = Canvas::default();
 
         loop {
-            canvas.clear();
             render_loop.tick().await;
             {
-                let game = state_handle.lock().await;
+                let mut game = state_handle.lock().await;
+                game.update(Instant::now());
                 if !game.running {
                     return Ok(());
                 }
-                game.current_view.render_at(&mut canvas, Position { x: 0, y: 0 });
+                game.publish();
+
+                canvas.set_theme(game.context.theme());
+                canvas.set_render_mode(game.context.config.render_mode);
+                canvas.clear();
+                if let Some(view) = game.views.last() {
+                    view.render_at(&mut canvas, Position { x: 0, y: 0 }, &game.context);
+                }
+
+                if game.context.config.frame_duration() != frame_duration {
+                    frame_duration = game.context.config.frame_duration();
+                    render_loop = interval(frame_duration);
+                }
             }
             screen.write_all(canvas.get_printable_string().as_bytes()).await?;
             screen.flush().await?;
@@ -60,7 +79,8 @@ impl Game {
             match events.next().await {
                 Some(Ok(event)) => {
                     let mut game = state_handle.lock().await;
-                    if !game.handle_input(&event) {
+                    game.handle_input(&event);
+                    if !game.running {
                         return;
                     }
                 }
@@ -73,22 +93,61 @@ impl Game {
         }
     }
 
-    fn handle_input(&mut self, event: &crossterm::event::Event) -> bool {
-        use crossterm::event::{Event, KeyEvent, KeyCode};
+    fn update(&mut self, now: Instant) {
+        let transition = match self.views.last_mut() {
+            Some(view) => view.update(now, &mut self.context),
+            None => Transition::Quit,
+        };
+        self.apply(transition, now);
+    }
 
-        match event {
-            Event::Key(KeyEvent { code, modifiers: _ }) => {
-                match code {
-                    KeyCode::Char('q') => {
-                        self.running = false;
-                        return false;
-                    }
-                    _ => self.current_view.handle_input(event)
-                }
+    /// Streams the topmost game, a paused game stays visible to the spectators.
+    fn publish(&mut self) {
+        if let Some(stream) = &mut self.stream {
+            if let Some(snapshot) = self.views.iter().rev().find_map(|view| view.snapshot()) {
+                stream.publish(snapshot);
             }
-            _ => ()
+        }
+    }
+
+    fn handle_input(&mut self, event: &crossterm::event::Event) {
+        let now = Instant::now();
+        let transition = match self.views.last_mut() {
+            Some(view) => view.handle_input(event, now, &mut self.context),
+            None => Transition::Quit,
         };
+        self.apply(transition, now);
+    }
+
+    fn close_views(&mut self, count: usize) {
+        for _ in 0..count {
+            if let Some(mut view) = self.views.pop() {
+                view.on_exit(&mut self.context);
+            }
+        }
+    }
+
+    fn apply(&mut self, transition: Transition, now: Instant) {
+        match transition {
+            Transition::None => return,
+            Transition::Push(view) => self.views.push(view),
+            Transition::Pop => {
+                self.close_views(1);
+                if let Some(view) = self.views.last_mut() {
+                    view.on_resume(now, &self.context);
+                }
+            }
+            Transition::Replace(view) => {
+                self.close_views(1);
+                self.views.push(view);
+            }
+            Transition::Reset(view) => {
+                self.close_views(self.views.len());
+                self.views.push(view);
+            }
+            Transition::Quit => self.close_views(self.views.len()),
+        }
 
-        true
+        self.running = !self.views.is_empty();
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,13 @@
+#![allow(dead_code, clippy::module_inception)]
+
+pub mod bot;
+pub mod config;
+pub mod engine;
+pub mod game;
+pub mod net;
+pub mod opener;
+pub mod puzzle;
+pub mod rendering;
+pub mod sim;
+pub mod stats;
+pub mod views;
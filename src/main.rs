@@ -1,23 +1,323 @@
-#![feature(clamp, async_closure, drain_filter)]
-#![allow(dead_code)]
-
-
-use crate::game::Game;
+use tet_rs::bot::ai::{BotChoice, Weights};
+use tet_rs::config::settings::Config;
+use tet_rs::game::Game;
+use tet_rs::rendering::renderer::{Canvas, Dimensions, Position, Texture, Theme};
+use tet_rs::stats::statistics::StatsHistory;
+use tet_rs::engine::mechanics::{decode_fumen, FumenPage, Mode, PieceGenerator, DIG_TARGETS};
+use tet_rs::sim::simulation::{write_results, Format, Simulation, Summary, Tuning, DEFAULT_MAX_PIECES};
+use tet_rs::opener::openers::OpenerLibrary;
+use tet_rs::puzzle::puzzles::Campaign;
+use tet_rs::net::network::{host, join, Session, Spectator, StateStream, StreamAddress, DEFAULT_PORT};
+use tet_rs::views::views::{DigView, EditorView, MenuView, NetworkView, OpenerView, Opponent, PcTrainerView, PlayView, PuzzleSelectView, SurvivalView, TrainerView, VersusView, View, WatchView};
 use crossterm::terminal::{enable_raw_mode, disable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, Clear, ClearType};
 use crossterm::cursor::{Show, Hide};
 use crossterm::execute;
 use crossterm::Result;
 use std::io::{stdout, Write};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tokio::net::TcpListener;
+
+async fn host_game(port: Option<String>) -> std::result::Result<Session, String> {
+    let port = match port {
+        Some(port) => port.parse::<u16>().map_err(|_| format!("invalid port '{}'", port))?,
+        None => DEFAULT_PORT,
+    };
+    let mut listener = TcpListener::bind(("0.0.0.0", port)).await.map_err(|error| format!("could not listen on port {}: {}", port, error))?;
+    eprintln!("waiting for an opponent on port {}", port);
+    host(&mut listener, rand::random()).await.map_err(|error| error.to_string())
+}
+
+async fn join_game(address: Option<String>) -> std::result::Result<Session, String> {
+    let address = address.ok_or("join needs the address of the host, e.g. tet-rs join 192.168.0.2")?;
+    let address = if address.contains(':') { address } else { format!("{}:{}", address, DEFAULT_PORT) };
+    join(&address).await.map_err(|error| error.to_string())
+}
 
-mod game;
-mod views;
-mod rendering;
+async fn watch_game(address: Option<String>) -> std::result::Result<Box<dyn View>, String> {
+    let address = address.ok_or("watch needs the address of the stream, e.g. tet-rs watch 192.168.0.2:7879")?;
+    let address = StreamAddress::parse(&address);
+    let spectator = Spectator::connect(&address).await.map_err(|error| format!("could not watch {}: {}", address, error))?;
+    Ok(Box::new(WatchView::new(address, spectator)))
+}
+
+/// Takes an option like `--stream <address>` out of the arguments, `usage` explains a missing value.
+fn take_option(args: &mut Vec<String>, name: &str, usage: &str) -> std::result::Result<Option<String>, String> {
+    let index = match args.iter().position(|arg| arg == name) {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+    if index + 1 >= args.len() {
+        return Err(usage.to_string());
+    }
+    let value = args.remove(index + 1);
+    args.remove(index);
+    Ok(Some(value))
+}
+
+fn number_option<T: std::str::FromStr>(args: &mut Vec<String>, name: &str, default: T) -> std::result::Result<T, String> {
+    match take_option(args, name, &format!("{} needs a number", name))? {
+        Some(value) => value.parse().map_err(|_| format!("{} needs a number, not '{}'", name, value)),
+        None => Ok(default),
+    }
+}
+
+/// `tet-rs simulate`, plays games without a terminal and writes the results to stdout or a file
+/// and the summary to stderr.
+fn simulate(mut args: Vec<String>) -> std::result::Result<(), String> {
+    let weights = match take_option(&mut args, "--weights", "--weights needs a file written by tet-rs tune")? {
+        Some(path) => Weights::load_from(Path::new(&path))?,
+        None => Weights::default(),
+    };
+    let bot = take_option(&mut args, "--bot", "--bot needs 'builtin' or the command of a TBP bot")?.unwrap_or_else(|| "builtin".to_string());
+    let bot = BotChoice::parse(&bot, &weights);
+    let mode = match take_option(&mut args, "--mode", "--mode needs a mode, e.g. --mode marathon")? {
+        Some(name) => Mode::from_name(&name).ok_or(format!("unknown mode '{}'", name))?,
+        None => Mode::Marathon,
+    };
+    let format = match take_option(&mut args, "--format", "--format needs csv or json")? {
+        Some(name) => Format::from_name(&name).ok_or(format!("unknown format '{}', use csv or json", name))?,
+        None => Format::Csv,
+    };
+    let output = take_option(&mut args, "--output", "--output needs a file")?;
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    let simulation = Simulation {
+        bot,
+        mode,
+        games: number_option(&mut args, "--games", 100)?,
+        seed: number_option(&mut args, "--seed", rand::random())?,
+        max_pieces: number_option(&mut args, "--max-pieces", DEFAULT_MAX_PIECES)?,
+        threads: number_option(&mut args, "--threads", threads)?,
+    };
+    if let Some(argument) = args.first() {
+        return Err(format!("unknown argument '{}', simulate takes --bot, --weights, --games, --seed, --mode, --max-pieces, --threads, --format and --output", argument));
+    }
+
+    let start = Instant::now();
+    let results = simulation.run()?;
+    let summary = Summary::new(&results, start.elapsed());
+
+    let written = match &output {
+        Some(path) => File::create(path).and_then(|file| write_results(&mut BufWriter::new(file), &results, &summary, format)),
+        None => write_results(&mut stdout().lock(), &results, &summary, format),
+    };
+    written.map_err(|error| format!("could not write the results: {}", error))?;
+
+    let mut errors: Vec<&str> = results.iter().filter_map(|result| result.error.as_deref()).collect();
+    errors.dedup();
+    errors.iter().for_each(|error| eprintln!("{}", error));
+    eprintln!("seed {}, {} pieces at most", simulation.seed, simulation.max_pieces);
+    eprintln!("{}", summary);
+    Ok(())
+}
+
+/// `tet-rs tune`, evolves the weights of the built-in bot and writes the best ones after every
+/// generation, so stopping a long run keeps what it found so far.
+fn tune(mut args: Vec<String>) -> std::result::Result<(), String> {
+    let start = match take_option(&mut args, "--weights", "--weights needs the file of the weights to start from")? {
+        Some(path) => Weights::load_from(Path::new(&path))?,
+        None => Weights::default(),
+    };
+    let mode = match take_option(&mut args, "--mode", "--mode needs a mode, e.g. --mode marathon")? {
+        Some(name) => Mode::from_name(&name).ok_or(format!("unknown mode '{}'", name))?,
+        None => Mode::Marathon,
+    };
+    let output = take_option(&mut args, "--output", "--output needs a file")?.unwrap_or_else(|| "weights.toml".to_string());
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    let tuning = Tuning {
+        start,
+        mode,
+        population: number_option(&mut args, "--population", 16)?,
+        generations: number_option(&mut args, "--generations", 20)?,
+        games: number_option(&mut args, "--games", 8)?,
+        seed: number_option(&mut args, "--seed", rand::random())?,
+        max_pieces: number_option(&mut args, "--max-pieces", 500)?,
+        threads: number_option(&mut args, "--threads", threads)?,
+    };
+    if let Some(argument) = args.first() {
+        return Err(format!("unknown argument '{}', tune takes --weights, --population, --generations, --games, --seed, --mode, --max-pieces, --threads and --output", argument));
+    }
+
+    eprintln!("seed {}, {} candidates playing {} games of {} pieces at most", tuning.seed, tuning.population, tuning.games, tuning.max_pieces);
+    let start = Instant::now();
+    let mut saved = Ok(());
+    tuning.run(|generation| {
+        eprintln!("generation {}/{} after {:.0}s: best {:.1}, mean {:.1}", generation.number, tuning.generations, start.elapsed().as_secs_f64(), generation.best_fitness, generation.mean_fitness);
+        eprintln!("  {}", generation.best);
+        saved = generation.best.save_to(Path::new(&output));
+    })?;
+    saved?;
+    eprintln!("wrote the best weights to {}", output);
+    Ok(())
+}
+
+/// The lowest rows of a fumen page with its piece, at least the four lowest.
+fn fumen_texture(page: &FumenPage, theme: &Theme) -> Texture {
+    let field = &page.field;
+    let width = field.dimensions().width;
+    let total_height = field.total_height() as i32;
+    let piece_cells: Vec<Position> = page.piece.iter().flat_map(|piece| piece.cells()).collect();
+    let top = (0..total_height)
+        .find(|&y| piece_cells.iter().any(|cell| cell.y == y) || (0..width as i32).any(|x| !field.get_cell_at_pos(&Position { x, y }).unwrap_or_default().is_empty()))
+        .map_or(total_height - 4, |y| (y - 1).clamp(0, total_height - 4));
+
+    let mut texture = Texture::new(Dimensions { width, height: (total_height - top) as usize });
+    for (row, y) in texture.pixels.iter_mut().zip(top..) {
+        for (pixel, x) in row.iter_mut().zip(0..) {
+            let position = Position { x, y };
+            *pixel = Some(match &page.piece {
+                Some(piece) if piece_cells.contains(&position) => theme.block_tile(piece.kind.color(theme)),
+                _ => field.get_cell_at_pos(&position).unwrap_or_default().to_tile(theme),
+            });
+        }
+    }
+    texture
+}
+
+/// `tet-rs fumen render <code>`, prints every page of a fumen with the colors of the theme.
+fn fumen(args: &[String], config: &Config) -> std::result::Result<(), String> {
+    let code = match args {
+        [command, code] if command == "render" => code,
+        _ => return Err("usage: tet-rs fumen render <code>".to_string()),
+    };
+    let pages = decode_fumen(code)?;
+    let (themes, _) = Theme::all();
+    let theme = themes.iter().find(|theme| theme.name == config.theme).unwrap_or(&themes[0]);
+
+    for (index, page) in pages.iter().enumerate() {
+        let texture = fumen_texture(page, theme);
+        let inner = texture.dimensions;
+        let mut text = vec![format!("page {}/{}", index + 1, pages.len())];
+        text.extend(page.comment.lines().map(str::to_string));
+        let text_width = text.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+
+        let mut canvas = Canvas::new(Dimensions { width: inner.width + 4 + text_width, height: (inner.height + 1).max(text.len()) });
+        canvas.set_theme(theme);
+        canvas.set_render_mode(config.render_mode);
+        canvas.clear();
+        canvas.add_well(&Position { x: 0, y: 0 }, &inner);
+        canvas.add_texture(texture, &Position { x: 1, y: 0 });
+        let text: Vec<&str> = text.iter().map(String::as_str).collect();
+        canvas.add_themed_paragraph(&text, Position { x: inner.width as i32 + 4, y: 0 });
+        canvas.printable_lines().iter().for_each(|line| println!("{}", line));
+    }
+    Ok(())
+}
+
+fn or_exit<T>(result: std::result::Result<T, String>) -> T {
+    result.unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    })
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("simulate") => {
+            or_exit(simulate(args.split_off(1)));
+            return Ok(());
+        }
+        Some("tune") => {
+            or_exit(tune(args.split_off(1)));
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+    if args.first().map(String::as_str) == Some("fumen") {
+        or_exit(fumen(&args[1..], &config));
+        return Ok(());
+    }
+
+    let stream_address = take_option(&mut args, "--stream", "--stream needs an address, e.g. --stream 7879 or --stream /tmp/tet-rs.sock");
+    let bot = or_exit(take_option(&mut args, "--bot", "--bot needs the command of a TBP bot, e.g. --bot tbp-reference-bot")).map(|bot| BotChoice::parse(&bot, &config.bot_weights));
+    let stream = match or_exit(stream_address).map(|address| StreamAddress::parse(&address)) {
+        Some(address) => {
+            let stream = or_exit(StateStream::bind(&address).await.map_err(|error| format!("could not stream to {}: {}", address, error)));
+            eprintln!("streaming to {}", address);
+            Some(stream)
+        }
+        None => None,
+    };
+    let argument = args.get(1).cloned();
+
+    let now = Instant::now();
+    let first_view: Box<dyn View> = match args.first().map(String::as_str) {
+        Some("host") => Box::new(NetworkView::new(or_exit(host_game(argument).await), Instant::now())),
+        Some("join") => Box::new(NetworkView::new(or_exit(join_game(argument).await), Instant::now())),
+        Some("watch") => or_exit(watch_game(argument).await),
+        Some("finesse") => Box::new(TrainerView::new(PieceGenerator::random())),
+        Some("pc") => match &argument {
+            Some(code) => Box::new(or_exit(PcTrainerView::from_fumen(code))),
+            None => Box::new(PcTrainerView::random()),
+        },
+        Some("opener") => {
+            let library = or_exit(OpenerLibrary::load());
+            let selected = match &argument {
+                Some(name) => library.position(name).unwrap_or_else(|| {
+                    let names: Vec<_> = library.openers().iter().map(|opener| opener.name.as_str()).collect();
+                    eprintln!("unknown opener '{}', available openers: {}", name, names.join(", "));
+                    std::process::exit(1);
+                }),
+                None => 0,
+            };
+            Box::new(OpenerView::new(library, selected))
+        }
+        Some("editor") => Box::new(EditorView::new(argument.map(PathBuf::from).or_else(EditorView::default_path))),
+        Some("puzzle") => match &argument {
+            Some(path) if Path::new(path).exists() => Box::new(PuzzleSelectView::new(or_exit(Campaign::from_file(Path::new(path))))),
+            Some(code) => Box::new(PuzzleSelectView::new(or_exit(Campaign::from_fumen(code)))),
+            None => Box::new(PuzzleSelectView::load()),
+        },
+        Some("versus") => match bot {
+            Some(BotChoice::External(command)) => Box::new(VersusView::new(Opponent::External(command), now)),
+            _ => Box::new(VersusView::against_cpu(config.cpu_difficulty, config.bot_weights.clone(), now)),
+        },
+        Some("two-player") => Box::new(VersusView::local(now)),
+        Some("survival") => Box::new(SurvivalView::new(now)),
+        Some("dig") => {
+            let lines = argument.map_or(Ok(DIG_TARGETS[0]), |lines| lines.parse().map_err(|_| format!("invalid number of lines '{}'", lines)));
+            Box::new(DigView::new(or_exit(lines), now))
+        }
+        Some(name) => match Mode::from_name(name) {
+            Some(mode) => match bot {
+                Some(bot) => Box::new(PlayView::watching(mode, bot, now)),
+                None => Box::new(PlayView::new(mode, now)),
+            },
+            None => {
+                let modes: Vec<_> = Mode::ALL.iter().map(|mode| mode.name()).collect();
+                eprintln!("unknown mode '{}', available modes: {}, finesse, pc, opener, editor, puzzle, dig, survival, versus, two-player, host, join, watch, fumen, simulate, tune", name, modes.join(", "));
+                return Ok(());
+            }
+        },
+        None => Box::new(MenuView::new(config.default_mode, now)),
+    };
+
+    let (themes, theme_errors) = Theme::all();
+    theme_errors.iter().for_each(|error| eprintln!("{}", error));
+
+    let stats = match StatsHistory::load() {
+        Ok(stats) => Some(stats),
+        Err(error) => {
+            eprintln!("{}, statistics won't be recorded", error);
+            None
+        }
+    };
+
     execute!(stdout(), EnterAlternateScreen, Hide)?;
     enable_raw_mode()?;
-    let game = Game::create();
+    let game = Game::create(config, themes, stats, stream, first_view);
     Game::run(game).await;
     disable_raw_mode()?;
     execute!(stdout(), Clear(ClearType::All), LeaveAlternateScreen, Show)?;
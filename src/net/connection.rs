@@ -0,0 +1,299 @@
+use crate::net::protocol::{Message, PROTOCOL_VERSION};
+use std::time::{Duration, Instant};
+use tokio::io::{split, AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, ReadHalf, WriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{self, error::TryRecvError, UnboundedReceiver, UnboundedSender};
+use tokio::time::timeout;
+
+pub const PING_INTERVAL: Duration = Duration::from_secs(1);
+/// The opponent counts as gone once nothing arrived for this long, pings are answered even when
+/// nothing happens in its game.
+pub const TIMEOUT: Duration = Duration::from_secs(5);
+/// Messages and streamed states are far shorter, a longer line means the other side is broken.
+pub const MAX_MESSAGE_BYTES: u64 = 64 * 1024;
+
+type Reader = BufReader<ReadHalf<TcpStream>>;
+type Writer = WriteHalf<TcpStream>;
+
+#[derive(Debug)]
+pub enum NetError {
+    Io(std::io::Error),
+    Protocol(String),
+    VersionMismatch { ours: u32, theirs: u32 },
+    Refused(String),
+    TimedOut,
+    Closed,
+}
+
+impl std::fmt::Display for NetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetError::Io(error) => write!(f, "connection failed: {}", error),
+            NetError::Protocol(reason) => write!(f, "received {}", reason),
+            NetError::VersionMismatch { ours, theirs } => write!(f, "the opponent uses protocol version {}, this is version {}", theirs, ours),
+            NetError::Refused(reason) => write!(f, "the host refused the game: {}", reason),
+            NetError::TimedOut => write!(f, "the connection timed out"),
+            NetError::Closed => write!(f, "the connection was closed"),
+        }
+    }
+}
+
+fn unexpected(message: &Message) -> NetError {
+    NetError::Protocol(format!("an unexpected message during the handshake: {:?}", message))
+}
+
+/// Reads one line of at most `MAX_MESSAGE_BYTES`, `None` once the other side closed the connection.
+pub async fn read_line<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<String>, NetError> {
+    let mut line = String::new();
+    let read = reader.take(MAX_MESSAGE_BYTES).read_line(&mut line).await.map_err(NetError::Io)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if read as u64 == MAX_MESSAGE_BYTES && !line.ends_with('\n') {
+        return Err(NetError::Protocol(format!("a message longer than {} bytes", MAX_MESSAGE_BYTES)));
+    }
+    Ok(Some(line))
+}
+
+async fn read_message(reader: &mut Reader) -> Result<Message, NetError> {
+    let line = read_line(reader).await?.ok_or(NetError::Closed)?;
+    Message::from_line(&line).map_err(NetError::Protocol)
+}
+
+async fn write_message(writer: &mut Writer, message: &Message) -> Result<(), NetError> {
+    writer.write_all(message.to_line().as_bytes()).await.map_err(NetError::Io)
+}
+
+async fn read_handshake(reader: &mut Reader) -> Result<Message, NetError> {
+    timeout(TIMEOUT, read_message(reader)).await.map_err(|_| NetError::TimedOut)?
+}
+
+/// A connected opponent and the seed both sides deal their pieces from.
+pub struct Session {
+    pub seed: u64,
+    pub connection: Connection,
+}
+
+/// Waits for one opponent, checks its protocol version and sends it the seed of the game.
+pub async fn host(listener: &mut TcpListener, seed: u64) -> Result<Session, NetError> {
+    let (stream, _) = listener.accept().await.map_err(NetError::Io)?;
+    let (reader, mut writer) = split(stream);
+    let mut reader = BufReader::new(reader);
+
+    match read_handshake(&mut reader).await? {
+        Message::Hello { version } if version == PROTOCOL_VERSION => {}
+        Message::Hello { version } => {
+            let reason = format!("the host uses protocol version {}, you use version {}", PROTOCOL_VERSION, version);
+            write_message(&mut writer, &Message::Refused { reason }).await?;
+            return Err(NetError::VersionMismatch { ours: PROTOCOL_VERSION, theirs: version });
+        }
+        message => return Err(unexpected(&message)),
+    }
+
+    write_message(&mut writer, &Message::Welcome { version: PROTOCOL_VERSION, seed }).await?;
+    Ok(Session { seed, connection: Connection::start(reader, writer, Instant::now()) })
+}
+
+/// Connects to a host, e.g. `192.168.0.2:7878`, and receives the seed of the game.
+pub async fn join(address: &str) -> Result<Session, NetError> {
+    let stream = timeout(TIMEOUT, TcpStream::connect(address)).await
+        .map_err(|_| NetError::TimedOut)?
+        .map_err(NetError::Io)?;
+    let (reader, mut writer) = split(stream);
+    let mut reader = BufReader::new(reader);
+
+    write_message(&mut writer, &Message::Hello { version: PROTOCOL_VERSION }).await?;
+    match read_handshake(&mut reader).await? {
+        Message::Welcome { version, seed } if version == PROTOCOL_VERSION => {
+            Ok(Session { seed, connection: Connection::start(reader, writer, Instant::now()) })
+        }
+        Message::Welcome { version, .. } => Err(NetError::VersionMismatch { ours: PROTOCOL_VERSION, theirs: version }),
+        Message::Refused { reason } => Err(NetError::Refused(reason)),
+        message => Err(unexpected(&message)),
+    }
+}
+
+/// The game side of a connection. Reading and writing happen in tasks of their own, the views
+/// only queue messages and poll for received ones once per frame.
+pub struct Connection {
+    outgoing: UnboundedSender<Message>,
+    incoming: UnboundedReceiver<Result<Message, NetError>>,
+    last_received: Instant,
+    last_ping: Instant,
+    pending_ping: Option<(u32, Instant)>,
+    next_ping_id: u32,
+    ping: Option<Duration>,
+    error: Option<NetError>,
+}
+
+impl Connection {
+    fn start(mut reader: Reader, mut writer: Writer, now: Instant) -> Self {
+        let (outgoing, mut queued) = mpsc::unbounded_channel::<Message>();
+        let (received, incoming) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            while let Some(message) = queued.recv().await {
+                if write_message(&mut writer, &message).await.is_err() {
+                    return;
+                }
+            }
+            let _ = writer.shutdown().await;
+        });
+        tokio::spawn(async move {
+            loop {
+                let message = read_message(&mut reader).await;
+                let failed = message.is_err();
+                if received.send(message).is_err() || failed {
+                    return;
+                }
+            }
+        });
+
+        Self {
+            outgoing,
+            incoming,
+            last_received: now,
+            last_ping: now,
+            pending_ping: None,
+            next_ping_id: 0,
+            ping: None,
+            error: None,
+        }
+    }
+
+    pub fn send(&mut self, message: Message) {
+        if self.error.is_none() && self.outgoing.send(message).is_err() {
+            self.error = Some(NetError::Closed);
+        }
+    }
+
+    /// The messages that arrived since the last call. Answers pings, sends its own every
+    /// `PING_INTERVAL` and gives up on the opponent after `TIMEOUT` without any message.
+    pub fn receive(&mut self, now: Instant) -> Vec<Message> {
+        let mut messages = Vec::new();
+        while self.error.is_none() {
+            match self.incoming.try_recv() {
+                Ok(Ok(message)) => {
+                    self.last_received = now;
+                    match message {
+                        Message::Ping { id } => self.send(Message::Pong { id }),
+                        Message::Pong { id } => {
+                            if let Some((_, sent)) = self.pending_ping.filter(|&(pending, _)| pending == id) {
+                                self.ping = Some(now.saturating_duration_since(sent));
+                                self.pending_ping = None;
+                            }
+                        }
+                        message => messages.push(message),
+                    }
+                }
+                Ok(Err(error)) => self.error = Some(error),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Closed) => self.error = Some(NetError::Closed),
+            }
+        }
+
+        if self.error.is_none() && now.saturating_duration_since(self.last_received) >= TIMEOUT {
+            self.error = Some(NetError::TimedOut);
+        }
+        if self.error.is_none() && now.saturating_duration_since(self.last_ping) >= PING_INTERVAL {
+            self.send(Message::Ping { id: self.next_ping_id });
+            self.pending_ping = Some((self.next_ping_id, now));
+            self.next_ping_id = self.next_ping_id.wrapping_add(1);
+            self.last_ping = now;
+        }
+        messages
+    }
+
+    /// The round trip time of the last answered ping.
+    pub fn ping(&self) -> Option<Duration> {
+        self.ping
+    }
+
+    /// Why the connection ended, `None` while it's up.
+    pub fn error(&self) -> Option<&NetError> {
+        self.error.as_ref()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::net::network::{host, join, Connection, Message, NetError, MAX_MESSAGE_BYTES, PROTOCOL_VERSION, TIMEOUT};
+    use std::time::{Duration, Instant};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::time::delay_for;
+
+    async fn next_messages(connection: &mut Connection) -> Vec<Message> {
+        for _ in 0..200 {
+            let messages = connection.receive(Instant::now());
+            if !messages.is_empty() || !connection.is_connected() {
+                return messages;
+            }
+            delay_for(Duration::from_millis(5)).await;
+        }
+        Vec::new()
+    }
+
+    #[tokio::test]
+    async fn peers_on_localhost() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let (hosted, joined) = tokio::join!(host(&mut listener, 42), join(&address));
+        let (mut hosted, mut joined) = (hosted.unwrap(), joined.unwrap());
+        assert_eq!(joined.seed, 42);
+
+        joined.connection.send(Message::Attack { lines: 4 });
+        assert_eq!(next_messages(&mut hosted.connection).await, vec![Message::Attack { lines: 4 }]);
+        hosted.connection.send(Message::GameOver);
+        assert_eq!(next_messages(&mut joined.connection).await, vec![Message::GameOver]);
+
+        joined.connection.receive(Instant::now() + TIMEOUT);
+        assert!(matches!(joined.connection.error(), Some(NetError::TimedOut)));
+
+        drop(joined);
+        assert_eq!(next_messages(&mut hosted.connection).await, vec![]);
+        assert!(matches!(hosted.connection.error(), Some(NetError::Closed)));
+    }
+
+    #[tokio::test]
+    async fn other_versions_are_refused() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let client = async {
+            let mut stream = TcpStream::connect(address).await.unwrap();
+            let hello = Message::Hello { version: PROTOCOL_VERSION + 1 };
+            stream.write_all(hello.to_line().as_bytes()).await.unwrap();
+            let mut line = String::new();
+            BufReader::new(stream).read_line(&mut line).await.unwrap();
+            Message::from_line(&line).unwrap()
+        };
+        let (hosted, answer) = tokio::join!(host(&mut listener, 42), client);
+
+        assert!(matches!(hosted, Err(NetError::VersionMismatch { .. })));
+        assert!(matches!(answer, Message::Refused { .. }));
+    }
+
+    #[tokio::test]
+    async fn endless_lines_are_cut_off() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let client = async {
+            let mut stream = TcpStream::connect(address).await.unwrap();
+            let _ = stream.write_all(&vec![b'a'; 2 * MAX_MESSAGE_BYTES as usize]).await;
+            stream
+        };
+        let (hosted, _stream) = tokio::join!(host(&mut listener, 42), client);
+
+        match hosted {
+            Err(NetError::Protocol(reason)) => assert!(reason.contains("longer than"), "{}", reason),
+            Err(error) => panic!("unexpected error {}", error),
+            Ok(_) => panic!("the host accepted an endless line"),
+        }
+    }
+}
@@ -0,0 +1,11 @@
+mod connection;
+mod protocol;
+mod snapshot;
+mod stream;
+
+pub mod network {
+    pub use super::connection::{host, join, Connection, NetError, Session, MAX_MESSAGE_BYTES, PING_INTERVAL, TIMEOUT};
+    pub use super::protocol::{Message, DEFAULT_PORT, PROTOCOL_VERSION};
+    pub use super::snapshot::{PieceState, Snapshot};
+    pub use super::stream::{Spectator, StateStream, StreamAddress};
+}
@@ -0,0 +1,51 @@
+use crate::net::snapshot::Snapshot;
+use serde::{Deserialize, Serialize};
+
+/// Has to change whenever a message does, peers with different versions refuse to play.
+pub const PROTOCOL_VERSION: u32 = 1;
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// Everything the peers send each other, one JSON object per line. After the handshake both sides
+/// play their own game and send their board and attacks, the other side only displays the board.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    /// Sent by the joining side right after connecting.
+    Hello { version: u32 },
+    /// The answer of the host, both sides deal their pieces from the seed.
+    Welcome { version: u32, seed: u64 },
+    Refused { reason: String },
+    State { snapshot: Snapshot },
+    Attack { lines: u32 },
+    GameOver,
+    Ping { id: u32 },
+    Pong { id: u32 },
+}
+
+impl Message {
+    pub fn to_line(&self) -> String {
+        let mut line = serde_json::to_string(self).expect("messages can always be serialized");
+        line.push('\n');
+        line
+    }
+
+    pub fn from_line(line: &str) -> Result<Self, String> {
+        serde_json::from_str(line.trim_end()).map_err(|error| format!("an invalid message: {}", error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::net::network::Message;
+
+    #[test]
+    fn one_message_per_line() {
+        let attack = Message::Attack { lines: 4 };
+        assert_eq!(attack.to_line(), "{\"type\":\"attack\",\"lines\":4}\n");
+        assert_eq!(Message::from_line(&attack.to_line()), Ok(attack));
+        assert_eq!(Message::from_line("{\"type\":\"game_over\"}"), Ok(Message::GameOver));
+
+        let error = Message::from_line("{\"type\":\"teleport\"}").unwrap_err();
+        assert!(error.contains("teleport"), "{}", error);
+    }
+}
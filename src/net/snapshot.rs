@@ -0,0 +1,143 @@
+use crate::engine::mechanics::{BoardLayout, Cell, Field, Stone, StoneKind, MAX_WIDTH};
+use crate::rendering::renderer::Position;
+use serde::{Deserialize, Serialize};
+
+/// The most visible rows a snapshot may have, more than any mode uses.
+const MAX_ROWS: usize = 64;
+/// How far above the visible rows the cells of the falling piece may be.
+const ROWS_ABOVE: i32 = 4;
+/// The box of a stone is at most this many cells wide and high.
+const STONE_SIZE: i32 = 4;
+
+/// The falling piece, positioned relative to the top of the visible rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PieceState {
+    pub kind: StoneKind,
+    pub x: i32,
+    pub y: i32,
+    pub rotation: u8,
+}
+
+impl PieceState {
+    pub fn of(stone: &Stone, field: &Field) -> Self {
+        let position = stone.position.offset_by(&field.visible_offset());
+        Self { kind: stone.kind, x: position.x, y: position.y, rotation: stone.rotation }
+    }
+
+    pub fn stone(&self) -> Stone {
+        Stone { kind: self.kind, rotation: self.rotation % 4, position: Position { x: self.x, y: self.y } }
+    }
+
+    /// The stone if it's on the field or just above it without overlapping any blocks, a peer may
+    /// send anything.
+    pub fn placed(&self, field: &Field) -> Option<Stone> {
+        let width = field.dimensions().width as i32;
+        let height = field.total_height() as i32;
+        if !(-STONE_SIZE..width).contains(&self.x) || !(-ROWS_ABOVE - STONE_SIZE..height).contains(&self.y) {
+            return None;
+        }
+        let stone = self.stone();
+        let on_field = stone.cells().all(|cell| (0..width).contains(&cell.x) && (-ROWS_ABOVE..height).contains(&cell.y));
+        Some(stone).filter(|stone| on_field && !field.board().collides(stone.shape(), &stone.position))
+    }
+}
+
+/// What another machine needs to show a game: the visible rows written with one character per
+/// cell (see `Cell::to_char`), the pieces and the score.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub rows: Vec<String>,
+    pub piece: Option<PieceState>,
+    pub hold: Option<StoneKind>,
+    pub next: Vec<StoneKind>,
+    pub points: u64,
+    pub level: u8,
+    pub lines: u64,
+    pub pending_garbage: u32,
+    pub game_over: bool,
+}
+
+impl Snapshot {
+    pub fn rows_of(field: &Field) -> Vec<String> {
+        (field.hidden_rows()..field.total_height()).map(|y| {
+            (0..field.dimensions().width)
+                .map(|x| field.get_cell_at_pos(&Position { x: x as i32, y: y as i32 }).unwrap_or_default().to_char())
+                .collect()
+        }).collect()
+    }
+
+    /// Rebuilds the visible part of the field, without hidden rows.
+    pub fn field(&self) -> Result<Field, String> {
+        if self.rows.len() > MAX_ROWS {
+            return Err(format!("the board has {} rows, at most {} are supported", self.rows.len(), MAX_ROWS));
+        }
+        let width = self.rows.first().map_or(0, |row| row.chars().count());
+        if width == 0 || self.rows.iter().any(|row| row.chars().count() != width) {
+            return Err("the rows of the board differ in width".to_string());
+        }
+        if width > MAX_WIDTH {
+            return Err(format!("the board is {} cells wide, at most {} are supported", width, MAX_WIDTH));
+        }
+
+        let mut field = Field::new(BoardLayout { width, visible_height: self.rows.len(), hidden_rows: 0 });
+        for (y, row) in self.rows.iter().enumerate() {
+            for (x, character) in row.chars().enumerate() {
+                let cell = Cell::from_char(character).ok_or_else(|| format!("invalid cell '{}' on the board", character))?;
+                field.set_cell_at_pos(&Position { x: x as i32, y: y as i32 }, cell);
+            }
+        }
+        Ok(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::mechanics::{Cell, Field, Mode, Stone, StoneKind, MAX_WIDTH};
+    use crate::net::network::{PieceState, Snapshot};
+    use super::MAX_ROWS;
+    use crate::rendering::renderer::Position;
+
+    #[test]
+    fn rows_round_trip() {
+        let mut field = Field::new(Mode::FourWide.layout());
+        let bottom = field.total_height() as i32 - 1;
+        field.set_cell_at_pos(&Position { x: 0, y: bottom }, Cell::Garbage);
+        field.set_cell_at_pos(&Position { x: 2, y: bottom }, Cell::Piece(StoneKind::T));
+
+        let rows = Snapshot::rows_of(&field);
+        assert_eq!(rows.len(), 20);
+        assert_eq!(rows[19], "G.T.");
+
+        let stone = Stone::new(field.spawn_position(StoneKind::I), StoneKind::I);
+        let piece = PieceState::of(&stone, &field);
+        assert_eq!(piece.y, -1);
+
+        let snapshot = Snapshot { rows, piece: Some(piece), hold: None, next: vec![], points: 0, level: 1, lines: 0, pending_garbage: 0, game_over: false };
+        let rebuilt = snapshot.field().unwrap();
+        assert_eq!(rebuilt.get_cell_at_pos(&Position { x: 2, y: 19 }), Some(Cell::Piece(StoneKind::T)));
+        assert_eq!(rebuilt.board().rows(), &field.board().rows()[20..]);
+
+        let broken = Snapshot { rows: vec!["..".to_string(), "x.".to_string()], ..snapshot.clone() };
+        assert!(broken.field().is_err());
+        let wide = Snapshot { rows: vec![".".repeat(MAX_WIDTH + 1); 2], ..snapshot.clone() };
+        assert_eq!(wide.field().err(), Some("the board is 33 cells wide, at most 32 are supported".to_string()));
+        let tall = Snapshot { rows: vec!["..".to_string(); MAX_ROWS + 1], ..snapshot.clone() };
+        assert!(tall.field().is_err());
+        let largest = Snapshot { rows: vec![".".repeat(MAX_WIDTH); MAX_ROWS], ..snapshot };
+        assert_eq!(largest.field().unwrap().total_height(), MAX_ROWS);
+    }
+
+    #[test]
+    fn pieces_off_the_field_are_dropped() {
+        let field = Snapshot { rows: vec!["G.........".to_string(); 20], piece: None, hold: None, next: vec![], points: 0, level: 1, lines: 0, pending_garbage: 0, game_over: false }
+            .field().unwrap();
+        let piece = |x, y| PieceState { kind: StoneKind::I, x, y, rotation: 0 };
+
+        assert!(piece(3, -1).placed(&field).is_some());
+        assert!(piece(3, -5).placed(&field).is_some());
+        assert!(piece(6, 18).placed(&field).is_some());
+        for &(x, y) in [(3, -6), (3, -1_500_000_000), (3, 19), (3, i32::MAX), (7, 0), (0, 0), (-1, 0), (i32::MIN, 0), (i32::MAX, i32::MIN)].iter() {
+            assert_eq!(piece(x, y).placed(&field), None, "{} {}", x, y);
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use crate::net::connection::{read_line, NetError};
+use crate::net::snapshot::Snapshot;
+use std::path::PathBuf;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{self, error::TryRecvError, UnboundedReceiver, UnboundedSender};
+use tokio::sync::watch;
+
+/// Where a game is streamed: `unix:<path>` or anything with a `/` is a Unix domain socket, a bare
+/// number a TCP port and anything else a TCP address like `192.168.0.2:7879`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamAddress {
+    /// Listens on all interfaces, spectators connect to this machine.
+    Port(u16),
+    Tcp(String),
+    Unix(PathBuf),
+}
+
+impl StreamAddress {
+    pub fn parse(address: &str) -> Self {
+        if let Some(path) = address.strip_prefix("unix:") {
+            StreamAddress::Unix(PathBuf::from(path))
+        } else if address.contains('/') {
+            StreamAddress::Unix(PathBuf::from(address))
+        } else if let Ok(port) = address.parse() {
+            StreamAddress::Port(port)
+        } else {
+            StreamAddress::Tcp(address.to_string())
+        }
+    }
+}
+
+impl std::fmt::Display for StreamAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamAddress::Port(port) => write!(f, "port {}", port),
+            StreamAddress::Tcp(address) => write!(f, "{}", address),
+            StreamAddress::Unix(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn unix_sockets_unsupported() -> NetError {
+    NetError::Io(std::io::Error::new(std::io::ErrorKind::Other, "unix sockets aren't supported on this platform"))
+}
+
+/// Publishes the running game to every spectator that connects, one JSON snapshot per line.
+/// Spectators only ever get the latest state, slow ones skip snapshots instead of lagging behind.
+pub struct StateStream {
+    sender: watch::Sender<Option<String>>,
+    last: Option<Snapshot>,
+    socket_path: Option<PathBuf>,
+}
+
+impl StateStream {
+    pub async fn bind(address: &StreamAddress) -> Result<Self, NetError> {
+        let (sender, receiver) = watch::channel(None);
+        let mut socket_path = None;
+
+        match address {
+            StreamAddress::Port(port) => serve_tcp(TcpListener::bind(("0.0.0.0", *port)).await.map_err(NetError::Io)?, receiver),
+            StreamAddress::Tcp(address) => serve_tcp(TcpListener::bind(address.as_str()).await.map_err(NetError::Io)?, receiver),
+            #[cfg(unix)]
+            StreamAddress::Unix(path) => {
+                serve_unix(bind_unix(path)?, receiver);
+                socket_path = Some(path.clone());
+            }
+            #[cfg(not(unix))]
+            StreamAddress::Unix(_) => return Err(unix_sockets_unsupported()),
+        }
+
+        Ok(Self { sender, last: None, socket_path })
+    }
+
+    /// Sends the snapshot to all spectators unless nothing changed since the last one.
+    pub fn publish(&mut self, snapshot: Snapshot) {
+        if self.last.as_ref() == Some(&snapshot) {
+            return;
+        }
+
+        let mut line = serde_json::to_string(&snapshot).expect("snapshots can always be serialized");
+        line.push('\n');
+        let _ = self.sender.broadcast(Some(line));
+        self.last = Some(snapshot);
+    }
+}
+
+impl Drop for StateStream {
+    fn drop(&mut self) {
+        if let Some(path) = &self.socket_path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+async fn send_states<S: AsyncWrite + Unpin>(mut stream: S, mut receiver: watch::Receiver<Option<String>>) {
+    while let Some(line) = receiver.recv().await {
+        if let Some(line) = line {
+            if stream.write_all(line.as_bytes()).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+fn serve_tcp(mut listener: TcpListener, receiver: watch::Receiver<Option<String>>) {
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(send_states(stream, receiver.clone()));
+        }
+    });
+}
+
+/// A socket file left behind by a game that didn't shut down is replaced.
+#[cfg(unix)]
+fn bind_unix(path: &std::path::Path) -> Result<tokio::net::UnixListener, NetError> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if std::fs::metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        let _ = std::fs::remove_file(path);
+    }
+    tokio::net::UnixListener::bind(path).map_err(NetError::Io)
+}
+
+#[cfg(unix)]
+fn serve_unix(mut listener: tokio::net::UnixListener, receiver: watch::Receiver<Option<String>>) {
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(send_states(stream, receiver.clone()));
+        }
+    });
+}
+
+/// The watching end of a state stream, it keeps the latest snapshot.
+pub struct Spectator {
+    incoming: UnboundedReceiver<Result<Snapshot, NetError>>,
+    snapshot: Option<Snapshot>,
+    error: Option<NetError>,
+}
+
+impl Spectator {
+    /// A bare port connects to a game on this machine.
+    pub async fn connect(address: &StreamAddress) -> Result<Self, NetError> {
+        let (sender, incoming) = mpsc::unbounded_channel();
+        match address {
+            StreamAddress::Port(port) => receive_states(TcpStream::connect(("127.0.0.1", *port)).await.map_err(NetError::Io)?, sender),
+            StreamAddress::Tcp(address) => receive_states(TcpStream::connect(address.as_str()).await.map_err(NetError::Io)?, sender),
+            #[cfg(unix)]
+            StreamAddress::Unix(path) => receive_states(tokio::net::UnixStream::connect(path).await.map_err(NetError::Io)?, sender),
+            #[cfg(not(unix))]
+            StreamAddress::Unix(_) => return Err(unix_sockets_unsupported()),
+        }
+
+        Ok(Self { incoming, snapshot: None, error: None })
+    }
+
+    /// Takes in everything that arrived since the last call.
+    pub fn update(&mut self) {
+        while self.error.is_none() {
+            match self.incoming.try_recv() {
+                Ok(Ok(snapshot)) => self.snapshot = Some(snapshot),
+                Ok(Err(error)) => self.error = Some(error),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Closed) => self.error = Some(NetError::Closed),
+            }
+        }
+    }
+
+    /// The latest state of the game, `None` until the player starts one.
+    pub fn snapshot(&self) -> Option<&Snapshot> {
+        self.snapshot.as_ref()
+    }
+
+    /// Why the stream ended, `None` while it's up.
+    pub fn error(&self) -> Option<&NetError> {
+        self.error.as_ref()
+    }
+}
+
+fn receive_states<S: AsyncRead + Unpin + Send + 'static>(stream: S, sender: UnboundedSender<Result<Snapshot, NetError>>) {
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(stream);
+        loop {
+            let state = match read_line(&mut reader).await {
+                Ok(Some(line)) => serde_json::from_str(&line).map_err(|error| NetError::Protocol(format!("an invalid state: {}", error))),
+                Ok(None) => Err(NetError::Closed),
+                Err(error) => Err(error),
+            };
+            let failed = state.is_err();
+            if sender.send(state).is_err() || failed {
+                return;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::mechanics::StoneKind;
+    use crate::net::network::{NetError, Snapshot, Spectator, StateStream, StreamAddress, MAX_MESSAGE_BYTES};
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+    use tokio::time::delay_for;
+
+    #[test]
+    fn addresses() {
+        assert_eq!(StreamAddress::parse("7879"), StreamAddress::Port(7879));
+        assert_eq!(StreamAddress::parse("192.168.0.2:7879"), StreamAddress::Tcp("192.168.0.2:7879".to_string()));
+        assert_eq!(StreamAddress::parse("/tmp/tet-rs.sock"), StreamAddress::Unix(PathBuf::from("/tmp/tet-rs.sock")));
+        assert_eq!(StreamAddress::parse("unix:game.sock"), StreamAddress::Unix(PathBuf::from("game.sock")));
+    }
+
+    async fn wait_for(spectator: &mut Spectator, condition: impl Fn(&Spectator) -> bool) {
+        for _ in 0..200 {
+            spectator.update();
+            if condition(spectator) {
+                return;
+            }
+            delay_for(Duration::from_millis(5)).await;
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn spectators_get_the_latest_state() {
+        let path = std::env::temp_dir().join(format!("tet-rs-stream-{}.sock", std::process::id()));
+        let address = StreamAddress::Unix(path.clone());
+        let mut stream = StateStream::bind(&address).await.unwrap();
+
+        let mut snapshot = Snapshot {
+            rows: vec!["....".to_string(), "GG.G".to_string()],
+            piece: None,
+            hold: Some(StoneKind::I),
+            next: vec![StoneKind::T, StoneKind::O],
+            points: 12,
+            level: 1,
+            lines: 3,
+            pending_garbage: 0,
+            game_over: false,
+        };
+        stream.publish(snapshot.clone());
+        let mut spectator = Spectator::connect(&address).await.unwrap();
+        wait_for(&mut spectator, |spectator| spectator.snapshot().is_some()).await;
+        assert_eq!(spectator.snapshot(), Some(&snapshot));
+
+        snapshot.game_over = true;
+        stream.publish(snapshot.clone());
+        wait_for(&mut spectator, |spectator| spectator.snapshot().is_some_and(|snapshot| snapshot.game_over)).await;
+        assert_eq!(spectator.snapshot(), Some(&snapshot));
+
+        drop(stream);
+        assert!(!path.exists());
+        wait_for(&mut spectator, |spectator| spectator.error().is_some()).await;
+        assert!(matches!(spectator.error(), Some(NetError::Closed)));
+    }
+
+    #[tokio::test]
+    async fn endless_lines_end_the_stream() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = StreamAddress::Tcp(listener.local_addr().unwrap().to_string());
+        let publisher = async {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let _ = stream.write_all(&vec![b'a'; 2 * MAX_MESSAGE_BYTES as usize]).await;
+            stream
+        };
+        let (spectator, _stream) = tokio::join!(Spectator::connect(&address), publisher);
+        let mut spectator = spectator.unwrap();
+
+        wait_for(&mut spectator, |spectator| spectator.error().is_some()).await;
+        assert!(matches!(spectator.error(), Some(NetError::Protocol(_))));
+        assert_eq!(spectator.snapshot(), None);
+    }
+}
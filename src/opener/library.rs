@@ -0,0 +1,295 @@
+use crate::config::settings::Config;
+use crate::engine::mechanics::{decode_fumen, Field, Mode, Stone, StoneKind};
+use crate::rendering::renderer::Position;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+const BUILTIN: &str = include_str!("openers.toml");
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OpenerDefinition {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    board: Vec<String>,
+    /// The shape as a fumen instead of a board, the field of the last page after its piece locks.
+    fumen: Option<String>,
+    #[serde(default)]
+    order: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OpenerFile {
+    #[serde(default)]
+    opener: Vec<OpenerDefinition>,
+}
+
+/// A target shape built from the first bag on the marathon board.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Opener {
+    pub name: String,
+    pub description: String,
+    targets: Vec<Stone>,
+    /// Pairs of pieces where the first has to be placed before the second.
+    order: Vec<(StoneKind, StoneKind)>,
+}
+
+impl Opener {
+    fn parse(definition: OpenerDefinition) -> Result<Self, String> {
+        let name = definition.name;
+        let layout = Mode::Marathon.layout();
+        let board = match definition.fumen {
+            Some(_) if !definition.board.is_empty() => return Err(format!("{} has both a board and a fumen", name)),
+            Some(code) => fumen_board(&code).map_err(|error| format!("invalid fumen of {}: {}", name, error))?,
+            None => definition.board,
+        };
+        if board.is_empty() || board.len() > layout.visible_height {
+            return Err(format!("the board of {} needs 1 to {} rows", name, layout.visible_height));
+        }
+
+        let bottom = Field::new(layout).total_height() - board.len();
+        let mut cells: Vec<(StoneKind, Vec<Position>)> = Vec::new();
+        for (y, row) in board.iter().enumerate() {
+            if row.chars().count() != layout.width {
+                return Err(format!("the rows of {} have to be {} cells wide", name, layout.width));
+            }
+            for (x, cell) in row.chars().enumerate().filter(|&(_, cell)| cell != '.') {
+                let kind = StoneKind::from_letter(cell).ok_or(format!("{} has an unknown piece '{}' on its board", name, cell))?;
+                let position = Position { x: x as i32, y: (bottom + y) as i32 };
+                match cells.iter_mut().find(|(other, _)| *other == kind) {
+                    Some((_, positions)) => positions.push(position),
+                    None => cells.push((kind, vec![position])),
+                }
+            }
+        }
+
+        let targets = cells.into_iter()
+            .map(|(kind, positions)| (0..4).find_map(|rotation| Stone::covering(kind, rotation, &positions)).ok_or(format!("the {} of {} isn't one whole piece", kind.letter(), name)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if targets.is_empty() {
+            return Err(format!("the board of {} is empty", name));
+        }
+
+        let mut order = Vec::new();
+        for sequence in &definition.order {
+            let kinds = sequence.chars()
+                .map(|letter| StoneKind::from_letter(letter).filter(|kind| targets.iter().any(|target| target.kind == *kind)))
+                .collect::<Option<Vec<_>>>()
+                .ok_or(format!("the order '{}' of {} names a piece that isn't on the board", sequence, name))?;
+            order.extend(kinds.windows(2).map(|pair| (pair[0], pair[1])));
+        }
+
+        let opener = Self { name, description: definition.description, targets, order };
+        if opener.placement_order(|available| available[0]).is_none() {
+            return Err(format!("the order requirements of {} contradict each other", opener.name));
+        }
+        Ok(opener)
+    }
+
+    pub fn targets(&self) -> &[Stone] {
+        &self.targets
+    }
+
+    pub fn target(&self, kind: StoneKind) -> Option<&Stone> {
+        self.targets.iter().find(|target| target.kind == kind)
+    }
+
+    /// A bag in an order the opener can be built in, the pieces that aren't part of it come last.
+    pub fn bag(&self, rng: &mut impl Rng) -> Vec<StoneKind> {
+        let mut bag = self.placement_order(|available| *available.choose(rng).expect("there's always a piece to choose from"))
+            .expect("the order requirements were checked when parsing");
+        let mut rest: Vec<StoneKind> = StoneKind::ALL.iter().copied().filter(|kind| !bag.contains(kind)).collect();
+        rest.shuffle(rng);
+        bag.append(&mut rest);
+        bag
+    }
+
+    /// Places the pieces one at a time, `choose` picks among those whose requirements are met.
+    fn placement_order(&self, mut choose: impl FnMut(&[StoneKind]) -> StoneKind) -> Option<Vec<StoneKind>> {
+        let mut left: Vec<StoneKind> = self.targets.iter().map(|target| target.kind).collect();
+        let mut placed = Vec::with_capacity(left.len());
+        while !left.is_empty() {
+            let available: Vec<StoneKind> = left.iter().copied()
+                .filter(|&kind| self.order.iter().all(|&(first, then)| then != kind || placed.contains(&first)))
+                .collect();
+            if available.is_empty() {
+                return None;
+            }
+            let kind = choose(&available);
+            left.retain(|&other| other != kind);
+            placed.push(kind);
+        }
+        Some(placed)
+    }
+}
+
+/// The rows of the last page's field from its highest block down, with letters like in a board.
+fn fumen_board(code: &str) -> Result<Vec<String>, String> {
+    let field = decode_fumen(code)?.last().expect("fumens have at least one page").locked_field();
+    let rows: Vec<String> = (0..field.total_height() as i32)
+        .map(|y| (0..field.dimensions().width as i32).map(|x| field.get_cell_at_pos(&Position { x, y }).unwrap_or_default().to_char()).collect())
+        .skip_while(|row: &String| row.chars().all(|cell| cell == '.'))
+        .collect();
+    Ok(rows)
+}
+
+/// The built-in openers together with the ones from the user's openers file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenerLibrary {
+    openers: Vec<Opener>,
+}
+
+impl Default for OpenerLibrary {
+    fn default() -> Self {
+        let mut library = Self { openers: Vec::new() };
+        library.add_toml(BUILTIN).expect("the built-in openers are valid");
+        library
+    }
+}
+
+impl OpenerLibrary {
+    pub fn path() -> Option<PathBuf> {
+        Config::directory().map(|directory| directory.join("openers.toml"))
+    }
+
+    /// Adds the openers of a file, an opener with the name of one that's already there replaces it.
+    pub fn add_toml(&mut self, content: &str) -> Result<(), String> {
+        let file: OpenerFile = toml::from_str(content).map_err(|error| error.to_string())?;
+        for definition in file.opener {
+            let opener = Opener::parse(definition)?;
+            match self.openers.iter_mut().find(|other| other.name.eq_ignore_ascii_case(&opener.name)) {
+                Some(other) => *other = opener,
+                None => self.openers.push(opener),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let mut library = Self::default();
+        match std::fs::read_to_string(path) {
+            Ok(content) => library.add_toml(&content).map_err(|error| format!("invalid openers file {}: {}", path.display(), error))?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => return Err(format!("could not read openers file {}: {}", path.display(), error)),
+        }
+        Ok(library)
+    }
+
+    pub fn load() -> Result<Self, String> {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn openers(&self) -> &[Opener] {
+        &self.openers
+    }
+
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.openers.iter().position(|opener| opener.name.eq_ignore_ascii_case(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bot::ai::reachable_placements;
+    use crate::engine::mechanics::{encode_fumen, Field, FumenPage, Mode, Stone, StoneKind};
+    use crate::opener::openers::OpenerLibrary;
+    use crate::rendering::renderer::Position;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn cells(stone: &Stone) -> Vec<Position> {
+        let mut cells: Vec<_> = stone.cells().collect();
+        cells.sort_by_key(|position| (position.y, position.x));
+        cells
+    }
+
+    #[test]
+    fn built_in_openers_can_be_built() {
+        let library = OpenerLibrary::default();
+        assert_eq!(library.openers().len(), 4);
+        let mut rng = StdRng::seed_from_u64(3);
+
+        for opener in library.openers() {
+            for _ in 0..10 {
+                let bag = opener.bag(&mut rng);
+                assert_eq!(bag.len(), 7);
+                let mut field = Field::new(Mode::Marathon.layout());
+                for kind in bag.iter().take(opener.targets().len()) {
+                    let target = opener.target(*kind).unwrap();
+                    let start = Stone::new(field.spawn_position(*kind), *kind);
+                    let reachable = reachable_placements(&field, &start).iter().any(|placement| cells(&placement.stone) == cells(target));
+                    assert!(reachable, "{} can't place {} after {:?}", opener.name, kind.letter(), bag);
+                    field.add_stone(target);
+                }
+                assert_eq!(field.try_delete_lines(), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn user_openers_extend_and_replace() {
+        let mut library = OpenerLibrary::default();
+        library.add_toml(r#"
+            [[opener]]
+            name = "tki"
+            board = ["IIII......"]
+
+            [[opener]]
+            name = "stack"
+            board = ["....OO....", "....OOIIII"]
+            order = ["IO"]
+        "#).unwrap();
+        assert_eq!(library.openers().len(), 5);
+        assert_eq!(library.openers()[0].targets().len(), 1);
+        assert_eq!(library.position("Stack"), Some(4));
+
+        let stack = &library.openers()[4];
+        let bag = stack.bag(&mut StdRng::seed_from_u64(0));
+        assert_eq!(&bag[..2], &[StoneKind::I, StoneKind::O]);
+        assert_eq!(stack.target(StoneKind::O).unwrap().rotation, 0);
+
+        let built_in = OpenerLibrary::default();
+        let tki = &built_in.openers()[0];
+        let mut field = Field::new(Mode::Marathon.layout());
+        tki.targets().iter().for_each(|target| field.add_stone(target));
+        let code = encode_fumen(&[FumenPage::new(field, None)]).unwrap();
+        library.add_toml(&format!("[[opener]]\nname = \"tki fumen\"\nfumen = \"{}\"", code)).unwrap();
+        let from_fumen = &library.openers()[5];
+        assert!(tki.targets().iter().all(|target| from_fumen.target(target.kind).map(cells) == Some(cells(target))));
+
+        let invalid = [
+            r#"[[opener]]
+               name = "split"
+               board = ["II..II...."]"#,
+            r#"[[opener]]
+               name = "narrow"
+               board = ["OO", "OO"]"#,
+            r#"[[opener]]
+               name = "cycle"
+               board = ["OO..IIII..", "OO........"]
+               order = ["OI", "IO"]"#,
+            r#"[[opener]]
+               name = "missing"
+               board = ["OO........", "OO........"]
+               order = ["OT"]"#,
+            r#"[[opener]]
+               name = "both"
+               board = ["IIII......"]
+               fumen = "v115@vhAAgH""#,
+            r#"[[opener]]
+               name = "broken fumen"
+               fumen = "v115@vh""#,
+        ];
+        for content in invalid.iter() {
+            assert!(library.add_toml(content).is_err(), "{}", content);
+        }
+    }
+}
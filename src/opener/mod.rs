@@ -0,0 +1,5 @@
+mod library;
+
+pub mod openers {
+    pub use super::library::{Opener, OpenerLibrary};
+}
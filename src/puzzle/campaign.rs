@@ -0,0 +1,345 @@
+use crate::config::settings::Config;
+use crate::engine::mechanics::{decode_fumen, Clear, Setup, TSpin};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+const BUILTIN: &str = include_str!("puzzles.toml");
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LevelDefinition {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    board: Vec<String>,
+    /// The board as a fumen instead, its quiz comment gives the pieces unless they're set here.
+    fumen: Option<String>,
+    #[serde(default)]
+    hold: String,
+    #[serde(default)]
+    queue: String,
+    goal: String,
+    lines: Option<u32>,
+    pieces: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LevelFile {
+    #[serde(default)]
+    level: Vec<LevelDefinition>,
+}
+
+/// What has to happen for a level to count as solved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    Lines(u32),
+    /// A T-spin that clears this many lines at once, one or two since there are no wall kicks.
+    TSpin(usize),
+    PerfectClear,
+}
+
+impl Goal {
+    fn parse(name: &str, lines: Option<u32>) -> Result<Self, String> {
+        match name {
+            "lines" => Ok(Goal::Lines(lines.unwrap_or(1).max(1))),
+            _ if lines.is_some() => Err(format!("only the goal lines takes a number of lines, not {}", name)),
+            "t-spin-single" => Ok(Goal::TSpin(1)),
+            "t-spin-double" => Ok(Goal::TSpin(2)),
+            "t-spin-triple" => Err("t-spin-triple can't be reached, pieces rotate without wall kicks so a T never gets into the slot".to_string()),
+            "perfect-clear" => Ok(Goal::PerfectClear),
+            _ => Err(format!("unknown goal '{}', use lines, t-spin-single, t-spin-double or perfect-clear", name)),
+        }
+    }
+
+    /// Whether the goal is reached with the lines cleared so far and the clear of the last piece.
+    pub fn is_reached(self, lines: u64, clear: &Clear) -> bool {
+        match self {
+            Goal::Lines(goal) => lines >= goal as u64,
+            Goal::TSpin(goal) => clear.t_spin == TSpin::Full && clear.lines == goal,
+            Goal::PerfectClear => clear.perfect_clear,
+        }
+    }
+
+    pub fn describe(self) -> String {
+        match self {
+            Goal::Lines(1) => "clear a line".to_string(),
+            Goal::Lines(lines) => format!("clear {} lines", lines),
+            Goal::TSpin(1) => "score a T-spin single".to_string(),
+            Goal::TSpin(_) => "score a T-spin double".to_string(),
+            Goal::PerfectClear => "clear the whole board".to_string(),
+        }
+    }
+}
+
+/// A board with a fixed sequence of pieces and a goal to reach with them.
+#[derive(Clone)]
+pub struct Level {
+    pub name: String,
+    pub description: String,
+    pub setup: Setup,
+    pub goal: Goal,
+    /// The goal has to be reached within this many placed pieces, otherwise the whole queue can
+    /// be used.
+    pub max_pieces: Option<u32>,
+}
+
+impl Level {
+    fn parse(definition: LevelDefinition) -> Result<Self, String> {
+        let name = definition.name;
+        let setup = match definition.fumen {
+            Some(_) if !definition.board.is_empty() => return Err(format!("{} has both a board and a fumen", name)),
+            Some(code) => {
+                let page = decode_fumen(&code).map_err(|error| format!("invalid fumen of {}: {}", name, error))?.remove(0);
+                let mut setup = Setup::from_fumen_page(&page);
+                let pieces = Setup::parse(&[], &definition.hold, &definition.queue).map_err(|error| format!("{}: {}", name, error))?;
+                if !pieces.queue.is_empty() {
+                    setup.hold = pieces.hold;
+                    setup.queue = pieces.queue;
+                }
+                setup
+            }
+            None => Setup::parse(&definition.board, &definition.hold, &definition.queue).map_err(|error| format!("{}: {}", name, error))?,
+        };
+        if setup.queue.is_empty() {
+            return Err(format!("{} has no pieces to play", name));
+        }
+        let goal = Goal::parse(&definition.goal, definition.lines).map_err(|error| format!("{}: {}", name, error))?;
+        Ok(Self { name, description: definition.description, setup, goal, max_pieces: definition.pieces })
+    }
+
+    /// Every page of the fumen as a perfect clear level, the pieces come from the quiz comments.
+    fn from_fumen(name: &str, code: &str) -> Result<Vec<Self>, String> {
+        let pages = decode_fumen(code)?;
+        let count = pages.len();
+        pages.iter().enumerate().map(|(index, page)| {
+            let name = if count == 1 { name.to_string() } else { format!("{} {}", name, index + 1) };
+            let setup = Setup::from_fumen_page(page);
+            if setup.queue.is_empty() {
+                return Err(format!("page {} needs a quiz comment with the pieces, e.g. #Q=[](T)IOZ", index + 1));
+            }
+            Ok(Self { name, description: String::new(), setup, goal: Goal::PerfectClear, max_pieces: None })
+        }).collect()
+    }
+}
+
+/// The built-in levels followed by the ones in the user's puzzles directory.
+#[derive(Clone)]
+pub struct Campaign {
+    levels: Vec<Level>,
+}
+
+impl Default for Campaign {
+    fn default() -> Self {
+        let mut campaign = Self { levels: Vec::new() };
+        campaign.add_toml(BUILTIN).expect("the built-in levels are valid");
+        campaign
+    }
+}
+
+impl Campaign {
+    pub fn directory() -> Option<PathBuf> {
+        Config::directory().map(|directory| directory.join("puzzles"))
+    }
+
+    fn add(&mut self, level: Level) {
+        match self.levels.iter_mut().find(|other| other.name.eq_ignore_ascii_case(&level.name)) {
+            Some(other) => *other = level,
+            None => self.levels.push(level),
+        }
+    }
+
+    /// Adds the levels of a file, a level with the name of one that's already there replaces it.
+    pub fn add_toml(&mut self, content: &str) -> Result<(), String> {
+        let file: LevelFile = toml::from_str(content).map_err(|error| error.to_string())?;
+        let levels = file.level.into_iter().map(Level::parse).collect::<Result<Vec<_>, _>>()?;
+        levels.into_iter().for_each(|level| self.add(level));
+        Ok(())
+    }
+
+    /// Adds a `.toml` file of levels or a `.fumen` or `.txt` file whose pages are perfect clear
+    /// levels named after the file.
+    pub fn add_file(&mut self, path: &Path) -> Result<(), String> {
+        let content = std::fs::read_to_string(path).map_err(|error| format!("could not read {}: {}", path.display(), error))?;
+        let added = if Setup::is_fumen_path(path) {
+            let name = path.file_stem().map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+            Level::from_fumen(&name, &content).map(|levels| levels.into_iter().for_each(|level| self.add(level)))
+        } else {
+            self.add_toml(&content)
+        };
+        added.map_err(|error| format!("invalid level file {}: {}", path.display(), error))
+    }
+
+    /// Adds the level files of the directory in the order of their names. A missing directory
+    /// is fine, broken files are skipped and reported.
+    pub fn load_from(directory: &Path) -> (Self, Vec<String>) {
+        let mut campaign = Self::default();
+        let mut errors = Vec::new();
+        let mut paths: Vec<PathBuf> = match std::fs::read_dir(directory) {
+            Ok(entries) => entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect(),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(error) => return (campaign, vec![format!("could not read {}: {}", directory.display(), error)]),
+        };
+        paths.retain(|path| path.extension().is_some_and(|extension| extension == "toml") || Setup::is_fumen_path(path));
+        paths.sort();
+        for path in paths {
+            if let Err(error) = campaign.add_file(&path) {
+                errors.push(error);
+            }
+        }
+        (campaign, errors)
+    }
+
+    pub fn load() -> (Self, Vec<String>) {
+        match Self::directory() {
+            Some(directory) => Self::load_from(&directory),
+            None => (Self::default(), Vec::new()),
+        }
+    }
+
+    /// Only the levels of one file, see `add_file`.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let mut campaign = Self { levels: Vec::new() };
+        campaign.add_file(path)?;
+        if campaign.levels.is_empty() {
+            return Err(format!("{} has no levels", path.display()));
+        }
+        Ok(campaign)
+    }
+
+    /// The pages of a fumen as perfect clear levels.
+    pub fn from_fumen(code: &str) -> Result<Self, String> {
+        Level::from_fumen("fumen", code).map(|levels| Self { levels })
+    }
+
+    pub fn levels(&self) -> &[Level] {
+        &self.levels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bot::ai::{apply_move, find_perfect_clear, reachable_placements, MAX_PC_LINES};
+    use crate::config::settings::Action;
+    use crate::engine::mechanics::{encode_fumen, quiz_comment, t_spin, Board, Cell, Clear, Field, FumenPage, Setup, Stone, StoneKind};
+    use crate::rendering::renderer::Position;
+    use crate::puzzle::puzzles::{Campaign, Goal, Level};
+    use std::collections::HashSet;
+
+    /// Whether the goal can be reached by placing the pieces in order, holding like the player can.
+    fn solvable(level: &Level) -> bool {
+        if level.goal == Goal::PerfectClear && level.max_pieces.is_none() {
+            return find_perfect_clear(&level.setup.field, &level.setup.queue, level.setup.hold, MAX_PC_LINES).is_some();
+        }
+        let mut solver = Solver { goal: level.goal, failed: HashSet::new() };
+        let limit = level.max_pieces.map_or(usize::MAX, |max| max as usize);
+        solver.solve(&level.setup.field, level.setup.hold, &level.setup.queue, 0, limit)
+    }
+
+    struct Solver {
+        goal: Goal,
+        failed: HashSet<(Board, Option<StoneKind>, usize, u64)>,
+    }
+
+    impl Solver {
+        /// `left` is how many pieces may still be placed.
+        fn solve(&mut self, field: &Field, hold: Option<StoneKind>, queue: &[StoneKind], lines: u64, left: usize) -> bool {
+            if left == 0 || !self.failed.insert((field.board().clone(), hold, queue.len(), lines)) {
+                return false;
+            }
+            // the piece to place, what's held afterwards and the pieces after it
+            let choices = match (queue.split_first(), hold) {
+                (Some((&current, rest)), Some(held)) => vec![(current, hold, rest), (held, Some(current), rest)],
+                (Some((&current, rest)), None) => match rest.split_first() {
+                    Some((&next, after)) => vec![(current, None, rest), (next, Some(current), after)],
+                    None => vec![(current, None, rest)],
+                },
+                (None, Some(held)) => vec![(held, None, queue)],
+                (None, None) => Vec::new(),
+            };
+
+            for (kind, hold, rest) in choices {
+                let start = Stone::new(field.spawn_position(kind), kind);
+                for placement in reachable_placements(field, &start) {
+                    let mut moved = start;
+                    placement.actions.iter().for_each(|&action| { apply_move(&mut moved, field, action); });
+                    let rotated_last = moved.position == placement.stone.position
+                        && placement.actions.last().is_some_and(|action| [Action::RotateCW, Action::RotateCCW, Action::Rotate180].contains(action));
+                    let t_spin = t_spin(field, &placement.stone, rotated_last);
+                    let mut next = field.clone();
+                    next.add_stone(&placement.stone);
+                    let cleared = next.try_delete_lines();
+                    let clear = Clear { lines: cleared, t_spin, perfect_clear: cleared > 0 && next.board().is_empty() };
+                    let lines = lines + cleared as u64;
+                    if self.goal.is_reached(lines, &clear) || self.solve(&next, hold, rest, lines, left - 1) {
+                        return true;
+                    }
+                }
+            }
+            false
+        }
+    }
+
+    #[test]
+    fn built_in_levels_can_be_solved() {
+        let campaign = Campaign::default();
+        assert_eq!(campaign.levels().len(), 20);
+        for level in campaign.levels() {
+            assert!(solvable(level), "{} can't be solved", level.name);
+        }
+    }
+
+    #[test]
+    fn user_levels_extend_and_replace() {
+        let directory = std::env::temp_dir().join(format!("tet-rs-levels-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("a.toml"), r#"
+            [[level]]
+            name = "First Line"
+            board = ["GGGGGGGGG."]
+            queue = "I"
+            goal = "lines"
+
+            [[level]]
+            name = "too many"
+            board = ["GGGGGGGG..", "GGGGGGGG.."]
+            queue = "IIO"
+            goal = "lines"
+            lines = 2
+            pieces = 1
+        "#).unwrap();
+        std::fs::write(directory.join("b.toml"), "[[level]]\nname = \"no goal\"\nqueue = \"I\"").unwrap();
+        let mut field = Setup::default().field;
+        (0..6).for_each(|x| { field.set_cell_at_pos(&Position { x, y: 39 }, Cell::Garbage); });
+        let mut page = FumenPage::new(field, None);
+        page.comment = quiz_comment(Some(StoneKind::I), &[StoneKind::T, StoneKind::I]);
+        std::fs::write(directory.join("c.fumen"), encode_fumen(&[page.clone(), page]).unwrap()).unwrap();
+        std::fs::write(directory.join("notes.md"), "not a level").unwrap();
+
+        let (campaign, errors) = Campaign::load_from(&directory);
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(errors[0].contains("b.toml"));
+        let names: Vec<&str> = campaign.levels()[20..].iter().map(|level| level.name.as_str()).collect();
+        assert_eq!(names, vec!["too many", "c 1", "c 2"]);
+        let first = &campaign.levels()[0];
+        assert_eq!((first.name.as_str(), first.setup.rows(), first.goal), ("First Line", vec!["GGGGGGGGG.".to_string()], Goal::Lines(1)));
+
+        assert!(!solvable(&campaign.levels()[20]));
+        assert!(solvable(&campaign.levels()[21]));
+        assert_eq!(campaign.levels()[21].setup.hold, Some(StoneKind::I));
+
+        let invalid = [
+            "[[level]]\nname = \"empty\"\nboard = [\"G.........\"]\ngoal = \"lines\"",
+            "[[level]]\nname = \"unknown\"\nqueue = \"T\"\ngoal = \"tsd\"",
+            "[[level]]\nname = \"counted\"\nqueue = \"T\"\ngoal = \"perfect-clear\"\nlines = 2",
+            "[[level]]\nname = \"both\"\nboard = [\"G.........\"]\nfumen = \"v115@vhAAgH\"\nqueue = \"T\"\ngoal = \"lines\"",
+        ];
+        for content in invalid.iter() {
+            assert!(Campaign::default().add_toml(content).is_err(), "{}", content);
+        }
+        let error = Campaign::default().add_toml("[[level]]\nname = \"tst\"\nqueue = \"T\"\ngoal = \"t-spin-triple\"").unwrap_err();
+        assert!(error.contains("wall kicks"), "{}", error);
+    }
+}
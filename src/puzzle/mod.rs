@@ -0,0 +1,7 @@
+mod campaign;
+mod progress;
+
+pub mod puzzles {
+    pub use super::campaign::{Campaign, Goal, Level};
+    pub use super::progress::{LevelProgress, Progress};
+}
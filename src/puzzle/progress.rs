@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelProgress {
+    pub attempts: u32,
+    pub completed: bool,
+    /// The fewest pieces the level was solved with.
+    pub best_pieces: Option<u32>,
+}
+
+/// How far the player got in every level, by the name of the level.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Progress {
+    #[serde(default)]
+    levels: BTreeMap<String, LevelProgress>,
+}
+
+impl Progress {
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|directory| directory.join("tet-rs").join("puzzles.toml"))
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content).map_err(|error| format!("invalid puzzle progress {}: {}", path.display(), error)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(format!("could not read puzzle progress {}: {}", path.display(), error)),
+        }
+    }
+
+    pub fn load() -> Result<Self, String> {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        let content = toml::to_string(self).map_err(|error| format!("could not serialize the puzzle progress: {}", error))?;
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory).map_err(|error| format!("could not create {}: {}", directory.display(), error))?;
+        }
+        std::fs::write(path, content).map_err(|error| format!("could not write puzzle progress {}: {}", path.display(), error))
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("could not determine the data directory")?;
+        self.save_to(&path)
+    }
+
+    pub fn level(&self, name: &str) -> LevelProgress {
+        self.levels.get(name).cloned().unwrap_or_default()
+    }
+
+    pub fn record_attempt(&mut self, name: &str) {
+        self.levels.entry(name.to_string()).or_default().attempts += 1;
+    }
+
+    pub fn record_completion(&mut self, name: &str, pieces: u32) {
+        let level = self.levels.entry(name.to_string()).or_default();
+        level.completed = true;
+        level.best_pieces = Some(level.best_pieces.map_or(pieces, |best| best.min(pieces)));
+    }
+
+    pub fn completed<'a>(&self, names: impl Iterator<Item=&'a str>) -> usize {
+        names.filter(|name| self.level(name).completed).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::puzzle::puzzles::Progress;
+
+    #[test]
+    fn progress_round_trip() {
+        let mut progress = Progress::default();
+        progress.record_attempt("first steps");
+        progress.record_attempt("first steps");
+        progress.record_completion("first steps", 4);
+        progress.record_completion("first steps", 6);
+        progress.record_attempt("T-spin \"triple\"");
+
+        let path = std::env::temp_dir().join(format!("tet-rs-puzzles-{}.toml", std::process::id()));
+        progress.save_to(&path).unwrap();
+        let loaded = Progress::load_from(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, progress);
+
+        let first = loaded.level("first steps");
+        assert_eq!((first.attempts, first.completed, first.best_pieces), (2, true, Some(4)));
+        assert!(!loaded.level("T-spin \"triple\"").completed);
+        assert_eq!(loaded.completed(["first steps", "T-spin \"triple\"", "unknown"].iter().copied()), 1);
+    }
+}
@@ -1,13 +1,16 @@
 use crate::rendering::texture::{Dimensions, Texture};
 use crate::rendering::tile::Tile;
 use crate::rendering::position::Position;
-use crate::rendering::color::Color;
+use crate::rendering::color::{Color, RenderMode};
+use crate::rendering::theme::Theme;
 
 #[derive(Debug, Clone)]
 pub struct Canvas {
     pub dimensions: Dimensions,
     rows: Vec<Vec<Tile>>,
     buffer: String,
+    theme: Theme,
+    render_mode: RenderMode,
 }
 
 impl Default for Canvas {
@@ -18,31 +21,60 @@ impl Default for Canvas {
             dimensions: Dimensions { width: width as usize, height: height as usize },
             rows: vec![vec![Tile::default(); width as usize]; height as usize],
             buffer: String::with_capacity(20000),
+            theme: Theme::default(),
+            render_mode: RenderMode::default(),
         }
     }
 }
 
 impl Canvas {
+    /// A canvas of a fixed size that isn't tied to the terminal, e.g. to print a board.
+    pub fn new(dimensions: Dimensions) -> Self {
+        Self {
+            dimensions,
+            rows: vec![vec![Tile::default(); dimensions.width]; dimensions.height],
+            buffer: String::new(),
+            theme: Theme::default(),
+            render_mode: RenderMode::default(),
+        }
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        if self.theme != *theme {
+            self.theme = theme.clone();
+        }
+    }
+
+    pub fn set_render_mode(&mut self, render_mode: RenderMode) {
+        self.render_mode = render_mode;
+    }
+
     pub fn clear(&mut self) {
+        let background = Tile::new_background(self.theme.background);
         self.rows.iter_mut().for_each(|row| {
             row.iter_mut().for_each(|tile| {
-                *tile = Tile::default();
+                *tile = background;
             });
         });
     }
 
 
     pub fn get_printable_string(&mut self) -> &String {
-        let mut buffer = std::mem::replace(&mut self.buffer, String::new());
+        let mut buffer = std::mem::take(&mut self.buffer);
         buffer.clear();
         buffer.push_str(crossterm::cursor::MoveTo(0, 0).to_string().as_str());
 
         let mut previous_tile = &Tile::default();
-        previous_tile.apply_colors_of_tile_to_buffer(&mut buffer);
+        let mode = self.render_mode;
+        previous_tile.apply_colors_of_tile_to_buffer(&mut buffer, mode);
 
         self.rows.iter().for_each(|row| {
             row.iter().for_each(|tile| {
-                tile.fill_buffer_with_printable_string_with_respect_to_previous_tile(&mut buffer, &previous_tile);
+                tile.fill_buffer_with_printable_string_with_respect_to_previous_tile(&mut buffer, previous_tile, mode);
                 previous_tile = tile;
             });
         });
@@ -50,6 +82,21 @@ impl Canvas {
         &self.buffer
     }
 
+    /// The rows as separate lines that reset their colors at the end.
+    pub fn printable_lines(&self) -> Vec<String> {
+        self.rows.iter().map(|row| {
+            let mut line = String::new();
+            let mut previous_tile = &row[0];
+            previous_tile.apply_colors_of_tile_to_buffer(&mut line, self.render_mode);
+            row.iter().for_each(|tile| {
+                tile.fill_buffer_with_printable_string_with_respect_to_previous_tile(&mut line, previous_tile, self.render_mode);
+                previous_tile = tile;
+            });
+            line.push_str("\u{1b}[0m");
+            line
+        }).collect()
+    }
+
     pub fn add_themed_paragraph(&mut self, text: &[&str], mut position: Position) {
         text.iter().for_each(|text| {
             self.add_themed_text(text, &position);
@@ -58,7 +105,11 @@ impl Canvas {
     }
 
     pub fn add_themed_text(&mut self, text: &str, position: &Position) {
-        self.add_text(text, Color::Black, Color::Orange, position);
+        self.add_text(text, self.theme.text_background, self.theme.text, position);
+    }
+
+    pub fn add_highlighted_text(&mut self, text: &str, position: &Position) {
+        self.add_text(text, self.theme.highlight, self.theme.text_background, position);
     }
 
     pub fn add_text(&mut self, text: &str, background: Color, foreground: Color, position: &Position) {
@@ -68,17 +119,49 @@ impl Canvas {
 
         let texture = Texture {
             pixels: vec![texture_to_draw],
-            dimensions: Dimensions { width: text.len(), height: 1 },
+            dimensions: Dimensions { width: text.chars().count(), height: 1 },
         };
 
         self.add_texture(texture, position);
     }
 
+    pub fn add_well(&mut self, position: &Position, inner: &Dimensions) {
+        let border = self.theme.border_tile();
+        let mut texture = Texture::new(Dimensions { width: inner.width + 2, height: inner.height + 1 });
+
+        for (row_index, row) in texture.pixels.iter_mut().enumerate() {
+            let is_floor = row_index == inner.height;
+            for (column_index, pixel) in row.iter_mut().enumerate() {
+                if is_floor || column_index == 0 || column_index == inner.width + 1 {
+                    *pixel = Some(border);
+                }
+            }
+        }
+
+        self.add_texture(texture, position);
+    }
+
+    pub fn add_frame(&mut self, position: &Position, inner: &Dimensions) {
+        let border = self.theme.border_tile();
+        let mut texture = Texture::new(Dimensions { width: inner.width + 2, height: inner.height + 2 });
+
+        for (row_index, row) in texture.pixels.iter_mut().enumerate() {
+            let is_edge_row = row_index == 0 || row_index == inner.height + 1;
+            for (column_index, pixel) in row.iter_mut().enumerate() {
+                if is_edge_row || column_index == 0 || column_index == inner.width + 1 {
+                    *pixel = Some(border);
+                }
+            }
+        }
+
+        self.add_texture(texture, position);
+    }
+
     pub fn add_texture(&mut self, texture: Texture, position: &Position) {
         use std::cmp::{max, min};
 
-        let skip_canvas_rows = min(max(position.y, 0), self.dimensions.height as i8) as usize;
-        let start_column_canvas = min(max(position.x, 0), self.dimensions.width as i8) as usize;
+        let skip_canvas_rows = min(max(position.y, 0), self.dimensions.height as i32) as usize;
+        let start_column_canvas = min(max(position.x, 0), self.dimensions.width as i32) as usize;
 
 
         let skip_texture_rows = if position.y < 0 { -position.y as usize } else { 0 };
@@ -1,6 +1,29 @@
 use crossterm::style::Color as Term_Color;
+use std::convert::TryFrom;
+use std::fmt::Write;
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, Debug, PartialOrd, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RenderMode {
+    #[default]
+    TrueColor,
+    Ansi256,
+}
+
+impl RenderMode {
+    pub const ALL: [RenderMode; 2] = [RenderMode::TrueColor, RenderMode::Ansi256];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RenderMode::TrueColor => "true color",
+            RenderMode::Ansi256 => "256 colors",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
 pub enum Color {
     Red,
     Yellow,
@@ -12,39 +35,103 @@ pub enum Color {
     White,
     Black,
     Gray,
+    Rgb { r: u8, g: u8, b: u8 },
 }
 
 impl Color {
-    pub fn to_rgb(&self) -> Term_Color {
-        use Term_Color::*;
+    pub fn rgb_values(self) -> (u8, u8, u8) {
+        match self {
+            Color::Red => (255, 0, 0),
+            Color::Yellow => (255, 247, 5),
+            Color::Green => (0, 255, 0),
+            Color::LightBlue => (0, 170, 255),
+            Color::DarkBlue => (15, 32, 189),
+            Color::Orange => (245, 167, 66),
+            Color::Purple => (125, 15, 189),
+            Color::White => (255, 255, 255),
+            Color::Black => (0, 0, 0),
+            Color::Gray => (100, 100, 100),
+            Color::Rgb { r, g, b } => (r, g, b),
+        }
+    }
 
+    pub fn to_rgb(self) -> Term_Color {
+        let (r, g, b) = self.rgb_values();
+        Term_Color::Rgb { r, g, b }
+    }
 
-        match self {
-            Color::Red => Rgb{r: 255, g: 0, b: 0},
-            Color::Yellow => Rgb{r: 255, g: 247, b: 5},
-            Color::Green => Rgb{r: 0, g: 255, b: 0},
-            Color::LightBlue => Rgb{r: 0, g: 170, b: 255},
-            Color::DarkBlue => Rgb{r: 15, g: 32, b: 189},
-            Color::Orange => Rgb{r: 245, g: 167, b: 66},
-            Color::Purple => Rgb{r: 125, g: 15, b: 189},
-            Color::White => Rgb{r: 255, g: 255, b: 255},
-            Color::Black => Rgb{r: 0, g: 0, b: 0},
-            Color::Gray => Rgb{r: 100, g: 100, b: 100}
+    pub fn to_ansi256(self) -> u8 {
+        let (r, g, b) = self.rgb_values();
+        let level = |channel: u8| (channel as u16 * 5 + 127) / 255;
+        (16 + 36 * level(r) + 6 * level(g) + level(b)) as u8
+    }
+
+    pub fn push_ansi(self, buffer: &mut String, mode: RenderMode) {
+        match mode {
+            RenderMode::TrueColor => {
+                let (r, g, b) = self.rgb_values();
+                let _ = write!(buffer, "2;{};{};{}", r, g, b);
+            }
+            RenderMode::Ansi256 => {
+                let _ = write!(buffer, "5;{}", self.to_ansi256());
+            }
         }
     }
+}
 
-    pub fn to_ansi(&self) -> &str {
-        match self {
-            Color::Red => "255;0;0",
-            Color::Yellow => "255;247;5",
-            Color::Green => "0;255;0",
-            Color::LightBlue => "0;170;255",
-            Color::DarkBlue => "15;32;189",
-            Color::Orange => "245;167;66",
-            Color::Purple => "125;15;189",
-            Color::White => "255;255;255",
-            Color::Black => "0;0;0",
-            Color::Gray => "100;100;100"
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let named = match value.to_lowercase().as_str() {
+            "red" => Some(Color::Red),
+            "yellow" => Some(Color::Yellow),
+            "green" => Some(Color::Green),
+            "lightblue" | "light_blue" => Some(Color::LightBlue),
+            "darkblue" | "dark_blue" => Some(Color::DarkBlue),
+            "orange" => Some(Color::Orange),
+            "purple" => Some(Color::Purple),
+            "white" => Some(Color::White),
+            "black" => Some(Color::Black),
+            "gray" | "grey" => Some(Color::Gray),
+            _ => None
+        };
+
+        if let Some(color) = named {
+            return Ok(color);
+        }
+
+        let hex = value.trim_start_matches('#');
+        let channel = |index: usize| hex.get(index..index + 2).and_then(|channel| u8::from_str_radix(channel, 16).ok());
+
+        match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(r), Some(g), Some(b)) => Ok(Color::Rgb { r, g, b }),
+            _ => Err(format!("'{}' is neither a color name nor a hex color like \"#ff8800\"", value))
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rendering::renderer::{Color, RenderMode};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn parse_colors() {
+        assert_eq!(Color::try_from("#0aFf10".to_string()), Ok(Color::Rgb { r: 10, g: 255, b: 16 }));
+        assert_eq!(Color::try_from("Grey".to_string()), Ok(Color::Gray));
+        assert!(Color::try_from("#12345".to_string()).is_err());
+        assert!(Color::try_from("#12345g".to_string()).is_err());
+    }
+
+    #[test]
+    fn ansi_sequence() {
+        let mut buffer = String::new();
+        Color::Rgb { r: 1, g: 22, b: 255 }.push_ansi(&mut buffer, RenderMode::TrueColor);
+        assert_eq!(buffer, "2;1;22;255");
+
+        buffer.clear();
+        Color::Rgb { r: 255, g: 0, b: 100 }.push_ansi(&mut buffer, RenderMode::Ansi256);
+        assert_eq!(buffer, "5;198");
+    }
+}
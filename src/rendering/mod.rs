@@ -2,12 +2,14 @@ mod canvas;
 mod color;
 mod position;
 mod texture;
+mod theme;
 mod tile;
 
 pub mod renderer {
     pub use super::canvas::Canvas;
-    pub use super::color::Color;
+    pub use super::color::{Color, RenderMode};
     pub use super::position::Position;
     pub use super::texture::{Texture, Dimensions};
+    pub use super::theme::Theme;
     pub use super::tile::Tile;
 }
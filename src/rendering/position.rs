@@ -1,19 +1,17 @@
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub struct Position {
-    pub x: i8,
-    pub y: i8,
+    pub x: i32,
+    pub y: i32,
 }
 
-impl Default for Position {
-    fn default() -> Self {
-        Self {
-            x: 0,
-            y: 0
+impl Position {
+    pub fn offset_by(&self, offset: &Position) -> Position {
+        Position {
+            x: self.x + offset.x,
+            y: self.y + offset.y,
         }
     }
-}
 
-impl Position {
     pub fn move_down(&mut self) {
         self.y += 1;
     }
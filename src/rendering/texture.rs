@@ -6,9 +6,7 @@ pub struct Dimensions { pub width: usize, pub height: usize }
 
 impl Dimensions {
     pub fn transpose(&mut self){
-        let temp = self.width;
-        self.width = self.height;
-        self.height = temp;
+        std::mem::swap(&mut self.width, &mut self.height);
     }
 
     pub fn transpose_into(&self) -> Self{
@@ -40,6 +38,15 @@ impl Texture {
         }
     }
 
+    pub fn filled_with(&self, tile: Tile) -> Self {
+        Self {
+            pixels: self.pixels.iter().map(|row| {
+                row.iter().map(|pixel| pixel.map(|_| tile)).collect()
+            }).collect(),
+            dimensions: self.dimensions,
+        }
+    }
+
     pub fn rotate(&mut self) {
         let old_data = std::mem::replace(&mut self.pixels, vec![vec![None; self.dimensions.height]; self.dimensions.width]);
 
@@ -0,0 +1,318 @@
+use crate::rendering::color::Color;
+use crate::rendering::tile::Tile;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PieceColors {
+    pub i: Color,
+    pub j: Color,
+    pub l: Color,
+    pub o: Color,
+    pub s: Color,
+    pub t: Color,
+    pub z: Color,
+}
+
+impl Default for PieceColors {
+    fn default() -> Self {
+        Theme::classic().pieces
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Glyphs {
+    pub block: char,
+    pub empty: char,
+    pub ghost: char,
+    pub border: char,
+}
+
+impl Default for Glyphs {
+    fn default() -> Self {
+        Theme::classic().glyphs
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    #[serde(skip)]
+    pub name: String,
+    pub pieces: PieceColors,
+    pub garbage: Color,
+    pub empty: Color,
+    pub grid: Color,
+    pub ghost: Color,
+    pub text: Color,
+    pub text_background: Color,
+    pub highlight: Color,
+    pub border: Color,
+    pub background: Color,
+    pub glyphs: Glyphs,
+}
+
+/// A theme as it's written in a file, where the name may be left out.
+#[derive(Deserialize)]
+struct ThemeFile {
+    name: Option<String>,
+    #[serde(flatten)]
+    theme: Theme,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+}
+
+impl std::fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThemeError::Io(path, error) => write!(f, "could not read theme {}: {}", path.display(), error),
+            ThemeError::Parse(path, error) => write!(f, "invalid theme {}: {}", path.display(), error),
+        }
+    }
+}
+
+impl Theme {
+    pub fn classic() -> Self {
+        Self {
+            name: "classic".to_string(),
+            pieces: PieceColors {
+                i: Color::LightBlue,
+                j: Color::DarkBlue,
+                l: Color::Orange,
+                o: Color::Yellow,
+                s: Color::Green,
+                t: Color::Purple,
+                z: Color::Red,
+            },
+            garbage: Color::Rgb { r: 160, g: 160, b: 160 },
+            empty: Color::Gray,
+            grid: Color::Rgb { r: 120, g: 120, b: 120 },
+            ghost: Color::Rgb { r: 140, g: 140, b: 140 },
+            text: Color::Orange,
+            text_background: Color::Black,
+            highlight: Color::White,
+            border: Color::Rgb { r: 60, g: 60, b: 60 },
+            background: Color::Black,
+            glyphs: Glyphs {
+                block: ' ',
+                empty: ' ',
+                ghost: '░',
+                border: ' ',
+            },
+        }
+    }
+
+    pub fn monochrome() -> Self {
+        let white = Color::White;
+        let light = Color::Rgb { r: 200, g: 200, b: 200 };
+
+        Self {
+            name: "monochrome".to_string(),
+            pieces: PieceColors { i: white, j: light, l: white, o: light, s: white, t: light, z: white },
+            garbage: Color::Rgb { r: 120, g: 120, b: 120 },
+            empty: Color::Black,
+            grid: Color::Rgb { r: 70, g: 70, b: 70 },
+            ghost: Color::Rgb { r: 150, g: 150, b: 150 },
+            text: white,
+            text_background: Color::Black,
+            highlight: Color::Black,
+            border: white,
+            background: Color::Black,
+            glyphs: Glyphs {
+                block: '▒',
+                empty: '·',
+                ghost: '░',
+                border: '│',
+            },
+        }
+    }
+
+    pub fn nord() -> Self {
+        Self {
+            name: "nord".to_string(),
+            pieces: PieceColors {
+                i: Color::Rgb { r: 136, g: 192, b: 208 },
+                j: Color::Rgb { r: 94, g: 129, b: 172 },
+                l: Color::Rgb { r: 208, g: 135, b: 112 },
+                o: Color::Rgb { r: 235, g: 203, b: 139 },
+                s: Color::Rgb { r: 163, g: 190, b: 140 },
+                t: Color::Rgb { r: 180, g: 142, b: 173 },
+                z: Color::Rgb { r: 191, g: 97, b: 106 },
+            },
+            garbage: Color::Rgb { r: 76, g: 86, b: 106 },
+            empty: Color::Rgb { r: 46, g: 52, b: 64 },
+            grid: Color::Rgb { r: 67, g: 76, b: 94 },
+            ghost: Color::Rgb { r: 216, g: 222, b: 233 },
+            text: Color::Rgb { r: 236, g: 239, b: 244 },
+            text_background: Color::Rgb { r: 46, g: 52, b: 64 },
+            highlight: Color::Rgb { r: 143, g: 188, b: 187 },
+            border: Color::Rgb { r: 59, g: 66, b: 82 },
+            background: Color::Rgb { r: 46, g: 52, b: 64 },
+            glyphs: Glyphs {
+                block: ' ',
+                empty: '·',
+                ghost: '░',
+                border: ' ',
+            },
+        }
+    }
+
+    pub fn solarized() -> Self {
+        Self {
+            name: "solarized".to_string(),
+            pieces: PieceColors {
+                i: Color::Rgb { r: 42, g: 161, b: 152 },
+                j: Color::Rgb { r: 38, g: 139, b: 210 },
+                l: Color::Rgb { r: 203, g: 75, b: 22 },
+                o: Color::Rgb { r: 181, g: 137, b: 0 },
+                s: Color::Rgb { r: 133, g: 153, b: 0 },
+                t: Color::Rgb { r: 108, g: 113, b: 196 },
+                z: Color::Rgb { r: 220, g: 50, b: 47 },
+            },
+            garbage: Color::Rgb { r: 88, g: 110, b: 117 },
+            empty: Color::Rgb { r: 0, g: 43, b: 54 },
+            grid: Color::Rgb { r: 7, g: 54, b: 66 },
+            ghost: Color::Rgb { r: 147, g: 161, b: 161 },
+            text: Color::Rgb { r: 238, g: 232, b: 213 },
+            text_background: Color::Rgb { r: 0, g: 43, b: 54 },
+            highlight: Color::Rgb { r: 211, g: 54, b: 130 },
+            border: Color::Rgb { r: 7, g: 54, b: 66 },
+            background: Color::Rgb { r: 0, g: 43, b: 54 },
+            glyphs: Glyphs {
+                block: ' ',
+                empty: ' ',
+                ghost: '░',
+                border: ' ',
+            },
+        }
+    }
+
+    pub fn built_in() -> Vec<Self> {
+        vec![Self::classic(), Self::monochrome(), Self::nord(), Self::solarized()]
+    }
+
+    /// Themes without a name get `default_name`.
+    pub fn from_toml(content: &str, default_name: &str) -> Result<Self, toml::de::Error> {
+        let file: ThemeFile = toml::from_str(content)?;
+        Ok(Self { name: file.name.unwrap_or_else(|| default_name.to_string()), ..file.theme })
+    }
+
+    /// Themes without a name are named after their file.
+    pub fn load(path: &Path) -> Result<Self, ThemeError> {
+        let content = std::fs::read_to_string(path).map_err(|error| ThemeError::Io(path.to_path_buf(), error))?;
+        let default_name = path.file_stem().map_or_else(|| Self::classic().name, |stem| stem.to_string_lossy().into_owned());
+        Self::from_toml(&content, &default_name).map_err(|error| ThemeError::Parse(path.to_path_buf(), error))
+    }
+
+    pub fn user_theme_directory() -> Option<PathBuf> {
+        dirs::config_dir().map(|directory| directory.join("tet-rs").join("themes"))
+    }
+
+    pub fn load_user_themes() -> Vec<Result<Self, ThemeError>> {
+        let directory = match Self::user_theme_directory() {
+            Some(directory) => directory,
+            None => return vec![],
+        };
+
+        let mut paths: Vec<_> = match std::fs::read_dir(&directory) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|extension| extension == "toml"))
+                .collect(),
+            Err(_) => return vec![],
+        };
+        paths.sort();
+
+        paths.iter().map(|path| Self::load(path)).collect()
+    }
+
+    pub fn all() -> (Vec<Self>, Vec<ThemeError>) {
+        let mut themes = Self::built_in();
+        let mut errors = vec![];
+
+        for result in Self::load_user_themes() {
+            match result {
+                Ok(theme) => {
+                    themes.retain(|existing| existing.name != theme.name);
+                    themes.push(theme);
+                }
+                Err(error) => errors.push(error),
+            }
+        }
+
+        (themes, errors)
+    }
+
+    pub fn text_tile(&self, text: char) -> Tile {
+        Tile::new_character(text, self.text_background, self.text)
+    }
+
+    pub fn block_tile(&self, color: Color) -> Tile {
+        Tile::new_character(self.glyphs.block, color, self.background)
+    }
+
+    pub fn empty_tile(&self) -> Tile {
+        Tile::new_character(self.glyphs.empty, self.empty, self.grid)
+    }
+
+    pub fn ghost_tile(&self) -> Tile {
+        Tile::new_character(self.glyphs.ghost, self.empty, self.ghost)
+    }
+
+    pub fn target_tile(&self, color: Color) -> Tile {
+        Tile::new_character(self.glyphs.ghost, self.empty, color)
+    }
+
+    pub fn border_tile(&self) -> Tile {
+        Tile::new_character(self.glyphs.border, self.border, self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rendering::renderer::{Theme, Color};
+
+    #[test]
+    fn partial_theme_falls_back_to_classic() {
+        let theme = Theme::from_toml(r##"
+            name = "custom"
+            empty = "#101010"
+
+            [pieces]
+            t = "#ff00ff"
+
+            [glyphs]
+            ghost = "x"
+        "##, "fallback").unwrap();
+
+        assert_eq!(theme.name, "custom");
+        assert_eq!(theme.empty, Color::Rgb { r: 16, g: 16, b: 16 });
+        assert_eq!(theme.pieces.t, Color::Rgb { r: 255, g: 0, b: 255 });
+        assert_eq!(theme.pieces.i, Theme::classic().pieces.i);
+        assert_eq!(theme.glyphs.ghost, 'x');
+        assert_eq!(theme.glyphs.block, Theme::classic().glyphs.block);
+    }
+
+    #[test]
+    fn invalid_color_is_rejected() {
+        assert!(Theme::from_toml("empty = \"#zzzzzz\"", "fallback").is_err());
+    }
+
+    #[test]
+    fn only_unnamed_themes_get_the_default_name() {
+        assert_eq!(Theme::from_toml("name = \"classic\"", "mine").unwrap().name, "classic");
+        assert_eq!(Theme::from_toml("empty = \"#101010\"", "mine").unwrap().name, "mine");
+    }
+}
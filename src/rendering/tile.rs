@@ -1,6 +1,6 @@
-use crate::rendering::color::Color;
+use crate::rendering::color::{Color, RenderMode};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct Tile {
     pub foreground: Color,
     pub background: Color,
@@ -26,38 +26,38 @@ impl Tile {
         Tile { background, foreground, text }
     }
 
-    fn push_background(&self, buffer: &mut String) {
-        buffer.push_str("\u{1b}[48;2;");
-        buffer.push_str(self.background.to_ansi());
+    fn push_background(&self, buffer: &mut String, mode: RenderMode) {
+        buffer.push_str("\u{1b}[48;");
+        self.background.push_ansi(buffer, mode);
         buffer.push('m');
     }
 
-    fn push_foreground(&self, buffer: &mut String) {
-        buffer.push_str("\u{1b}[38;2;");
-        buffer.push_str(self.foreground.to_ansi());
+    fn push_foreground(&self, buffer: &mut String, mode: RenderMode) {
+        buffer.push_str("\u{1b}[38;");
+        self.foreground.push_ansi(buffer, mode);
         buffer.push('m');
     }
 
-    pub fn fill_buffer_with_printable_string(&self, buffer: &mut String) {
-        self.apply_colors_of_tile_to_buffer(buffer);
+    pub fn fill_buffer_with_printable_string(&self, buffer: &mut String, mode: RenderMode) {
+        self.apply_colors_of_tile_to_buffer(buffer, mode);
         buffer.push(self.text);
     }
 
-    pub fn apply_colors_of_tile_to_buffer(&self, buffer: &mut String) {
-        buffer.push_str("\u{1b}[48;2;");
-        buffer.push_str(self.background.to_ansi());
-        buffer.push_str(";38;2;");
-        buffer.push_str(self.foreground.to_ansi());
+    pub fn apply_colors_of_tile_to_buffer(&self, buffer: &mut String, mode: RenderMode) {
+        buffer.push_str("\u{1b}[48;");
+        self.background.push_ansi(buffer, mode);
+        buffer.push_str(";38;");
+        self.foreground.push_ansi(buffer, mode);
         buffer.push('m');
     }
 
-    pub fn fill_buffer_with_printable_string_with_respect_to_previous_tile(&self, buffer: &mut String, previous_tile: &Tile) {
+    pub fn fill_buffer_with_printable_string_with_respect_to_previous_tile(&self, buffer: &mut String, previous_tile: &Tile, mode: RenderMode) {
         if self.foreground != previous_tile.foreground {
-            self.push_foreground(buffer);
+            self.push_foreground(buffer, mode);
         }
 
         if self.background != previous_tile.background {
-            self.push_background(buffer);
+            self.push_background(buffer, mode);
         }
 
         buffer.push(self.text);
@@ -0,0 +1,169 @@
+use crate::bot::ai::{Bot, BotChoice, Situation, TbpBot};
+use crate::config::settings::MAX_NEXT_QUEUE_LENGTH;
+use crate::engine::mechanics::{AttackTable, Clear, Field, Mode, NextQueue, PieceGenerator, Score, Stone};
+use serde::Serialize;
+
+/// Why a simulated game ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndCause {
+    /// The next piece couldn't spawn.
+    BlockOut,
+    /// A piece locked completely above the visible field.
+    LockOut,
+    /// The bot found no placement or gave up.
+    NoMove,
+    /// The bot chose a placement that overlaps the stack, floats or needs a piece hold can't give.
+    InvalidMove,
+    /// The external bot failed or stopped answering.
+    BotError,
+    /// The game reached the piece limit without topping out.
+    PieceLimit,
+}
+
+impl EndCause {
+    pub fn name(self) -> &'static str {
+        match self {
+            EndCause::BlockOut => "block_out",
+            EndCause::LockOut => "lock_out",
+            EndCause::NoMove => "no_move",
+            EndCause::InvalidMove => "invalid_move",
+            EndCause::BotError => "bot_error",
+            EndCause::PieceLimit => "piece_limit",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GameResult {
+    pub game: usize,
+    pub seed: u64,
+    pub score: u64,
+    pub lines: u64,
+    pub level: u8,
+    pub pieces: u64,
+    pub cause: EndCause,
+    /// What went wrong with an external bot.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The bot of a simulation thread, an external bot is started once and plays all games of its
+/// thread.
+pub enum SimulatedBot {
+    Builtin(Bot),
+    External(TbpBot),
+}
+
+impl SimulatedBot {
+    pub fn start(choice: &BotChoice) -> Result<Self, String> {
+        match choice {
+            BotChoice::Builtin(weights) => Ok(SimulatedBot::Builtin(Bot::new(weights.clone()))),
+            BotChoice::External(command) => {
+                let mut bot = TbpBot::spawn(command)?;
+                bot.wait_until_ready()?;
+                Ok(SimulatedBot::External(bot))
+            }
+        }
+    }
+
+    /// The target of the current piece, a target of another kind means holding first.
+    fn choose(&mut self, situation: &Situation) -> Result<Option<Stone>, String> {
+        match self {
+            SimulatedBot::Builtin(bot) => Ok(bot.plan(situation.field, situation.current, situation.hold, situation.can_hold).map(|plan| plan.target)),
+            SimulatedBot::External(bot) => {
+                bot.request(situation)?;
+                bot.wait_for_suggestion(situation)
+            }
+        }
+    }
+}
+
+/// Places every piece right where the bot wants it, without gravity or inputs. The placement only
+/// has to rest on the stack, unlike in real games it isn't checked whether it can be reached.
+/// Scores the same way as a real game.
+pub fn play_game(game: usize, mode: Mode, seed: u64, bot: &mut SimulatedBot, max_pieces: u64) -> GameResult {
+    let mut field = Field::new(mode.layout());
+    let mut next_queue = NextQueue::new(PieceGenerator::new(seed), MAX_NEXT_QUEUE_LENGTH);
+    let mut held = None;
+    let mut score = Score::default();
+    let mut attack_table = AttackTable::default();
+    let mut pieces = 0;
+
+    let result = |score: &Score, pieces, cause, error| GameResult {
+        game,
+        seed,
+        score: score.points,
+        lines: score.lines,
+        level: score.level,
+        pieces,
+        cause,
+        error,
+    };
+
+    loop {
+        if pieces >= max_pieces {
+            return result(&score, pieces, EndCause::PieceLimit, None);
+        }
+        let kind = next_queue.pop();
+        let current = Stone::new(field.spawn_position(kind), kind);
+        if !field.can_spawn(&current) {
+            return result(&score, pieces, EndCause::BlockOut, None);
+        }
+
+        let situation = Situation {
+            hold: held.or_else(|| Some(next_queue.peek())),
+            can_hold: true,
+            held,
+            queue: next_queue.pieces(),
+            combo: attack_table.combo().map_or(0, |combo| combo as u32 + 1),
+            back_to_back: attack_table.back_to_back(),
+            ..Situation::new(&field, &current, pieces)
+        };
+        let target = match bot.choose(&situation) {
+            Ok(Some(target)) => target,
+            Ok(None) => return result(&score, pieces, EndCause::NoMove, None),
+            Err(error) => return result(&score, pieces, EndCause::BotError, Some(error)),
+        };
+
+        if target.kind != kind {
+            match held {
+                Some(held_kind) if held_kind == target.kind => {}
+                None if next_queue.peek() == target.kind => {
+                    next_queue.pop();
+                }
+                _ => return result(&score, pieces, EndCause::InvalidMove, None),
+            }
+            held = Some(kind);
+        }
+        if !field.can_spawn(&target) || target.can_move_down(&field) {
+            return result(&score, pieces, EndCause::InvalidMove, None);
+        }
+        if field.is_locked_out(&target) {
+            return result(&score, pieces, EndCause::LockOut, None);
+        }
+
+        field.add_stone(&target);
+        let lines = field.try_delete_lines();
+        attack_table.attack(&Clear { lines, perfect_clear: lines > 0 && field.board().is_empty(), ..Clear::default() });
+        score.record(lines);
+        pieces += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bot::ai::Bot;
+    use crate::engine::mechanics::Mode;
+    use crate::sim::simulation::{play_game, EndCause, SimulatedBot};
+
+    #[test]
+    fn games_are_reproducible() {
+        let mut bot = SimulatedBot::Builtin(Bot::default());
+        let first = play_game(0, Mode::Marathon, 7, &mut bot, 60);
+        assert_eq!(first.cause, EndCause::PieceLimit);
+        assert_eq!(first.pieces, 60);
+        assert!(first.lines >= 20);
+        assert_eq!(play_game(0, Mode::Marathon, 7, &mut bot, 60), first);
+    }
+}
@@ -0,0 +1,11 @@
+mod game;
+mod report;
+mod runner;
+mod tuner;
+
+pub mod simulation {
+    pub use super::game::{play_game, EndCause, GameResult, SimulatedBot};
+    pub use super::report::{write_results, Distribution, Format, Summary};
+    pub use super::runner::{Simulation, DEFAULT_MAX_PIECES};
+    pub use super::tuner::{Generation, Tuning};
+}
@@ -0,0 +1,158 @@
+use crate::sim::game::{EndCause, GameResult};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::time::Duration;
+
+/// How the results of the single games are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One line per game with a header, for spreadsheets and scripts.
+    Csv,
+    /// The games and the summary in one object.
+    Json,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "csv" => Some(Format::Csv),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Mean, spread and extremes of one value over all games.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct Distribution {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: u64,
+    pub median: f64,
+    pub max: u64,
+}
+
+impl Distribution {
+    pub fn of(values: impl Iterator<Item=u64>) -> Self {
+        let mut values: Vec<u64> = values.collect();
+        if values.is_empty() {
+            return Self::default();
+        }
+        values.sort_unstable();
+
+        let count = values.len() as f64;
+        let mean = values.iter().sum::<u64>() as f64 / count;
+        let variance = values.iter().map(|&value| (value as f64 - mean).powi(2)).sum::<f64>() / count;
+        let middle = values.len() / 2;
+        let median = if values.len().is_multiple_of(2) { (values[middle - 1] + values[middle]) as f64 / 2.0 } else { values[middle] as f64 };
+
+        Self { mean, std_dev: variance.sqrt(), min: values[0], median, max: values[values.len() - 1] }
+    }
+}
+
+impl std::fmt::Display for Distribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "mean {:.1} ± {:.1}, median {}, min {}, max {}", self.mean, self.std_dev, self.median, self.min, self.max)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Summary {
+    pub games: usize,
+    pub seconds: f64,
+    pub score: Distribution,
+    pub lines: Distribution,
+    pub pieces: Distribution,
+    /// How many games ended for each reason.
+    pub causes: BTreeMap<EndCause, usize>,
+}
+
+impl Summary {
+    pub fn new(results: &[GameResult], elapsed: Duration) -> Self {
+        let mut causes = BTreeMap::new();
+        for result in results {
+            *causes.entry(result.cause).or_insert(0) += 1;
+        }
+
+        Self {
+            games: results.len(),
+            seconds: elapsed.as_secs_f64(),
+            score: Distribution::of(results.iter().map(|result| result.score)),
+            lines: Distribution::of(results.iter().map(|result| result.lines)),
+            pieces: Distribution::of(results.iter().map(|result| result.pieces)),
+            causes,
+        }
+    }
+}
+
+impl std::fmt::Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rate = if self.seconds > 0.0 { self.games as f64 / self.seconds } else { 0.0 };
+        writeln!(f, "{} games in {:.1}s ({:.1} games/s)", self.games, self.seconds, rate)?;
+        writeln!(f, "score:  {}", self.score)?;
+        writeln!(f, "lines:  {}", self.lines)?;
+        writeln!(f, "pieces: {}", self.pieces)?;
+        let causes: Vec<String> = self.causes.iter().map(|(cause, count)| format!("{} {}", cause.name(), count)).collect();
+        write!(f, "ended by: {}", causes.join(", "))
+    }
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    summary: &'a Summary,
+    games: &'a [GameResult],
+}
+
+/// CSV only has the games, the summary is meant to be computed by whatever reads it.
+pub fn write_results(writer: &mut impl Write, results: &[GameResult], summary: &Summary, format: Format) -> std::io::Result<()> {
+    match format {
+        Format::Csv => {
+            writeln!(writer, "game,seed,score,lines,level,pieces,cause")?;
+            for result in results {
+                writeln!(writer, "{},{},{},{},{},{},{}", result.game, result.seed, result.score, result.lines, result.level, result.pieces, result.cause.name())?;
+            }
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *writer, &JsonReport { summary, games: results })?;
+            writeln!(writer)?;
+        }
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sim::simulation::{write_results, Distribution, EndCause, Format, GameResult, Summary};
+    use std::time::Duration;
+
+    fn result(game: usize, score: u64, cause: EndCause) -> GameResult {
+        GameResult { game, seed: 100 + game as u64, score, lines: score / 2, level: 1, pieces: 10, cause, error: None }
+    }
+
+    #[test]
+    fn distributions() {
+        let distribution = Distribution::of(vec![4, 1, 3, 2].into_iter());
+        assert_eq!((distribution.mean, distribution.median, distribution.min, distribution.max), (2.5, 2.5, 1, 4));
+        assert!((distribution.std_dev - 1.25f64.sqrt()).abs() < 1e-9);
+        assert_eq!(Distribution::of(std::iter::empty()), Distribution::default());
+    }
+
+    #[test]
+    fn csv_and_json() {
+        let results = vec![result(0, 10, EndCause::BlockOut), result(1, 20, EndCause::PieceLimit)];
+        let summary = Summary::new(&results, Duration::from_secs(2));
+        assert_eq!(summary.causes.get(&EndCause::BlockOut), Some(&1));
+
+        let mut csv = Vec::new();
+        write_results(&mut csv, &results, &summary, Format::Csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "game,seed,score,lines,level,pieces,cause\n0,100,10,5,1,10,block_out\n1,101,20,10,1,10,piece_limit\n");
+
+        let mut json = Vec::new();
+        write_results(&mut json, &results, &summary, Format::Json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json["summary"]["causes"]["piece_limit"], 1);
+        assert_eq!(json["games"][1]["cause"], "piece_limit");
+        assert_eq!(json["summary"]["score"]["mean"], 15.0);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::bot::ai::BotChoice;
+use crate::engine::mechanics::Mode;
+use crate::sim::game::{play_game, GameResult, SimulatedBot};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Good bots would play marathon forever without gravity, the limit keeps the games comparable.
+pub const DEFAULT_MAX_PIECES: u64 = 1000;
+
+/// A batch of games played by one bot without a terminal, spread over threads.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub bot: BotChoice,
+    pub mode: Mode,
+    pub games: usize,
+    /// Game `n` deals its pieces from `seed + n`, so runs can be repeated and compared.
+    pub seed: u64,
+    pub max_pieces: u64,
+    pub threads: usize,
+}
+
+impl Simulation {
+    /// The results ordered by game. Every thread starts its own external bot and starts it again
+    /// after it failed, the simulation stops if that isn't possible.
+    pub fn run(&self) -> Result<Vec<GameResult>, String> {
+        let next_game = AtomicUsize::new(0);
+        let results = Mutex::new(Vec::with_capacity(self.games));
+
+        let outcomes: Vec<Result<(), String>> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.threads.clamp(1, self.games.max(1)))
+                .map(|_| scope.spawn(|| self.play_games(&next_game, &results)))
+                .collect();
+            workers.into_iter().map(|worker| worker.join().unwrap_or_else(|_| Err("a simulation thread panicked".to_string()))).collect()
+        });
+        outcomes.into_iter().collect::<Result<(), String>>()?;
+
+        let mut results = results.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner());
+        results.sort_by_key(|result| result.game);
+        Ok(results)
+    }
+
+    fn play_games(&self, next_game: &AtomicUsize, results: &Mutex<Vec<GameResult>>) -> Result<(), String> {
+        let mut bot = SimulatedBot::start(&self.bot)?;
+        loop {
+            let game = next_game.fetch_add(1, Ordering::Relaxed);
+            if game >= self.games {
+                return Ok(());
+            }
+
+            let result = play_game(game, self.mode, self.seed.wrapping_add(game as u64), &mut bot, self.max_pieces);
+            if result.error.is_some() {
+                bot = SimulatedBot::start(&self.bot)?;
+            }
+            results.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bot::ai::{BotChoice, Weights};
+    use crate::engine::mechanics::Mode;
+    use crate::sim::simulation::{play_game, SimulatedBot, Simulation};
+
+    #[test]
+    fn threads_play_the_same_games() {
+        let simulation = Simulation { bot: BotChoice::Builtin(Weights::default()), mode: Mode::FourWide, games: 6, seed: 3, max_pieces: 40, threads: 3 };
+        let results = simulation.run().unwrap();
+        assert_eq!(results.iter().map(|result| result.game).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4, 5]);
+
+        let mut bot = SimulatedBot::start(&BotChoice::Builtin(Weights::default())).unwrap();
+        assert_eq!(results[4], play_game(4, Mode::FourWide, 7, &mut bot, 40));
+    }
+
+    #[test]
+    fn bots_that_cant_start_stop_the_simulation() {
+        let simulation = Simulation { bot: BotChoice::External("/nonexistent/bot".to_string()), mode: Mode::Marathon, games: 2, seed: 0, max_pieces: 10, threads: 2 };
+        assert!(simulation.run().unwrap_err().contains("could not start the bot"));
+    }
+}
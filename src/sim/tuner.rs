@@ -0,0 +1,163 @@
+use crate::bot::ai::{BotChoice, Weights};
+use crate::engine::mechanics::Mode;
+use crate::sim::runner::Simulation;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// The best candidates of a generation are kept as they are.
+const ELITES: usize = 2;
+/// Parents are the best of this many randomly drawn candidates.
+const TOURNAMENT_SIZE: usize = 3;
+/// Chance that a weight of a child is changed.
+const MUTATION_RATE: f64 = 0.3;
+/// Standard deviation of a change, the weights are scaled to a length of 1.
+const MUTATION_STRENGTH: f64 = 0.15;
+
+/// Settings of a tuning run, the same settings always find the same weights.
+#[derive(Debug, Clone)]
+pub struct Tuning {
+    /// The first candidate, the others of the first generation are variations of it.
+    pub start: Weights,
+    pub population: usize,
+    pub generations: usize,
+    /// Every candidate plays the same games, dealt from `seed` to `seed + games - 1`. The seed
+    /// also drives the evolution.
+    pub games: usize,
+    pub seed: u64,
+    pub mode: Mode,
+    pub max_pieces: u64,
+    pub threads: usize,
+}
+
+/// How a generation did, reported after it played its games.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generation {
+    pub number: usize,
+    pub best: Weights,
+    /// The mean score of the games of a candidate.
+    pub best_fitness: f64,
+    pub mean_fitness: f64,
+}
+
+type Values = [f64; Weights::COUNT];
+
+#[derive(Debug, Clone)]
+struct Candidate {
+    values: Values,
+    fitness: Option<f64>,
+}
+
+impl Tuning {
+    /// Evolves the weights with a genetic algorithm and returns the best ones of the last
+    /// generation. The elites survive unchanged, the rest of every generation are children of
+    /// tournament winners that take every weight from one of their parents and are mutated.
+    /// Since the games never change, a candidate only plays them once.
+    pub fn run(&self, mut on_generation: impl FnMut(&Generation)) -> Result<Weights, String> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let start = normalized(self.start.values());
+        let size = self.population.max(ELITES + 1);
+        let mut population: Vec<Candidate> = (0..size)
+            .map(|index| if index == 0 { start } else { mutated(start, 1.0, &mut rng) })
+            .map(|values| Candidate { values, fitness: None })
+            .collect();
+
+        for number in 1..=self.generations.max(1) {
+            for candidate in &mut population {
+                if candidate.fitness.is_none() {
+                    candidate.fitness = Some(self.fitness(candidate.values)?);
+                }
+            }
+            population.sort_by(|a, b| fitness(b).total_cmp(&fitness(a)));
+
+            on_generation(&Generation {
+                number,
+                best: Weights::from_values(population[0].values),
+                best_fitness: fitness(&population[0]),
+                mean_fitness: population.iter().map(fitness).sum::<f64>() / population.len() as f64,
+            });
+
+            let mut next: Vec<Candidate> = population[..ELITES].to_vec();
+            while next.len() < size {
+                let (first, second) = (tournament(&population, &mut rng), tournament(&population, &mut rng));
+                let mut child = [0.0; Weights::COUNT];
+                for (index, value) in child.iter_mut().enumerate() {
+                    *value = if rng.gen() { first.values[index] } else { second.values[index] };
+                }
+                next.push(Candidate { values: mutated(child, MUTATION_RATE, &mut rng), fitness: None });
+            }
+            if number < self.generations {
+                population = next;
+            }
+        }
+
+        Ok(Weights::from_values(population[0].values))
+    }
+
+    fn fitness(&self, values: Values) -> Result<f64, String> {
+        let simulation = Simulation {
+            bot: BotChoice::Builtin(Weights::from_values(values)),
+            mode: self.mode,
+            games: self.games,
+            seed: self.seed,
+            max_pieces: self.max_pieces,
+            threads: self.threads,
+        };
+        let results = simulation.run()?;
+        Ok(results.iter().map(|result| result.score as f64).sum::<f64>() / results.len().max(1) as f64)
+    }
+}
+
+fn fitness(candidate: &Candidate) -> f64 {
+    candidate.fitness.unwrap_or(f64::MIN)
+}
+
+fn tournament<'a>(population: &'a [Candidate], rng: &mut StdRng) -> &'a Candidate {
+    (0..TOURNAMENT_SIZE)
+        .map(|_| &population[rng.gen_range(0, population.len())])
+        .max_by(|a, b| fitness(a).total_cmp(&fitness(b)))
+        .expect("tournaments have candidates")
+}
+
+/// Only the ratios of the weights matter to the bot, scaling them keeps the search bounded.
+fn normalized(mut values: Values) -> Values {
+    let length = values.iter().map(|value| value * value).sum::<f64>().sqrt();
+    if length > 0.0 {
+        values.iter_mut().for_each(|value| *value /= length);
+    }
+    values
+}
+
+fn mutated(mut values: Values, rate: f64, rng: &mut StdRng) -> Values {
+    for value in values.iter_mut() {
+        if rng.gen_bool(rate) {
+            *value += gaussian(rng) * MUTATION_STRENGTH;
+        }
+    }
+    normalized(values)
+}
+
+/// A standard normal sample by the Box-Muller transform.
+fn gaussian(rng: &mut StdRng) -> f64 {
+    let (u, v) = (1.0 - rng.gen::<f64>(), rng.gen::<f64>());
+    (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bot::ai::Weights;
+    use crate::engine::mechanics::Mode;
+    use crate::sim::simulation::Tuning;
+
+    #[test]
+    fn tuning_is_reproducible() {
+        let tuning = Tuning { start: Weights::default(), population: 4, generations: 3, games: 2, seed: 5, mode: Mode::FourWide, max_pieces: 30, threads: 2 };
+        let mut generations = Vec::new();
+        let best = tuning.run(|generation| generations.push(generation.clone())).unwrap();
+
+        assert_eq!(generations.len(), 3);
+        assert_eq!(generations[2].best, best);
+        assert!(generations.windows(2).all(|pair| pair[1].best_fitness >= pair[0].best_fitness));
+        assert!(generations.iter().all(|generation| generation.mean_fitness <= generation.best_fitness));
+        assert_eq!(tuning.run(|_| {}).unwrap(), best);
+    }
+}
@@ -0,0 +1,221 @@
+use crate::engine::mechanics::Mode;
+use crate::stats::session::SessionStats;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Only the most recent sessions are kept for the trends, the totals cover all of them.
+pub const MAX_SESSIONS: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub finished_at: u64,
+    pub mode: Mode,
+    pub seconds: f64,
+    pub points: u64,
+    pub pieces: u64,
+    pub lines: u64,
+    pub keys: u64,
+    pub attack: u64,
+    pub tetrises: u64,
+    #[serde(default)]
+    pub finesse_faults: u64,
+}
+
+impl SessionRecord {
+    pub fn new(mode: Mode, points: u64, stats: &SessionStats) -> Self {
+        let finished_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since_epoch| since_epoch.as_secs());
+        Self {
+            finished_at,
+            mode,
+            seconds: stats.elapsed.as_secs_f64(),
+            points,
+            pieces: stats.pieces,
+            lines: stats.lines,
+            keys: stats.keys,
+            attack: stats.attack,
+            tetrises: stats.tetrises,
+            finesse_faults: stats.finesse_faults,
+        }
+    }
+
+    pub fn pieces_per_second(&self) -> f64 {
+        if self.seconds > 0.0 { self.pieces as f64 / self.seconds } else { 0.0 }
+    }
+
+    pub fn lines_per_minute(&self) -> f64 {
+        if self.seconds > 0.0 { self.lines as f64 * 60.0 / self.seconds } else { 0.0 }
+    }
+
+    pub fn attack_per_minute(&self) -> f64 {
+        if self.seconds > 0.0 { self.attack as f64 * 60.0 / self.seconds } else { 0.0 }
+    }
+
+    pub fn keys_per_piece(&self) -> f64 {
+        if self.pieces > 0 { self.keys as f64 / self.pieces as f64 } else { 0.0 }
+    }
+
+    pub fn faults_per_piece(&self) -> f64 {
+        if self.pieces > 0 { self.finesse_faults as f64 / self.pieces as f64 } else { 0.0 }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Totals {
+    pub games: u64,
+    pub seconds: f64,
+    pub points: u64,
+    pub pieces: u64,
+    pub lines: u64,
+    pub keys: u64,
+    pub attack: u64,
+    pub tetrises: u64,
+    pub finesse_faults: u64,
+    pub best_points: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatsHistory {
+    pub totals: Totals,
+    pub sessions: Vec<SessionRecord>,
+}
+
+#[derive(Debug)]
+pub enum StatsError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Serialize(toml::ser::Error),
+    NoDataDirectory,
+}
+
+impl std::fmt::Display for StatsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatsError::Io(path, error) => write!(f, "could not access stats file {}: {}", path.display(), error),
+            StatsError::Parse(path, error) => write!(f, "invalid stats file {}: {}", path.display(), error),
+            StatsError::Serialize(error) => write!(f, "could not serialize stats: {}", error),
+            StatsError::NoDataDirectory => write!(f, "could not determine the data directory"),
+        }
+    }
+}
+
+impl StatsHistory {
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|directory| directory.join("tet-rs").join("stats.toml"))
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, StatsError> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content).map_err(|error| StatsError::Parse(path.to_path_buf(), error)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(StatsError::Io(path.to_path_buf(), error)),
+        }
+    }
+
+    pub fn load() -> Result<Self, StatsError> {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), StatsError> {
+        let content = toml::to_string(self).map_err(StatsError::Serialize)?;
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory).map_err(|error| StatsError::Io(directory.to_path_buf(), error))?;
+        }
+        std::fs::write(path, content).map_err(|error| StatsError::Io(path.to_path_buf(), error))
+    }
+
+    pub fn save(&self) -> Result<(), StatsError> {
+        let path = Self::path().ok_or(StatsError::NoDataDirectory)?;
+        self.save_to(&path)
+    }
+
+    pub fn record(&mut self, session: SessionRecord) {
+        let totals = &mut self.totals;
+        totals.games += 1;
+        totals.seconds += session.seconds;
+        totals.points += session.points;
+        totals.pieces += session.pieces;
+        totals.lines += session.lines;
+        totals.keys += session.keys;
+        totals.attack += session.attack;
+        totals.tetrises += session.tetrises;
+        totals.finesse_faults += session.finesse_faults;
+        totals.best_points = totals.best_points.max(session.points);
+
+        self.sessions.push(session);
+        if self.sessions.len() > MAX_SESSIONS {
+            let surplus = self.sessions.len() - MAX_SESSIONS;
+            self.sessions.drain(..surplus);
+        }
+    }
+
+    /// The values of the most recent sessions, oldest first.
+    pub fn trend(&self, count: usize, value: fn(&SessionRecord) -> f64) -> Vec<f64> {
+        let skip = self.sessions.len().saturating_sub(count);
+        self.sessions.iter().skip(skip).map(value).collect()
+    }
+}
+
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub fn sparkline(values: &[f64]) -> String {
+    let max = values.iter().cloned().fold(0.0, f64::max);
+    values.iter().map(|&value| {
+        if max <= 0.0 {
+            SPARK_LEVELS[0]
+        } else {
+            SPARK_LEVELS[((value / max) * (SPARK_LEVELS.len() - 1) as f64).round() as usize]
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::mechanics::{Mode, StoneKind};
+    use crate::stats::statistics::{sparkline, SessionRecord, SessionStats, StatsHistory, MAX_SESSIONS};
+    use std::time::Duration;
+
+    fn session(pieces: u64, seconds: u64) -> SessionRecord {
+        let mut stats = SessionStats::default();
+        (0..pieces).for_each(|_| stats.record_piece(StoneKind::I, 4, 4));
+        stats.elapsed = Duration::from_secs(seconds);
+        SessionRecord::new(Mode::Marathon, pieces * 10, &stats)
+    }
+
+    #[test]
+    fn totals_and_trends() {
+        let mut history = StatsHistory::default();
+        history.record(session(10, 10));
+        history.record(session(30, 10));
+
+        assert_eq!(history.totals.games, 2);
+        assert_eq!(history.totals.pieces, 40);
+        assert_eq!(history.totals.best_points, 300);
+        assert_eq!(history.trend(5, SessionRecord::pieces_per_second), vec![1.0, 3.0]);
+        assert_eq!(history.trend(1, SessionRecord::pieces_per_second), vec![3.0]);
+
+        let serialized = toml::to_string(&history).unwrap();
+        assert_eq!(toml::from_str::<StatsHistory>(&serialized).unwrap(), history);
+    }
+
+    #[test]
+    fn keeps_recent_sessions() {
+        let mut history = StatsHistory::default();
+        (0..MAX_SESSIONS as u64 + 5).for_each(|pieces| history.record(session(pieces, 1)));
+
+        assert_eq!(history.sessions.len(), MAX_SESSIONS);
+        assert_eq!(history.sessions[0].pieces, 5);
+        assert_eq!(history.totals.games, MAX_SESSIONS as u64 + 5);
+    }
+
+    #[test]
+    fn sparkline_scales_to_the_maximum() {
+        assert_eq!(sparkline(&[0.0, 1.0, 2.0]), "▁▅█");
+        assert_eq!(sparkline(&[0.0, 0.0]), "▁▁");
+    }
+}
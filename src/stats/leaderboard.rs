@@ -0,0 +1,140 @@
+use crate::stats::history::StatsError;
+use crate::stats::session::SessionStats;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How many results are kept for survival and for every dig target.
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// A finished dig race.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DigRecord {
+    pub finished_at: u64,
+    /// The garbage lines that had to be cleared.
+    pub lines: u32,
+    pub seconds: f64,
+    pub pieces: u64,
+}
+
+impl DigRecord {
+    pub fn new(lines: u32, stats: &SessionStats) -> Self {
+        let finished_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since_epoch| since_epoch.as_secs());
+        Self { finished_at, lines, seconds: stats.elapsed.as_secs_f64(), pieces: stats.pieces }
+    }
+}
+
+/// A survival game that topped out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SurvivalRecord {
+    pub finished_at: u64,
+    pub seconds: f64,
+    pub lines: u64,
+    pub pieces: u64,
+}
+
+impl SurvivalRecord {
+    pub fn new(stats: &SessionStats) -> Self {
+        let finished_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since_epoch| since_epoch.as_secs());
+        Self { finished_at, seconds: stats.elapsed.as_secs_f64(), lines: stats.lines, pieces: stats.pieces }
+    }
+}
+
+/// The best results of the dig races and of survival, kept next to the statistics.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Leaderboard {
+    dig: Vec<DigRecord>,
+    survival: Vec<SurvivalRecord>,
+}
+
+impl Leaderboard {
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|directory| directory.join("tet-rs").join("leaderboard.toml"))
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, StatsError> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content).map_err(|error| StatsError::Parse(path.to_path_buf(), error)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(StatsError::Io(path.to_path_buf(), error)),
+        }
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), StatsError> {
+        let content = toml::to_string(self).map_err(StatsError::Serialize)?;
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory).map_err(|error| StatsError::Io(directory.to_path_buf(), error))?;
+        }
+        std::fs::write(path, content).map_err(|error| StatsError::Io(path.to_path_buf(), error))
+    }
+
+    /// Adds a finished race and returns its place among the races with the same target, `None`
+    /// if it was too slow to be kept.
+    pub fn record_dig(&mut self, record: DigRecord) -> Option<usize> {
+        let (mut races, others): (Vec<_>, Vec<_>) = self.dig.drain(..).partition(|other| other.lines == record.lines);
+        let place = races.iter().position(|other| (other.seconds, other.pieces) > (record.seconds, record.pieces)).unwrap_or(races.len());
+        races.insert(place, record);
+        races.truncate(LEADERBOARD_SIZE);
+        self.dig = others;
+        self.dig.append(&mut races);
+        Some(place).filter(|&place| place < LEADERBOARD_SIZE)
+    }
+
+    /// The kept races with the given target, fastest first.
+    pub fn dig(&self, lines: u32) -> Vec<&DigRecord> {
+        self.dig.iter().filter(|record| record.lines == lines).collect()
+    }
+
+    /// Adds a survival game and returns its place, `None` if it was too short to be kept.
+    pub fn record_survival(&mut self, record: SurvivalRecord) -> Option<usize> {
+        let place = self.survival.iter().position(|other| (other.seconds, other.lines) < (record.seconds, record.lines)).unwrap_or(self.survival.len());
+        self.survival.insert(place, record);
+        self.survival.truncate(LEADERBOARD_SIZE);
+        Some(place).filter(|&place| place < LEADERBOARD_SIZE)
+    }
+
+    /// The kept survival games, longest first.
+    pub fn survival(&self) -> &[SurvivalRecord] {
+        &self.survival
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stats::statistics::{DigRecord, Leaderboard, SurvivalRecord, LEADERBOARD_SIZE};
+
+    fn race(lines: u32, seconds: f64, pieces: u64) -> DigRecord {
+        DigRecord { finished_at: 0, lines, seconds, pieces }
+    }
+
+    #[test]
+    fn keeps_the_best_results() {
+        let mut leaderboard = Leaderboard::default();
+        for second in 0..LEADERBOARD_SIZE {
+            assert_eq!(leaderboard.record_dig(race(10, 30.0 + second as f64, 20)), Some(second));
+        }
+        assert_eq!(leaderboard.record_dig(race(18, 60.0, 40)), Some(0));
+        assert_eq!(leaderboard.record_dig(race(10, 50.0, 30)), None);
+        assert_eq!(leaderboard.record_dig(race(10, 31.0, 18)), Some(1));
+
+        let ten = leaderboard.dig(10);
+        assert_eq!(ten.len(), LEADERBOARD_SIZE);
+        assert_eq!((ten[1].seconds, ten[1].pieces), (31.0, 18));
+        assert_eq!(ten.last().unwrap().seconds, 38.0);
+        assert_eq!(leaderboard.dig(18).len(), 1);
+
+        let game = |seconds, lines| SurvivalRecord { finished_at: 0, seconds, lines, pieces: 0 };
+        assert_eq!(leaderboard.record_survival(game(60.0, 10)), Some(0));
+        assert_eq!(leaderboard.record_survival(game(90.0, 5)), Some(0));
+        assert_eq!(leaderboard.record_survival(game(60.0, 12)), Some(1));
+        let lines: Vec<u64> = leaderboard.survival().iter().map(|record| record.lines).collect();
+        assert_eq!(lines, vec![5, 12, 10]);
+
+        let path = std::env::temp_dir().join(format!("tet-rs-leaderboard-{}.toml", std::process::id()));
+        leaderboard.save_to(&path).unwrap();
+        let loaded = Leaderboard::load_from(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, leaderboard);
+    }
+}
@@ -0,0 +1,9 @@
+mod history;
+mod leaderboard;
+mod session;
+
+pub mod statistics {
+    pub use super::history::{sparkline, SessionRecord, StatsError, StatsHistory, Totals, MAX_SESSIONS};
+    pub use super::leaderboard::{DigRecord, Leaderboard, SurvivalRecord, LEADERBOARD_SIZE};
+    pub use super::session::SessionStats;
+}
@@ -0,0 +1,101 @@
+use crate::engine::mechanics::StoneKind;
+use std::time::Duration;
+
+/// Counters of a single game. The elapsed time only counts while the game is running.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionStats {
+    pub pieces: u64,
+    pub lines: u64,
+    pub keys: u64,
+    pub attack: u64,
+    pub tetrises: u64,
+    pub finesse_faults: u64,
+    pub piece_counts: [u64; 7],
+    pub elapsed: Duration,
+}
+
+fn per_minute(count: u64, elapsed: Duration) -> f64 {
+    per_second(count, elapsed) * 60.0
+}
+
+fn per_second(count: u64, elapsed: Duration) -> f64 {
+    if elapsed.as_secs_f64() > 0.0 {
+        count as f64 / elapsed.as_secs_f64()
+    } else {
+        0.0
+    }
+}
+
+impl SessionStats {
+    pub fn record_key(&mut self) {
+        self.keys += 1;
+    }
+
+    pub fn record_piece(&mut self, kind: StoneKind, lines: usize, attack: u32) {
+        self.pieces += 1;
+        self.piece_counts[kind.index()] += 1;
+        self.lines += lines as u64;
+        self.attack += attack as u64;
+        if lines == 4 {
+            self.tetrises += 1;
+        }
+    }
+
+    pub fn record_finesse_fault(&mut self) {
+        self.finesse_faults += 1;
+    }
+
+    pub fn pieces_per_second(&self) -> f64 {
+        per_second(self.pieces, self.elapsed)
+    }
+
+    pub fn lines_per_minute(&self) -> f64 {
+        per_minute(self.lines, self.elapsed)
+    }
+
+    pub fn attack_per_minute(&self) -> f64 {
+        per_minute(self.attack, self.elapsed)
+    }
+
+    pub fn keys_per_piece(&self) -> f64 {
+        if self.pieces > 0 { self.keys as f64 / self.pieces as f64 } else { 0.0 }
+    }
+
+    /// Share of the cleared lines that were cleared with tetrises.
+    pub fn tetris_rate(&self) -> f64 {
+        if self.lines > 0 { (4 * self.tetrises) as f64 / self.lines as f64 } else { 0.0 }
+    }
+
+    pub fn piece_count(&self, kind: StoneKind) -> u64 {
+        self.piece_counts[kind.index()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::mechanics::StoneKind;
+    use crate::stats::statistics::SessionStats;
+    use std::time::Duration;
+
+    #[test]
+    fn rates() {
+        let mut stats = SessionStats::default();
+        assert_eq!(stats.pieces_per_second(), 0.0);
+        assert_eq!(stats.tetris_rate(), 0.0);
+
+        stats.record_piece(StoneKind::I, 4, 4);
+        stats.record_piece(StoneKind::T, 2, 1);
+        stats.record_piece(StoneKind::T, 0, 0);
+        stats.record_piece(StoneKind::O, 2, 1);
+        (0..10).for_each(|_| stats.record_key());
+        stats.elapsed = Duration::from_secs(2);
+
+        assert_eq!(stats.pieces_per_second(), 2.0);
+        assert_eq!(stats.lines_per_minute(), 240.0);
+        assert_eq!(stats.attack_per_minute(), 180.0);
+        assert_eq!(stats.keys_per_piece(), 2.5);
+        assert_eq!(stats.tetris_rate(), 0.5);
+        assert_eq!(stats.piece_count(StoneKind::T), 2);
+        assert_eq!(stats.piece_count(StoneKind::S), 0);
+    }
+}
@@ -0,0 +1,51 @@
+use crate::engine::mechanics::{encode_fumen, FumenPage};
+use std::io::{stdout, Write};
+
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], chunk.get(1).copied().unwrap_or(0), chunk.get(2).copied().unwrap_or(0)];
+        let value = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(BASE64[(value >> (18 - 6 * index) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Asks the terminal to put the text on the clipboard with an OSC 52 sequence, terminals that
+/// don't support it ignore the request.
+fn copy_to_clipboard(text: &str) -> std::io::Result<()> {
+    let mut stdout = stdout();
+    write!(stdout, "\u{1b}]52;c;{}\u{7}", base64(text.as_bytes()))?;
+    stdout.flush()
+}
+
+/// Copies the page as a fumen and describes the outcome for the status line.
+pub fn copy_fumen(page: FumenPage) -> String {
+    let copied = encode_fumen(&[page]).and_then(|code| copy_to_clipboard(&code).map(|_| code).map_err(|error| error.to_string()));
+    match copied {
+        Ok(code) => format!("copied {}", code),
+        Err(error) => format!("could not copy the board: {}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::base64;
+
+    #[test]
+    fn base64_padding() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"v115@vhAAgH"), "djExNUB2aEFBZ0g=");
+    }
+}
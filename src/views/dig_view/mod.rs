@@ -0,0 +1,5 @@
+mod view;
+
+pub mod dig_view {
+    pub use super::view::DigView;
+}
@@ -0,0 +1,171 @@
+use crate::config::settings::Action;
+use crate::engine::mechanics::{DigRace, GarbageHoles, HoleSwitch, Mode, PieceGenerator, DIG_TARGETS};
+use crate::net::network::Snapshot;
+use crate::rendering::renderer::{Canvas, Position};
+use crate::stats::statistics::DigRecord;
+use crate::views::clipboard::copy_fumen;
+use crate::views::pause_view::pause_view::PauseView;
+use crate::views::player::Player;
+use crate::views::ranking::{format_time, Ranking};
+use crate::views::view::{Context, Transition, View};
+use crossterm::event::{Event, KeyCode};
+use std::time::{Duration, Instant};
+
+/// Races through a number of garbage lines with messy holes, the field is topped up from below
+/// until all of them were sent. Finished races go on the leaderboard by time, then pieces.
+pub struct DigView {
+    race: DigRace,
+    player: Player,
+    placed: u64,
+    ranking: Ranking,
+    message: Option<String>,
+}
+
+impl DigView {
+    pub fn new(target: u32, now: Instant) -> Self {
+        let generator = PieceGenerator::random();
+        let holes = GarbageHoles::new(HoleSwitch::PerRow, 1.0, generator.seed());
+        let player = Player::new(Mode::Marathon.layout(), generator, now).with_garbage_holes(holes);
+        let (ranking, message) = Ranking::load();
+        let mut view = Self { race: DigRace::new(target), player, placed: 0, ranking, message };
+        view.refill();
+        view
+    }
+
+    fn refill(&mut self) {
+        let rows = self.race.refill(self.player.field().garbage_rows());
+        if rows > 0 {
+            self.player.insert_garbage(rows);
+        }
+    }
+
+    /// Tops the garbage up after every lock and ends the race once all of it is cleared.
+    fn check(&mut self) {
+        let pieces = self.player.stats().pieces;
+        if self.ranking.place.is_some() || pieces == self.placed {
+            return;
+        }
+        self.placed = pieces;
+        self.refill();
+        if self.race.is_finished() {
+            self.finish();
+        }
+    }
+
+    fn finish(&mut self) {
+        self.player.stop();
+        let record = DigRecord::new(self.race.target(), self.player.stats());
+        if let Err(error) = self.ranking.record(|leaderboard| leaderboard.record_dig(record)) {
+            self.message = Some(error);
+        }
+    }
+
+    fn next_target(&self) -> u32 {
+        DIG_TARGETS.iter().copied().find(|&target| target > self.race.target()).unwrap_or(DIG_TARGETS[0])
+    }
+}
+
+impl View for DigView {
+    fn render_at(&self, canvas: &mut Canvas, position: Position, context: &Context) {
+        let dimensions = *self.player.field().dimensions();
+        let status = self.player.render_at(canvas, position, context);
+        let stats = self.player.stats();
+
+        let lines = [
+            format!("dig {} lines", self.race.target()),
+            format!("left   {}", self.race.left()),
+            format!("time   {}", format_time(stats.elapsed)),
+            format!("pieces {}", stats.pieces),
+        ];
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        canvas.add_themed_paragraph(&lines, status);
+
+        let result = status.offset_by(&Position { x: 0, y: 5 });
+        match self.ranking.place {
+            Some(Some(place)) => canvas.add_highlighted_text(&format!("finished, place {} on the leaderboard", place + 1), &result),
+            Some(None) => canvas.add_highlighted_text("finished", &result),
+            None if self.player.is_game_over() => canvas.add_highlighted_text("topped out", &result),
+            None => {}
+        }
+        if let Some(message) = &self.message {
+            canvas.add_highlighted_text(message, &result.offset_by(&Position { x: 0, y: 1 }));
+        }
+
+        let records = self.ranking.leaderboard.dig(self.race.target());
+        let results = records.iter()
+            .map(|record| format!("{}  {} pieces", format_time(Duration::from_secs_f64(record.seconds)), record.pieces));
+        self.ranking.render_at(canvas, result.offset_by(&Position { x: 0, y: 3 }), results);
+
+        let keys = &context.config.keys;
+        let help = [
+            format!("clear {} lines of garbage as fast as you can", self.race.target()),
+            format!("tab - dig {} lines, {} - restart, {} - copy as fumen, {} - pause", self.next_target(),
+                    keys.describe(Action::Restart), keys.describe(Action::CopyFumen), keys.describe(Action::Pause)),
+        ];
+        let help: Vec<&str> = help.iter().map(String::as_str).collect();
+        canvas.add_themed_paragraph(&help, position.offset_by(&Position { x: 0, y: dimensions.height as i32 + 1 }));
+    }
+
+    fn handle_input(&mut self, event: &Event, now: Instant, context: &mut Context) -> Transition {
+        let key_event = match event {
+            Event::Key(key_event) => key_event,
+            _ => return Transition::None,
+        };
+        if key_event.code == KeyCode::Tab {
+            return Transition::Replace(Box::new(DigView::new(self.next_target(), now)));
+        }
+
+        match context.config.keys.action_for(key_event) {
+            Some(Action::Quit) => return Transition::Quit,
+            Some(Action::Pause) => {
+                let target = self.race.target();
+                return Transition::Push(Box::new(PauseView::new(Box::new(move |now| Box::new(DigView::new(target, now))))));
+            }
+            Some(Action::Restart) => return Transition::Replace(Box::new(DigView::new(self.race.target(), now))),
+            Some(Action::CopyFumen) => self.message = Some(copy_fumen(self.player.fumen_page())),
+            Some(action) => {
+                self.player.perform(action, now, &context.config.handling);
+                self.check();
+            }
+            None => {}
+        }
+        Transition::None
+    }
+
+    fn update(&mut self, now: Instant, context: &mut Context) -> Transition {
+        self.player.update(now, &context.config.handling);
+        self.check();
+        Transition::None
+    }
+
+    fn on_resume(&mut self, now: Instant, _context: &Context) {
+        self.player.resume(now);
+    }
+
+    fn snapshot(&self) -> Option<Snapshot> {
+        Some(self.player.snapshot())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::mechanics::{Cell, DIG_ROWS};
+    use crate::rendering::renderer::Position;
+    use crate::views::dig_view::dig_view::DigView;
+    use std::time::Instant;
+
+    #[test]
+    fn starts_with_messy_garbage() {
+        let view = DigView::new(18, Instant::now());
+        let field = view.player.field();
+        assert_eq!(field.garbage_rows(), DIG_ROWS as usize);
+        assert_eq!((view.race.left(), view.next_target()), (18, 100));
+
+        let bottom = field.total_height() as i32 - 1;
+        let holes: Vec<i32> = (0..DIG_ROWS as i32)
+            .map(|row| (0..10).filter(|&x| field.get_cell_at_pos(&Position { x, y: bottom - row }) == Some(Cell::Empty)).collect::<Vec<_>>())
+            .map(|holes| { assert_eq!(holes.len(), 1); holes[0] })
+            .collect();
+        assert!(holes.windows(2).all(|pair| pair[0] != pair[1]));
+    }
+}
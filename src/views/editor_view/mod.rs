@@ -0,0 +1,5 @@
+mod view;
+
+pub mod editor_view {
+    pub use super::view::EditorView;
+}
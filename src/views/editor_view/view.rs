@@ -0,0 +1,279 @@
+use crate::config::settings::{Action, Config};
+use crate::engine::mechanics::{Cell, Setup, StoneKind};
+use crate::rendering::renderer::{Canvas, Dimensions, Position, Texture, Tile};
+use crate::views::clipboard::copy_fumen;
+use crate::views::play_view::play_view::PlayView;
+use crate::views::player::render_preview;
+use crate::views::view::{Context, Transition, View};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// The part of the setup that typed pieces go to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Board,
+    Hold,
+    Queue,
+}
+
+/// Paints a board cell by cell and sets the held piece and the queue. The result is saved to a
+/// file as TOML or fumen, copied as fumen or played from.
+pub struct EditorView {
+    setup: Setup,
+    path: Option<PathBuf>,
+    cursor: Position,
+    brush: Cell,
+    focus: Focus,
+    /// A fumen being typed or pasted.
+    prompt: Option<String>,
+    feedback: Option<String>,
+}
+
+impl EditorView {
+    /// `board.toml` next to the config file.
+    pub fn default_path() -> Option<PathBuf> {
+        Config::directory().map(|directory| directory.join("board.toml"))
+    }
+
+    /// Edits the file if there is one, a file with the extension `.fumen` or `.txt` is saved as
+    /// fumen.
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let setup = Setup::default();
+        let cursor = Position { x: 0, y: setup.field.total_height() as i32 - 1 };
+        let mut view = Self { setup, path, cursor, brush: Cell::Piece(StoneKind::I), focus: Focus::Board, prompt: None, feedback: None };
+        if view.path.as_deref().is_some_and(Path::exists) {
+            view.load();
+        }
+        view
+    }
+
+    fn load(&mut self) {
+        let loaded = match &self.path {
+            Some(path) => Setup::load(path).map(|setup| (setup, format!("loaded {}", path.display()))),
+            None => Err("there's no file to load from".to_string()),
+        };
+        self.feedback = Some(match loaded {
+            Ok((setup, message)) => {
+                self.setup = setup;
+                message
+            }
+            Err(error) => error,
+        });
+    }
+
+    fn save(&mut self) {
+        let saved = match &self.path {
+            Some(path) => self.setup.save(path).map(|_| format!("saved {}", path.display())),
+            None => Err("there's no file to save to".to_string()),
+        };
+        self.feedback = Some(saved.unwrap_or_else(|error| error));
+    }
+
+    fn move_cursor(&mut self, dx: i32, dy: i32) {
+        let field = &self.setup.field;
+        let top = field.hidden_rows() as i32;
+        let bottom = field.total_height() as i32 - 1;
+        self.cursor.x = (self.cursor.x + dx).clamp(0, field.dimensions().width as i32 - 1);
+        self.cursor.y = (self.cursor.y + dy).clamp(top, bottom);
+    }
+
+    fn paint(&mut self, cell: Cell) {
+        self.setup.field.set_cell_at_pos(&self.cursor, cell);
+    }
+
+    fn type_piece(&mut self, kind: StoneKind) {
+        match self.focus {
+            Focus::Board => {
+                self.brush = Cell::Piece(kind);
+                self.paint(self.brush);
+            }
+            Focus::Hold => self.setup.hold = Some(kind),
+            Focus::Queue => self.setup.queue.push(kind),
+        }
+    }
+
+    fn erase(&mut self) {
+        match self.focus {
+            Focus::Board => self.paint(Cell::Empty),
+            Focus::Hold => self.setup.hold = None,
+            Focus::Queue => {
+                self.setup.queue.pop();
+            }
+        }
+    }
+
+    fn apply_prompt(&mut self, code: &str) {
+        self.feedback = Some(match Setup::from_text(code) {
+            Ok(setup) => {
+                self.setup = setup;
+                "pasted the fumen".to_string()
+            }
+            Err(error) => error,
+        });
+    }
+
+    fn handle_prompt(&mut self, key_event: &KeyEvent) {
+        let prompt = self.prompt.get_or_insert_with(String::new);
+        match key_event.code {
+            KeyCode::Char(character) => prompt.push(character),
+            KeyCode::Backspace => {
+                prompt.pop();
+            }
+            KeyCode::Enter => {
+                let code = self.prompt.take().unwrap_or_default();
+                self.apply_prompt(&code);
+            }
+            KeyCode::Esc => self.prompt = None,
+            _ => {}
+        }
+    }
+
+    fn handle_shortcut(&mut self, character: char) {
+        match character {
+            's' => self.save(),
+            'o' => self.load(),
+            'f' => self.feedback = Some(copy_fumen(self.setup.to_fumen_page())),
+            'v' => self.prompt = Some(String::new()),
+            'n' => {
+                self.setup = Setup::default();
+                self.feedback = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl View for EditorView {
+    fn render_at(&self, canvas: &mut Canvas, position: Position, _context: &Context) {
+        let field = &self.setup.field;
+        let dimensions = *field.dimensions();
+        let field_position = position.offset_by(&Position { x: 1, y: 0 });
+        let sidebar = position.offset_by(&Position { x: dimensions.width as i32 + 3, y: 0 });
+
+        canvas.add_well(&position, &dimensions);
+        field.render_at(canvas, field_position);
+        let under_cursor = field.get_cell_at_pos(&self.cursor).unwrap_or_default().to_tile(canvas.theme());
+        let mut cursor = Texture::new(Dimensions { width: 1, height: 1 });
+        cursor.pixels[0][0] = Some(Tile::new_character('+', under_cursor.background, canvas.theme().highlight));
+        canvas.add_texture(cursor, &self.cursor.offset_by(&field_position.offset_by(&field.visible_offset())));
+
+        canvas.add_highlighted_text("board editor", &sidebar);
+        let label = |canvas: &mut Canvas, text: &str, focus: Focus, position: &Position| {
+            if self.focus == focus {
+                canvas.add_highlighted_text(text, position);
+            } else {
+                canvas.add_themed_text(text, position);
+            }
+        };
+        label(canvas, &format!("brush {}", self.brush.to_char()), Focus::Board, &sidebar.offset_by(&Position { x: 0, y: 2 }));
+        label(canvas, "hold", Focus::Hold, &sidebar.offset_by(&Position { x: 0, y: 4 }));
+        if let Some(kind) = self.setup.hold {
+            render_preview(canvas, kind, &sidebar.offset_by(&Position { x: 1, y: 5 }));
+        }
+        label(canvas, "queue", Focus::Queue, &sidebar.offset_by(&Position { x: 0, y: 8 }));
+        for (index, &kind) in self.setup.queue.iter().take(4).enumerate() {
+            render_preview(canvas, kind, &sidebar.offset_by(&Position { x: 1, y: 9 + 3 * index as i32 }));
+        }
+        let queue: String = self.setup.queue.iter().map(|kind| kind.letter()).collect();
+        canvas.add_themed_text(&queue, &sidebar.offset_by(&Position { x: 7, y: 8 }));
+
+        let status = sidebar.offset_by(&Position { x: 7, y: 2 });
+        if let Some(path) = &self.path {
+            canvas.add_themed_text(&path.display().to_string(), &status);
+        }
+        if let Some(prompt) = &self.prompt {
+            canvas.add_highlighted_text(&format!("fumen: {}", prompt), &status.offset_by(&Position { x: 0, y: 2 }));
+        } else if let Some(feedback) = &self.feedback {
+            canvas.add_highlighted_text(feedback, &status.offset_by(&Position { x: 0, y: 2 }));
+        }
+
+        let help = [
+            "arrows - move, i j l o s t z g - paint that piece or garbage, space - paint again, backspace - erase",
+            "tab - switch between board, hold and queue, enter - play from here, esc - back",
+            "ctrl+s - save, ctrl+o - load, ctrl+f - copy as fumen, ctrl+v - paste a fumen, ctrl+n - clear",
+        ];
+        canvas.add_themed_paragraph(&help, position.offset_by(&Position { x: 0, y: dimensions.height as i32 + 1 }));
+    }
+
+    fn handle_input(&mut self, event: &Event, now: Instant, context: &mut Context) -> Transition {
+        let key_event = match event {
+            Event::Key(key_event) => key_event,
+            _ => return Transition::None,
+        };
+        if context.config.keys.action_for(key_event) == Some(Action::Quit) {
+            return Transition::Quit;
+        }
+        if self.prompt.is_some() {
+            self.handle_prompt(key_event);
+            return Transition::None;
+        }
+
+        match key_event.code {
+            KeyCode::Char(character) if key_event.modifiers.contains(KeyModifiers::CONTROL) => self.handle_shortcut(character),
+            KeyCode::Left => self.move_cursor(-1, 0),
+            KeyCode::Right => self.move_cursor(1, 0),
+            KeyCode::Up => self.move_cursor(0, -1),
+            KeyCode::Down => self.move_cursor(0, 1),
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Board => Focus::Hold,
+                    Focus::Hold => Focus::Queue,
+                    Focus::Queue => Focus::Board,
+                }
+            }
+            KeyCode::Char(' ') if self.focus == Focus::Board => self.paint(self.brush),
+            KeyCode::Char('g') | KeyCode::Char('G') if self.focus == Focus::Board => {
+                self.brush = Cell::Garbage;
+                self.paint(self.brush);
+            }
+            KeyCode::Char(letter) => {
+                if let Some(kind) = StoneKind::from_letter(letter) {
+                    self.type_piece(kind);
+                }
+            }
+            KeyCode::Backspace | KeyCode::Delete => self.erase(),
+            KeyCode::Enter => return Transition::Push(Box::new(PlayView::from_setup(self.setup.clone(), now))),
+            KeyCode::Esc => return Transition::Pop,
+            _ => {}
+        }
+        Transition::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::mechanics::{encode_fumen, Cell, StoneKind};
+    use crate::views::editor_view::editor_view::EditorView;
+    use super::Focus;
+
+    #[test]
+    fn paints_cells_and_edits_the_pieces() {
+        let mut editor = EditorView::new(None);
+        editor.type_piece(StoneKind::T);
+        editor.move_cursor(1, 0);
+        editor.paint(editor.brush);
+        editor.move_cursor(-5, -100);
+        editor.paint(Cell::Garbage);
+        assert_eq!(editor.setup.rows().len(), 20);
+        assert_eq!(editor.setup.rows()[19], "TT........");
+        assert_eq!(editor.setup.rows()[0], "G.........");
+        editor.erase();
+        assert_eq!(editor.setup.rows(), vec!["TT........"]);
+
+        editor.focus = Focus::Hold;
+        editor.type_piece(StoneKind::O);
+        editor.focus = Focus::Queue;
+        [StoneKind::I, StoneKind::S, StoneKind::Z].iter().for_each(|&kind| editor.type_piece(kind));
+        editor.erase();
+        assert_eq!((editor.setup.hold, &editor.setup.queue), (Some(StoneKind::O), &vec![StoneKind::I, StoneKind::S]));
+
+        let code = encode_fumen(&[editor.setup.to_fumen_page()]).unwrap();
+        let mut other = EditorView::new(None);
+        other.apply_prompt(&code);
+        assert_eq!(other.setup.rows(), editor.setup.rows());
+        assert_eq!((other.setup.hold, &other.setup.queue), (editor.setup.hold, &editor.setup.queue));
+        other.apply_prompt("v115@broken");
+        assert_eq!(other.setup.rows(), editor.setup.rows());
+    }
+}
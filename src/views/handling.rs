@@ -0,0 +1,177 @@
+use crate::config::settings::Handling;
+use std::time::{Duration, Instant};
+
+// Terminals don't report key releases, holding a key only produces repeated presses. The first
+// repetition takes a while to arrive, the following ones come in quickly, so a key counts as
+// released as soon as the expected repetition doesn't show up in time.
+const FIRST_REPEAT_WINDOW: Duration = Duration::from_millis(600);
+const RELEASE_TIMEOUT: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shift {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct KeyHold {
+    pressed_at: Instant,
+    last_event: Instant,
+    repeats: u32,
+}
+
+impl KeyHold {
+    pub fn new(now: Instant) -> Self {
+        Self { pressed_at: now, last_event: now, repeats: 0 }
+    }
+
+    fn window(&self) -> Duration {
+        if self.repeats == 0 { FIRST_REPEAT_WINDOW } else { RELEASE_TIMEOUT }
+    }
+
+    pub fn is_released(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.last_event) > self.window()
+    }
+
+    fn repeat(&mut self, now: Instant) {
+        self.last_event = now;
+        self.repeats += 1;
+    }
+
+    /// A single repetition might as well be a quick double tap, only the second one proves that the
+    /// key is really held down.
+    pub fn is_held(&self, now: Instant) -> bool {
+        self.repeats >= 2 && !self.is_released(now)
+    }
+
+    /// Registers a press and returns whether it started a new hold rather than continuing this one.
+    pub fn press(hold: &mut Option<KeyHold>, now: Instant) -> bool {
+        match hold {
+            Some(hold) if !hold.is_released(now) => {
+                hold.repeat(now);
+                false
+            }
+            _ => {
+                *hold = Some(KeyHold::new(now));
+                true
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AutoShift {
+    held: Option<(Shift, KeyHold)>,
+    next_shift: Option<Instant>,
+}
+
+impl AutoShift {
+    /// Returns whether the piece should be shifted once right away.
+    pub fn press(&mut self, direction: Shift, now: Instant, handling: &Handling) -> bool {
+        let mut hold = match self.held {
+            Some((held_direction, hold)) if held_direction == direction => Some(hold),
+            _ => None,
+        };
+
+        if KeyHold::press(&mut hold, now) {
+            self.held = hold.map(|hold| (direction, hold));
+            self.next_shift = None;
+            return true;
+        }
+
+        let hold = hold.expect("press keeps the hold");
+        self.held = Some((direction, hold));
+        if hold.repeats == 1 {
+            return true;
+        }
+
+        if self.next_shift.is_none() {
+            self.next_shift = Some(std::cmp::max(hold.pressed_at + handling.delayed_auto_shift(), now));
+        }
+        false
+    }
+
+    pub fn release(&mut self) {
+        self.held = None;
+        self.next_shift = None;
+    }
+
+    /// Returns the direction and the number of cells the piece should be shifted by, an ARR of 0
+    /// shifts all the way to the wall.
+    pub fn update(&mut self, now: Instant, handling: &Handling) -> Option<(Shift, usize)> {
+        let (direction, hold) = self.held?;
+        if hold.is_released(now) {
+            self.release();
+            return None;
+        }
+
+        let next_shift = self.next_shift.filter(|&next_shift| next_shift <= now)?;
+        if handling.arr == 0 {
+            return Some((direction, usize::MAX));
+        }
+
+        let repeat_rate = handling.auto_repeat_rate();
+        let shifts = 1 + (now - next_shift).as_millis() / repeat_rate.as_millis();
+        self.next_shift = Some(next_shift + repeat_rate * shifts as u32);
+        Some((direction, shifts as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::settings::Handling;
+    use crate::views::handling::{AutoShift, KeyHold, Shift};
+    use std::time::{Duration, Instant};
+
+    fn after(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn taps_and_holds() {
+        let start = Instant::now();
+        let mut hold = None;
+
+        assert!(KeyHold::press(&mut hold, start));
+        assert!(!KeyHold::press(&mut hold, after(start, 400)));
+        assert!(!hold.unwrap().is_held(after(start, 400)));
+        assert!(!KeyHold::press(&mut hold, after(start, 430)));
+        assert!(hold.unwrap().is_held(after(start, 500)));
+        assert!(!hold.unwrap().is_held(after(start, 600)));
+        assert!(KeyHold::press(&mut hold, after(start, 600)));
+    }
+
+    #[test]
+    fn auto_shift_after_delay() {
+        let handling = Handling { das: 500, arr: 50, sdf: 6 };
+        let start = Instant::now();
+        let mut auto_shift = AutoShift::default();
+
+        assert!(auto_shift.press(Shift::Left, start, &handling));
+        assert!(auto_shift.press(Shift::Left, after(start, 300), &handling));
+        assert!(!auto_shift.press(Shift::Left, after(start, 330), &handling));
+        assert_eq!(auto_shift.update(after(start, 360), &handling), None);
+        assert!(!auto_shift.press(Shift::Left, after(start, 390), &handling));
+        assert!(!auto_shift.press(Shift::Left, after(start, 420), &handling));
+        assert!(!auto_shift.press(Shift::Left, after(start, 450), &handling));
+        assert!(!auto_shift.press(Shift::Left, after(start, 480), &handling));
+        assert_eq!(auto_shift.update(after(start, 500), &handling), Some((Shift::Left, 1)));
+        assert!(!auto_shift.press(Shift::Left, after(start, 510), &handling));
+        assert_eq!(auto_shift.update(after(start, 600), &handling), Some((Shift::Left, 2)));
+        assert_eq!(auto_shift.update(after(start, 700), &handling), None);
+
+        assert!(auto_shift.press(Shift::Right, after(start, 700), &handling));
+    }
+
+    #[test]
+    fn instant_auto_repeat_goes_to_the_wall() {
+        let handling = Handling { das: 0, arr: 0, sdf: 6 };
+        let start = Instant::now();
+        let mut auto_shift = AutoShift::default();
+
+        auto_shift.press(Shift::Right, start, &handling);
+        auto_shift.press(Shift::Right, after(start, 300), &handling);
+        auto_shift.press(Shift::Right, after(start, 330), &handling);
+        assert_eq!(auto_shift.update(after(start, 340), &handling), Some((Shift::Right, usize::MAX)));
+    }
+}
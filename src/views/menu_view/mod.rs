@@ -0,0 +1,5 @@
+mod view;
+
+pub mod menu_view {
+    pub use super::view::MenuView;
+}
@@ -0,0 +1,166 @@
+use crate::config::settings::Action;
+use crate::engine::mechanics::{Mode, PieceGenerator, DIG_TARGETS};
+use crate::rendering::renderer::{Canvas, Position};
+use crate::views::dig_view::dig_view::DigView;
+use crate::views::editor_view::editor_view::EditorView;
+use crate::views::play_view::play_view::PlayView;
+use crate::views::settings_view::settings_view::SettingsView;
+use crate::views::stats_view::stats_view::StatsView;
+use crate::views::survival_view::survival_view::SurvivalView;
+use crate::views::opener_view::opener_view::OpenerView;
+use crate::views::pc_view::pc_view::PcTrainerView;
+use crate::views::puzzle_view::puzzle_view::PuzzleSelectView;
+use crate::views::trainer_view::trainer_view::TrainerView;
+use crate::views::versus_view::versus_view::VersusView;
+use crate::views::view::{Context, Transition, View};
+use crossterm::event::{Event, KeyCode};
+use std::time::{Duration, Instant};
+
+/// How long the menu waits for input before the AI demo starts.
+const DEMO_DELAY: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Entry {
+    Play(Mode),
+    Dig,
+    Survival,
+    Versus,
+    TwoPlayers,
+    FinesseTrainer,
+    PcTrainer,
+    OpenerPractice,
+    Puzzles,
+    Editor,
+    WatchBot,
+    Demo,
+    Statistics,
+    Settings,
+    Quit,
+}
+
+impl Entry {
+    const ALL: [Entry; 17] = [
+        Entry::Play(Mode::Marathon),
+        Entry::Play(Mode::FourWide),
+        Entry::Play(Mode::Big),
+        Entry::Dig,
+        Entry::Survival,
+        Entry::Versus,
+        Entry::TwoPlayers,
+        Entry::FinesseTrainer,
+        Entry::PcTrainer,
+        Entry::OpenerPractice,
+        Entry::Puzzles,
+        Entry::Editor,
+        Entry::WatchBot,
+        Entry::Demo,
+        Entry::Statistics,
+        Entry::Settings,
+        Entry::Quit,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Entry::Play(mode) => mode.name(),
+            Entry::Dig => "dig race",
+            Entry::Survival => "survival",
+            Entry::Versus => "versus cpu",
+            Entry::TwoPlayers => "two players",
+            Entry::FinesseTrainer => "finesse trainer",
+            Entry::PcTrainer => "perfect clear trainer",
+            Entry::OpenerPractice => "opener practice",
+            Entry::Puzzles => "puzzles",
+            Entry::Editor => "board editor",
+            Entry::WatchBot => "watch the bot",
+            Entry::Demo => "AI demo",
+            Entry::Statistics => "statistics",
+            Entry::Settings => "settings",
+            Entry::Quit => "quit",
+        }
+    }
+}
+
+pub struct MenuView {
+    selected: usize,
+    last_input: Instant,
+}
+
+impl MenuView {
+    /// Opens the menu with the given mode selected.
+    pub fn new(mode: Mode, now: Instant) -> Self {
+        let selected = Entry::ALL.iter().position(|&entry| entry == Entry::Play(mode)).unwrap_or(0);
+        Self { selected, last_input: now }
+    }
+
+    fn choose(&self, entry: Entry, now: Instant, context: &Context) -> Transition {
+        let mode = context.config.default_mode;
+        match entry {
+            Entry::Play(mode) => Transition::Push(Box::new(PlayView::new(mode, now))),
+            Entry::Dig => Transition::Push(Box::new(DigView::new(DIG_TARGETS[0], now))),
+            Entry::Survival => Transition::Push(Box::new(SurvivalView::new(now))),
+            Entry::Versus => Transition::Push(Box::new(VersusView::against_cpu(context.config.cpu_difficulty, context.config.bot_weights.clone(), now))),
+            Entry::TwoPlayers => Transition::Push(Box::new(VersusView::local(now))),
+            Entry::FinesseTrainer => Transition::Push(Box::new(TrainerView::new(PieceGenerator::random()))),
+            Entry::PcTrainer => Transition::Push(Box::new(PcTrainerView::random())),
+            Entry::OpenerPractice => Transition::Push(Box::new(OpenerView::load())),
+            Entry::Puzzles => Transition::Push(Box::new(PuzzleSelectView::load())),
+            Entry::Editor => Transition::Push(Box::new(EditorView::new(EditorView::default_path()))),
+            Entry::WatchBot => Transition::Push(Box::new(PlayView::watch_bot(mode, context.config.bot_weights.clone(), now))),
+            Entry::Demo => Transition::Push(Box::new(PlayView::demo(mode, context.config.bot_weights.clone(), now))),
+            Entry::Statistics => Transition::Push(Box::new(StatsView::new())),
+            Entry::Settings => Transition::Push(Box::new(SettingsView::new(context))),
+            Entry::Quit => Transition::Quit,
+        }
+    }
+}
+
+impl View for MenuView {
+    fn render_at(&self, canvas: &mut Canvas, position: Position, _context: &Context) {
+        canvas.add_highlighted_text("tet-rs", &position);
+
+        let mut line = position.offset_by(&Position { x: 0, y: 2 });
+        for (index, entry) in Entry::ALL.iter().enumerate() {
+            if index == self.selected {
+                canvas.add_highlighted_text(entry.label(), &line);
+            } else {
+                canvas.add_themed_text(entry.label(), &line);
+            }
+            line.move_down();
+        }
+
+        line.move_down();
+        canvas.add_themed_paragraph(&["up/down - select, enter - confirm"], line);
+    }
+
+    fn handle_input(&mut self, event: &Event, now: Instant, context: &mut Context) -> Transition {
+        let key_event = match event {
+            Event::Key(key_event) => key_event,
+            _ => return Transition::None,
+        };
+        self.last_input = now;
+
+        match key_event.code {
+            KeyCode::Up => self.selected = (self.selected + Entry::ALL.len() - 1) % Entry::ALL.len(),
+            KeyCode::Down => self.selected = (self.selected + 1) % Entry::ALL.len(),
+            KeyCode::Enter => return self.choose(Entry::ALL[self.selected], now, context),
+            _ => if let Some(Action::Quit) = context.config.keys.action_for(key_event) {
+                return Transition::Quit;
+            },
+        }
+
+        Transition::None
+    }
+
+    fn update(&mut self, now: Instant, context: &mut Context) -> Transition {
+        if now.saturating_duration_since(self.last_input) < DEMO_DELAY {
+            return Transition::None;
+        }
+
+        self.last_input = now;
+        Transition::Push(Box::new(PlayView::demo(context.config.default_mode, context.config.bot_weights.clone(), now)))
+    }
+
+    fn on_resume(&mut self, now: Instant, _context: &Context) {
+        self.last_input = now;
+    }
+}
@@ -1,5 +1,39 @@
+mod clipboard;
+mod dig_view;
+mod editor_view;
+mod handling;
+mod menu_view;
+mod network_view;
+mod opener_view;
+mod pause_view;
+mod pc_view;
 mod play_view;
+mod player;
+mod puzzle_view;
+mod ranking;
+mod settings_view;
+mod stats_view;
+mod survival_view;
+mod trainer_view;
+mod versus_view;
+mod view;
+mod watch_view;
 
 pub mod views {
+    pub use super::dig_view::dig_view::DigView;
+    pub use super::editor_view::editor_view::EditorView;
+    pub use super::menu_view::menu_view::MenuView;
+    pub use super::network_view::network_view::NetworkView;
+    pub use super::opener_view::opener_view::OpenerView;
+    pub use super::pause_view::pause_view::PauseView;
+    pub use super::pc_view::pc_view::PcTrainerView;
     pub use super::play_view::play_view::PlayView;
-}
\ No newline at end of file
+    pub use super::puzzle_view::puzzle_view::{PuzzleSelectView, PuzzleView};
+    pub use super::settings_view::settings_view::SettingsView;
+    pub use super::stats_view::stats_view::StatsView;
+    pub use super::survival_view::survival_view::SurvivalView;
+    pub use super::trainer_view::trainer_view::TrainerView;
+    pub use super::versus_view::versus_view::{Opponent, VersusView};
+    pub use super::view::{Context, Transition, View, ViewFactory};
+    pub use super::watch_view::watch_view::WatchView;
+}
@@ -0,0 +1,5 @@
+mod view;
+
+pub mod network_view {
+    pub use super::view::NetworkView;
+}
@@ -0,0 +1,145 @@
+use crate::config::settings::Action;
+use crate::engine::mechanics::{Mode, PieceGenerator};
+use crate::net::network::{Connection, Message, Session, Snapshot};
+use crate::rendering::renderer::{Canvas, Position};
+use crate::views::player::{render_snapshot, Player};
+use crate::views::view::{Context, Transition, View};
+use crossterm::event::Event;
+use std::time::Instant;
+
+/// Width of the sidebar next to each well.
+const SIDEBAR_WIDTH: i32 = 16;
+
+/// A versus game against someone on another machine. Each side plays its own field and sends its
+/// board and attacks, the opponent's field is only displayed. There's no pause or restart, the
+/// game goes on on the other side.
+pub struct NetworkView {
+    player: Player,
+    opponent: Option<Snapshot>,
+    connection: Connection,
+    sent: Option<Snapshot>,
+    lost: bool,
+    opponent_lost: bool,
+}
+
+impl NetworkView {
+    pub fn new(session: Session, now: Instant) -> Self {
+        Self {
+            player: Player::new(Mode::Marathon.layout(), PieceGenerator::new(session.seed), now),
+            opponent: None,
+            connection: session.connection,
+            sent: None,
+            lost: false,
+            opponent_lost: false,
+        }
+    }
+
+    fn handle_message(&mut self, message: Message) {
+        match message {
+            Message::State { snapshot } => self.opponent = Some(snapshot),
+            Message::Attack { lines } => self.player.receive_garbage(lines),
+            Message::GameOver => {
+                self.opponent_lost = true;
+                self.player.stop();
+            }
+            _ => {}
+        }
+    }
+
+    /// Sends the attacks and the board if it changed since the last frame.
+    fn send_updates(&mut self) {
+        let attack = self.player.take_attack();
+        if attack > 0 {
+            self.connection.send(Message::Attack { lines: attack });
+        }
+
+        if self.player.is_game_over() && !self.lost && !self.opponent_lost && self.connection.is_connected() {
+            self.lost = true;
+            self.connection.send(Message::GameOver);
+        }
+
+        let snapshot = self.player.snapshot();
+        if self.sent.as_ref() != Some(&snapshot) {
+            self.connection.send(Message::State { snapshot: snapshot.clone() });
+            self.sent = Some(snapshot);
+        }
+    }
+
+    /// The labels below both boards once the game is decided.
+    fn results(&self) -> Option<(&'static str, &'static str)> {
+        match (self.lost, self.opponent_lost) {
+            (true, true) => Some(("draw", "draw")),
+            (true, false) => Some(("game over", "winner")),
+            (false, true) => Some(("winner", "game over")),
+            (false, false) => None,
+        }
+    }
+}
+
+impl View for NetworkView {
+    fn render_at(&self, canvas: &mut Canvas, position: Position, context: &Context) {
+        let dimensions = *self.player.field().dimensions();
+        let right_position = position.offset_by(&Position { x: dimensions.width as i32 + 3 + SIDEBAR_WIDTH, y: 0 });
+        let left_status = self.player.render_at(canvas, position, context);
+        canvas.add_themed_text("you", &left_status);
+
+        match &self.opponent {
+            Some(snapshot) => {
+                let right_status = render_snapshot(canvas, snapshot, right_position, context);
+                canvas.add_themed_text("opponent", &right_status);
+                if let Some((left, right)) = self.results() {
+                    canvas.add_highlighted_text(left, &left_status.offset_by(&Position { x: 0, y: 1 }));
+                    canvas.add_highlighted_text(right, &right_status.offset_by(&Position { x: 0, y: 1 }));
+                }
+            }
+            None => canvas.add_themed_text("waiting for the opponent", &right_position),
+        }
+
+        let help_position = position.offset_by(&Position { x: 0, y: dimensions.height as i32 + 1 });
+        let ping = match self.connection.ping() {
+            Some(ping) => format!("ping: {} ms", ping.as_millis()),
+            None => "ping: -".to_string(),
+        };
+        canvas.add_themed_text(&format!("{}, {} - quit", ping, context.config.keys.describe(Action::Quit)), &help_position);
+        if let Some(error) = self.connection.error() {
+            canvas.add_highlighted_text(&error.to_string(), &help_position.offset_by(&Position { x: 0, y: 1 }));
+        }
+    }
+
+    fn handle_input(&mut self, event: &Event, now: Instant, context: &mut Context) -> Transition {
+        let action = match event {
+            Event::Key(key_event) => context.config.keys.action_for(key_event),
+            _ => None,
+        };
+
+        match action {
+            Some(Action::Quit) => return Transition::Quit,
+            Some(action) if action.is_piece_action() => {
+                self.player.perform(action, now, &context.config.handling);
+            }
+            _ => {}
+        }
+        Transition::None
+    }
+
+    fn update(&mut self, now: Instant, context: &mut Context) -> Transition {
+        for message in self.connection.receive(now) {
+            self.handle_message(message);
+        }
+        if !self.connection.is_connected() {
+            self.player.stop();
+        }
+
+        self.player.update(now, &context.config.handling);
+        self.send_updates();
+        Transition::None
+    }
+
+    fn on_resume(&mut self, now: Instant, _context: &Context) {
+        self.player.resume(now);
+    }
+
+    fn snapshot(&self) -> Option<Snapshot> {
+        Some(self.player.snapshot())
+    }
+}
@@ -0,0 +1,5 @@
+mod view;
+
+pub mod opener_view {
+    pub use super::view::OpenerView;
+}
@@ -0,0 +1,309 @@
+use crate::config::settings::Action;
+use crate::engine::mechanics::{describe, landing_cells, Field, FinesseTracker, Mode, Stone, StoneKind};
+use crate::opener::openers::{Opener, OpenerLibrary};
+use crate::rendering::renderer::{Canvas, Position};
+use crate::views::handling::{AutoShift, Shift};
+use crate::views::pause_view::pause_view::PauseView;
+use crate::views::player::render_preview;
+use crate::views::view::{Context, Transition, View};
+use crossterm::event::{Event, KeyCode};
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Grade {
+    Perfect,
+    Good,
+    Miss,
+}
+
+/// Feeds bags that fit the chosen opener and outlines where the next piece goes. Placements on the
+/// target are graded by their inputs, others count as a miss and the piece starts over.
+pub struct OpenerView {
+    library: OpenerLibrary,
+    selected: usize,
+    field: Field,
+    bag: Vec<StoneKind>,
+    placed: usize,
+    current_stone: Stone,
+    finesse: FinesseTracker,
+    auto_shift: AutoShift,
+    grades: [u32; 3],
+    built: u32,
+    feedback: Option<String>,
+}
+
+impl OpenerView {
+    pub fn new(library: OpenerLibrary, selected: usize) -> Self {
+        let field = Field::new(Mode::Marathon.layout());
+        let start = Stone::new(field.spawn_position(StoneKind::I), StoneKind::I);
+        let mut view = Self {
+            library,
+            selected,
+            field,
+            bag: Vec::new(),
+            placed: 0,
+            current_stone: start,
+            finesse: FinesseTracker::new(start),
+            auto_shift: AutoShift::default(),
+            grades: [0; 3],
+            built: 0,
+            feedback: None,
+        };
+        view.start_over();
+        view
+    }
+
+    /// Practices the openers of the user's file, a broken file leaves the built-in ones.
+    pub fn load() -> Self {
+        match OpenerLibrary::load() {
+            Ok(library) => Self::new(library, 0),
+            Err(error) => {
+                let mut view = Self::new(OpenerLibrary::default(), 0);
+                view.feedback = Some(error);
+                view
+            }
+        }
+    }
+
+    fn opener(&self) -> &Opener {
+        &self.library.openers()[self.selected]
+    }
+
+    fn start_over(&mut self) {
+        self.field = Field::new(Mode::Marathon.layout());
+        self.bag = self.opener().bag(&mut rand::thread_rng());
+        self.placed = 0;
+        self.spawn();
+    }
+
+    fn spawn(&mut self) {
+        let kind = self.bag[self.placed];
+        self.current_stone = Stone::new(self.field.spawn_position(kind), kind);
+        self.reset_piece();
+    }
+
+    fn reset_piece(&mut self) {
+        self.current_stone = Stone::new(self.field.spawn_position(self.current_stone.kind), self.current_stone.kind);
+        self.finesse.reset(self.current_stone);
+        self.auto_shift.release();
+    }
+
+    fn target(&self) -> Option<&Stone> {
+        self.opener().target(self.current_stone.kind)
+    }
+
+    fn next_opener(&mut self) {
+        self.selected = (self.selected + 1) % self.library.openers().len();
+        self.feedback = None;
+        self.start_over();
+    }
+
+    fn grade(&self, placed: &Stone) -> (Grade, String) {
+        let target = match self.target() {
+            Some(target) => target,
+            None => return (Grade::Miss, "wrong placement, try again".to_string()),
+        };
+        let mut target_cells: Vec<Position> = target.cells().collect();
+        target_cells.sort_by_key(|cell| (cell.y, cell.x));
+        if landing_cells(placed, &self.field) != target_cells {
+            return (Grade::Miss, "wrong placement, try again".to_string());
+        }
+
+        match self.finesse.evaluate(&self.field, placed) {
+            Some(result) if result.is_fault() => {
+                (Grade::Good, format!("good, {} inputs instead of {}: {}", result.inputs, result.optimal.len(), describe(&result.optimal)))
+            }
+            _ => (Grade::Perfect, "perfect".to_string()),
+        }
+    }
+
+    fn place(&mut self) {
+        let (grade, feedback) = self.grade(&self.current_stone);
+        self.grades[grade as usize] += 1;
+        self.feedback = Some(feedback);
+        if grade == Grade::Miss {
+            self.reset_piece();
+            return;
+        }
+
+        while self.current_stone.move_down(&self.field) {}
+        self.field.add_stone(&self.current_stone);
+        self.placed += 1;
+        if self.placed < self.opener().targets().len() {
+            self.spawn();
+        } else {
+            self.built += 1;
+            self.feedback = Some(format!("{} built, here's the next bag", self.opener().name));
+            self.start_over();
+        }
+    }
+
+    fn shift(&mut self, direction: Shift, times: usize) {
+        for _ in 0..times {
+            let moved = match direction {
+                Shift::Left => self.current_stone.move_left(&self.field),
+                Shift::Right => self.current_stone.move_right(&self.field),
+            };
+            if !moved {
+                break;
+            }
+        }
+    }
+}
+
+impl View for OpenerView {
+    fn render_at(&self, canvas: &mut Canvas, position: Position, context: &Context) {
+        let dimensions = *self.field.dimensions();
+        let field_position = position.offset_by(&Position { x: 1, y: 0 });
+        let stone_offset = field_position.offset_by(&self.field.visible_offset());
+        let sidebar = position.offset_by(&Position { x: dimensions.width as i32 + 3, y: 0 });
+
+        canvas.add_well(&position, &dimensions);
+        self.field.render_at(canvas, field_position);
+        if let Some(target) = self.target() {
+            let target_tile = canvas.theme().target_tile(target.kind.color(canvas.theme()));
+            canvas.add_texture(target.texture(target_tile), &target.position.offset_by(&stone_offset));
+        }
+        self.current_stone.render_at(canvas, &stone_offset);
+
+        let opener = self.opener();
+        canvas.add_highlighted_text(&format!("opener practice: {}", opener.name), &sidebar);
+        canvas.add_themed_text(&opener.description, &sidebar.offset_by(&Position { x: 0, y: 1 }));
+        canvas.add_themed_text("next", &sidebar.offset_by(&Position { x: 0, y: 3 }));
+        let upcoming = self.bag[self.placed + 1..opener.targets().len()].iter();
+        for (index, &kind) in upcoming.take(4).enumerate() {
+            render_preview(canvas, kind, &sidebar.offset_by(&Position { x: 1, y: 4 + 3 * index as i32 }));
+        }
+
+        let lines = [
+            format!("piece     {}/{}", self.placed + 1, opener.targets().len()),
+            format!("built     {}", self.built),
+            format!("perfect   {}", self.grades[Grade::Perfect as usize]),
+            format!("good      {}", self.grades[Grade::Good as usize]),
+            format!("missed    {}", self.grades[Grade::Miss as usize]),
+        ];
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        canvas.add_themed_paragraph(&lines, sidebar.offset_by(&Position { x: 7, y: 3 }));
+
+        if let Some(feedback) = &self.feedback {
+            canvas.add_highlighted_text(feedback, &sidebar.offset_by(&Position { x: 7, y: 9 }));
+        }
+
+        let keys = &context.config.keys;
+        let help = [
+            "place each piece on its outline, placements with more inputs than necessary only count as good".to_string(),
+            format!("tab - next opener, {} - start over, {} - pause", keys.describe(Action::Restart), keys.describe(Action::Pause)),
+        ];
+        let help: Vec<&str> = help.iter().map(String::as_str).collect();
+        canvas.add_themed_paragraph(&help, position.offset_by(&Position { x: 0, y: dimensions.height as i32 + 1 }));
+    }
+
+    fn handle_input(&mut self, event: &Event, now: Instant, context: &mut Context) -> Transition {
+        let key_event = match event {
+            Event::Key(key_event) => key_event,
+            _ => return Transition::None,
+        };
+        if key_event.code == KeyCode::Tab {
+            self.next_opener();
+            return Transition::None;
+        }
+
+        let action = context.config.keys.action_for(key_event);
+        let handling = &context.config.handling;
+        let counts_as_input = match action {
+            Some(Action::Quit) => return Transition::Quit,
+            Some(Action::Pause) => {
+                let (library, selected) = (self.library.clone(), self.selected);
+                return Transition::Push(Box::new(PauseView::new(Box::new(move |_| Box::new(OpenerView::new(library.clone(), selected))))));
+            }
+            Some(Action::Restart) => {
+                self.feedback = None;
+                self.start_over();
+                false
+            }
+            Some(Action::MoveLeft) | Some(Action::MoveRight) => {
+                let direction = if action == Some(Action::MoveLeft) { Shift::Left } else { Shift::Right };
+                let pressed = self.auto_shift.press(direction, now, handling);
+                if pressed {
+                    self.shift(direction, 1);
+                }
+                pressed
+            }
+            Some(Action::RotateCW) => {
+                self.current_stone.rotate(&self.field);
+                true
+            }
+            Some(Action::RotateCCW) => {
+                self.current_stone.rotate_counterclockwise(&self.field);
+                true
+            }
+            Some(Action::Rotate180) => {
+                self.current_stone.rotate_180(&self.field);
+                true
+            }
+            Some(Action::SoftDrop) => {
+                while self.current_stone.move_down(&self.field) {}
+                self.finesse.record_soft_drop();
+                false
+            }
+            Some(Action::HardDrop) => {
+                self.place();
+                false
+            }
+            Some(Action::Hold) | Some(Action::CopyFumen) | None => false,
+        };
+
+        if counts_as_input {
+            self.finesse.record_input();
+        }
+        Transition::None
+    }
+
+    fn update(&mut self, now: Instant, context: &mut Context) -> Transition {
+        if let Some((direction, times)) = self.auto_shift.update(now, &context.config.handling) {
+            self.shift(direction, times);
+        }
+        Transition::None
+    }
+
+    fn on_resume(&mut self, _now: Instant, _context: &Context) {
+        self.auto_shift.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::mechanics::finesse_path;
+    use crate::opener::openers::OpenerLibrary;
+    use crate::views::opener_view::opener_view::OpenerView;
+
+    #[test]
+    fn grades_placements_until_the_opener_is_built() {
+        let mut view = OpenerView::new(OpenerLibrary::default(), 0);
+        let pieces = view.opener().targets().len();
+
+        view.place();
+        assert_eq!((view.grades, view.placed), ([0, 0, 1], 0));
+
+        for _ in 0..pieces {
+            let target = *view.target().unwrap();
+            let mut target_cells: Vec<_> = target.cells().collect();
+            target_cells.sort_by_key(|cell| (cell.y, cell.x));
+            let optimal = finesse_path(&view.field, &view.current_stone, &target_cells);
+            view.current_stone = target;
+            match optimal {
+                Some(optimal) => (0..optimal.len() + 1).for_each(|_| view.finesse.record_input()),
+                None => view.finesse.record_soft_drop(),
+            }
+            view.place();
+        }
+        assert_eq!(view.built, 1);
+        assert_eq!(view.placed, 0);
+        assert_eq!(view.grades[2], 1);
+        assert!(view.grades[1] > 0);
+        assert_eq!(view.grades[0] + view.grades[1], pieces as u32);
+
+        view.next_opener();
+        assert_eq!((view.selected, view.placed), (1, 0));
+    }
+}
@@ -0,0 +1,5 @@
+mod view;
+
+pub mod pause_view {
+    pub use super::view::PauseView;
+}
@@ -0,0 +1,95 @@
+use crate::config::settings::Action;
+use crate::rendering::renderer::{Canvas, Position};
+use crate::views::menu_view::menu_view::MenuView;
+use crate::views::settings_view::settings_view::SettingsView;
+use crate::views::stats_view::stats_view::StatsView;
+use crate::views::view::{Context, Transition, View, ViewFactory};
+use crossterm::event::{Event, KeyCode};
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Entry {
+    Resume,
+    Restart,
+    Settings,
+    Statistics,
+    MainMenu,
+    Quit,
+}
+
+impl Entry {
+    const ALL: [Entry; 6] = [Entry::Resume, Entry::Restart, Entry::Settings, Entry::Statistics, Entry::MainMenu, Entry::Quit];
+
+    fn label(self) -> &'static str {
+        match self {
+            Entry::Resume => "resume",
+            Entry::Restart => "restart",
+            Entry::Settings => "settings",
+            Entry::Statistics => "statistics",
+            Entry::MainMenu => "main menu",
+            Entry::Quit => "quit",
+        }
+    }
+}
+
+pub struct PauseView {
+    restart: ViewFactory,
+    selected: usize,
+}
+
+impl PauseView {
+    pub fn new(restart: ViewFactory) -> Self {
+        Self { restart, selected: 0 }
+    }
+
+    fn choose(&self, entry: Entry, now: Instant, context: &Context) -> Transition {
+        match entry {
+            Entry::Resume => Transition::Pop,
+            Entry::Restart => Transition::Reset((self.restart)(now)),
+            Entry::Settings => Transition::Push(Box::new(SettingsView::new(context))),
+            Entry::Statistics => Transition::Push(Box::new(StatsView::new())),
+            Entry::MainMenu => Transition::Reset(Box::new(MenuView::new(context.config.default_mode, now))),
+            Entry::Quit => Transition::Quit,
+        }
+    }
+}
+
+impl View for PauseView {
+    fn render_at(&self, canvas: &mut Canvas, position: Position, _context: &Context) {
+        canvas.add_highlighted_text("paused", &position);
+
+        let mut line = position.offset_by(&Position { x: 0, y: 2 });
+        for (index, entry) in Entry::ALL.iter().enumerate() {
+            if index == self.selected {
+                canvas.add_highlighted_text(entry.label(), &line);
+            } else {
+                canvas.add_themed_text(entry.label(), &line);
+            }
+            line.move_down();
+        }
+
+        line.move_down();
+        canvas.add_themed_paragraph(&["up/down - select, enter - confirm"], line);
+    }
+
+    fn handle_input(&mut self, event: &Event, now: Instant, context: &mut Context) -> Transition {
+        let key_event = match event {
+            Event::Key(key_event) => key_event,
+            _ => return Transition::None,
+        };
+
+        match key_event.code {
+            KeyCode::Up => self.selected = (self.selected + Entry::ALL.len() - 1) % Entry::ALL.len(),
+            KeyCode::Down => self.selected = (self.selected + 1) % Entry::ALL.len(),
+            KeyCode::Enter => return self.choose(Entry::ALL[self.selected], now, context),
+            _ => match context.config.keys.action_for(key_event) {
+                Some(Action::Pause) => return Transition::Pop,
+                Some(Action::Restart) => return self.choose(Entry::Restart, now, context),
+                Some(Action::Quit) => return Transition::Quit,
+                _ => {}
+            },
+        }
+
+        Transition::None
+    }
+}
@@ -0,0 +1,5 @@
+mod view;
+
+pub mod pc_view {
+    pub use super::view::PcTrainerView;
+}
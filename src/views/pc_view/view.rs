@@ -0,0 +1,388 @@
+use crate::bot::ai::{find_perfect_clear, MAX_PC_LINES};
+use crate::config::settings::Action;
+use crate::engine::mechanics::{decode_fumen, quiz_comment, quiz_pieces, Cell, Field, FumenPage, Mode, Stone, StoneKind};
+use crate::rendering::renderer::{Canvas, Position};
+use crate::views::clipboard::copy_fumen;
+use crate::views::handling::{AutoShift, Shift};
+use crate::views::pause_view::pause_view::PauseView;
+use crate::views::player::render_preview;
+use crate::views::view::{Context, Transition, View};
+use crossterm::event::{Event, KeyCode};
+use rand::Rng;
+use std::time::Instant;
+
+/// Perfect clear openers a few pieces in: the lowest four rows from top to bottom, the held piece
+/// (`-` for none) and the pieces left, the first one is falling.
+const SITUATIONS: [&str; 13] = [
+    "....TTTZI./.....TZZI./.OO.SSZLI./.OOSSLLLI. J ITLZ",
+    "........../...L.SSJJJ/.OOLSSTZZJ/.OOLLTTTZZ - IOITZ",
+    "........../..T......./..TT...LS./OOTZZ.JLLS - ITOZJ",
+    "I....SSZ../I...SSZZ.J/I..TTTZL.J/I...TLLLJJ O IJTS",
+    "........../JJJ......./OOJT....../OOLTTSS... - ZILJO",
+    "........../S........./SS....OO.T/JSZZ..OOTT L OZTI",
+    "........../........ZI/.....J.ZZI/OO.SSJ.ZLI - TTJLZ",
+    ".ZZIIII.../.LZZ....OO/.LTTT..JOO/.LLT...JJJ S LIZJ",
+    "........../........../...ZZ.J.LL/OO.TZZJJJL S LJOT",
+    "........../IOO......./IOO.T...S./IL..T..ZZS J ISTJ",
+    "........../JJ......../J......LLL/JSS..ZZLOO T SZIO",
+    "......IIII/.....SSOOJ/..ZZSSTOOJ/...ZZTTTJJ L ZISJ",
+    "........../..I......./..IT....ZJ/OOITSS.ZJJ L SZLO",
+];
+
+#[derive(Clone)]
+struct Situation {
+    field: Field,
+    hold: Option<StoneKind>,
+    pieces: Vec<StoneKind>,
+}
+
+impl Situation {
+    fn parse(line: &str) -> Self {
+        let mut parts = line.split(' ');
+        let mut field = Field::new(Mode::Marathon.layout());
+        let rows: Vec<&str> = parts.next().unwrap_or_default().split('/').collect();
+        let top = (field.total_height() - rows.len()) as i32;
+        for (y, row) in rows.iter().enumerate() {
+            for (x, cell) in row.chars().enumerate() {
+                field.set_cell_at_pos(&Position { x: x as i32, y: top + y as i32 }, Cell::from_char(cell).unwrap_or_default());
+            }
+        }
+        let hold = parts.next().and_then(|hold| hold.chars().next()).and_then(StoneKind::from_letter);
+        let pieces = parts.next().unwrap_or_default().chars().filter_map(StoneKind::from_letter).collect();
+        Self { field, hold, pieces }
+    }
+
+    /// The field of the page with the pieces of its quiz comment.
+    fn from_fumen(page: &FumenPage) -> Result<Self, String> {
+        let (hold, pieces) = quiz_pieces(&page.comment).filter(|(_, pieces)| !pieces.is_empty())
+            .ok_or("needs a quiz comment with the pieces, e.g. #Q=[](T)IOZ")?;
+        if find_perfect_clear(&page.field, &pieces, hold, MAX_PC_LINES).is_none() {
+            return Err(format!("has no perfect clear within {} lines", MAX_PC_LINES));
+        }
+        Ok(Self { field: page.field.clone(), hold, pieces })
+    }
+}
+
+/// Presents perfect clear openers with the first pieces already placed. Every placement and hold
+/// is checked with the solver, once no perfect clear is left the situation starts over. The next
+/// placement of a solution is shown on request.
+pub struct PcTrainerView {
+    situations: Vec<Situation>,
+    situation: usize,
+    field: Field,
+    hold: Option<StoneKind>,
+    pieces: Vec<StoneKind>,
+    current_stone: Stone,
+    solution: Vec<Stone>,
+    show_solution: bool,
+    auto_shift: AutoShift,
+    solved: u32,
+    failed: u32,
+    streak: u32,
+    feedback: Option<String>,
+}
+
+impl PcTrainerView {
+    pub fn new(situation: usize) -> Self {
+        Self::with_situations(SITUATIONS.iter().map(|line| Situation::parse(line)).collect(), situation)
+    }
+
+    /// Trains the pages of a fumen, each one needs a quiz comment with its pieces.
+    pub fn from_fumen(code: &str) -> Result<Self, String> {
+        let situations = decode_fumen(code)?.iter().enumerate()
+            .map(|(index, page)| Situation::from_fumen(page).map_err(|error| format!("page {} of the fumen {}", index + 1, error)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::with_situations(situations, 0))
+    }
+
+    fn with_situations(situations: Vec<Situation>, situation: usize) -> Self {
+        let field = Field::new(Mode::Marathon.layout());
+        let start = Stone::new(field.spawn_position(StoneKind::I), StoneKind::I);
+        let mut view = Self {
+            situations,
+            situation,
+            field,
+            hold: None,
+            pieces: Vec::new(),
+            current_stone: start,
+            solution: Vec::new(),
+            show_solution: false,
+            auto_shift: AutoShift::default(),
+            solved: 0,
+            failed: 0,
+            streak: 0,
+            feedback: None,
+        };
+        view.load(situation);
+        view
+    }
+
+    pub fn random() -> Self {
+        Self::new(rand::thread_rng().gen_range(0, SITUATIONS.len()))
+    }
+
+    fn load(&mut self, situation: usize) {
+        self.situation = situation % self.situations.len();
+        let Situation { field, hold, pieces } = self.situations[self.situation].clone();
+        self.field = field;
+        self.hold = hold;
+        self.pieces = pieces;
+        self.show_solution = false;
+        self.check();
+    }
+
+    fn next_situation(&mut self) {
+        let skip = match self.situations.len() {
+            1 => 0,
+            count => rand::thread_rng().gen_range(1, count),
+        };
+        self.load(self.situation + skip);
+    }
+
+    /// Looks for a perfect clear from the current state, starts the situation over without one.
+    fn check(&mut self) {
+        match find_perfect_clear(&self.field, &self.pieces, self.hold, MAX_PC_LINES) {
+            Some(solution) => {
+                self.solution = solution;
+                self.spawn();
+            }
+            None => {
+                self.failed += 1;
+                self.streak = 0;
+                self.feedback = Some("no perfect clear left, try again".to_string());
+                self.load(self.situation);
+            }
+        }
+    }
+
+    fn spawn(&mut self) {
+        let kind = self.pieces[0];
+        self.current_stone = Stone::new(self.field.spawn_position(kind), kind);
+        self.auto_shift.release();
+    }
+
+    fn hold(&mut self) {
+        let current = self.pieces[0];
+        match self.hold {
+            Some(held) => self.pieces[0] = held,
+            None if self.pieces.len() > 1 => {
+                self.pieces.remove(0);
+            }
+            None => return,
+        }
+        self.hold = Some(current);
+        self.check();
+    }
+
+    fn place(&mut self) {
+        while self.current_stone.move_down(&self.field) {}
+        self.field.add_stone(&self.current_stone);
+        self.field.try_delete_lines();
+        self.pieces.remove(0);
+
+        if self.field.board().is_empty() {
+            self.solved += 1;
+            self.streak += 1;
+            self.feedback = Some("perfect clear!".to_string());
+            self.next_situation();
+        } else {
+            self.feedback = None;
+            self.check();
+        }
+    }
+
+    fn shift(&mut self, direction: Shift, times: usize) {
+        for _ in 0..times {
+            let moved = match direction {
+                Shift::Left => self.current_stone.move_left(&self.field),
+                Shift::Right => self.current_stone.move_right(&self.field),
+            };
+            if !moved {
+                break;
+            }
+        }
+    }
+}
+
+impl View for PcTrainerView {
+    fn render_at(&self, canvas: &mut Canvas, position: Position, context: &Context) {
+        let dimensions = *self.field.dimensions();
+        let field_position = position.offset_by(&Position { x: 1, y: 0 });
+        let stone_offset = field_position.offset_by(&self.field.visible_offset());
+        let sidebar = position.offset_by(&Position { x: dimensions.width as i32 + 3, y: 0 });
+
+        canvas.add_well(&position, &dimensions);
+        self.field.render_at(canvas, field_position);
+        let hint = self.solution.first().filter(|_| self.show_solution);
+        if let Some(target) = hint {
+            let target_tile = canvas.theme().target_tile(target.kind.color(canvas.theme()));
+            canvas.add_texture(target.texture(target_tile), &target.position.offset_by(&stone_offset));
+        }
+        if context.config.ghost {
+            self.current_stone.render_ghost_at(canvas, &stone_offset, &self.field);
+        }
+        self.current_stone.render_at(canvas, &stone_offset);
+
+        canvas.add_highlighted_text("perfect clear trainer", &sidebar);
+        canvas.add_themed_text("hold", &sidebar.offset_by(&Position { x: 0, y: 2 }));
+        if let Some(kind) = self.hold {
+            render_preview(canvas, kind, &sidebar.offset_by(&Position { x: 1, y: 3 }));
+        }
+        canvas.add_themed_text("next", &sidebar.offset_by(&Position { x: 0, y: 6 }));
+        for (index, &kind) in self.pieces.iter().skip(1).enumerate() {
+            render_preview(canvas, kind, &sidebar.offset_by(&Position { x: 1, y: 7 + 3 * index as i32 }));
+        }
+
+        let status = sidebar.offset_by(&Position { x: 7, y: 2 });
+        let lines = [
+            format!("situation {}/{}", self.situation + 1, self.situations.len()),
+            format!("solved    {}", self.solved),
+            format!("failed    {}", self.failed),
+            format!("streak    {}", self.streak),
+        ];
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        canvas.add_themed_paragraph(&lines, status);
+
+        if let Some(target) = hint {
+            let order: Vec<String> = self.solution.iter().map(|stone| stone.kind.letter().to_string()).collect();
+            canvas.add_themed_text(&format!("solution  {}", order.join(" ")), &status.offset_by(&Position { x: 0, y: 5 }));
+            if target.kind != self.current_stone.kind {
+                canvas.add_themed_text("hold first", &status.offset_by(&Position { x: 0, y: 6 }));
+            }
+        }
+        if let Some(feedback) = &self.feedback {
+            canvas.add_highlighted_text(feedback, &status.offset_by(&Position { x: 0, y: 8 }));
+        }
+
+        let keys = &context.config.keys;
+        let help = [
+            "clear the whole board with the pieces left, soft drop moves the piece to the bottom".to_string(),
+            format!("tab - show the solution, enter - next situation, {} - start over, {} - copy as fumen, {} - pause",
+                    keys.describe(Action::Restart), keys.describe(Action::CopyFumen), keys.describe(Action::Pause)),
+        ];
+        let help: Vec<&str> = help.iter().map(String::as_str).collect();
+        canvas.add_themed_paragraph(&help, position.offset_by(&Position { x: 0, y: dimensions.height as i32 + 1 }));
+    }
+
+    fn handle_input(&mut self, event: &Event, now: Instant, context: &mut Context) -> Transition {
+        let key_event = match event {
+            Event::Key(key_event) => key_event,
+            _ => return Transition::None,
+        };
+        match key_event.code {
+            KeyCode::Tab => {
+                self.show_solution = !self.show_solution;
+                return Transition::None;
+            }
+            KeyCode::Enter => {
+                self.feedback = None;
+                self.next_situation();
+                return Transition::None;
+            }
+            _ => {}
+        }
+
+        let handling = &context.config.handling;
+        let action = context.config.keys.action_for(key_event);
+        match action {
+            Some(Action::Quit) => return Transition::Quit,
+            Some(Action::Pause) => {
+                let (situations, situation) = (self.situations.clone(), self.situation);
+                return Transition::Push(Box::new(PauseView::new(Box::new(move |_| Box::new(PcTrainerView::with_situations(situations.clone(), situation))))));
+            }
+            Some(Action::Restart) => {
+                self.feedback = None;
+                self.load(self.situation);
+            }
+            Some(Action::MoveLeft) | Some(Action::MoveRight) => {
+                let direction = if action == Some(Action::MoveLeft) { Shift::Left } else { Shift::Right };
+                if self.auto_shift.press(direction, now, handling) {
+                    self.shift(direction, 1);
+                }
+            }
+            Some(Action::RotateCW) => {
+                self.current_stone.rotate(&self.field);
+            }
+            Some(Action::RotateCCW) => {
+                self.current_stone.rotate_counterclockwise(&self.field);
+            }
+            Some(Action::Rotate180) => {
+                self.current_stone.rotate_180(&self.field);
+            }
+            Some(Action::SoftDrop) => while self.current_stone.move_down(&self.field) {},
+            Some(Action::HardDrop) => self.place(),
+            Some(Action::Hold) => self.hold(),
+            Some(Action::CopyFumen) => {
+                let mut page = FumenPage::new(self.field.clone(), None);
+                page.comment = quiz_comment(self.hold, &self.pieces);
+                self.feedback = Some(copy_fumen(page));
+            }
+            None => {}
+        }
+        Transition::None
+    }
+
+    fn update(&mut self, now: Instant, context: &mut Context) -> Transition {
+        if let Some((direction, times)) = self.auto_shift.update(now, &context.config.handling) {
+            self.shift(direction, times);
+        }
+        Transition::None
+    }
+
+    fn on_resume(&mut self, _now: Instant, _context: &Context) {
+        self.auto_shift.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::mechanics::{encode_fumen, quiz_comment, FumenPage};
+    use crate::views::pc_view::pc_view::PcTrainerView;
+    use super::SITUATIONS;
+
+    #[test]
+    fn following_the_solution_clears_the_board() {
+        for situation in 0..SITUATIONS.len() {
+            let mut trainer = PcTrainerView::new(situation);
+            assert_eq!(trainer.failed, 0, "situation {} has no perfect clear", situation + 1);
+
+            while trainer.solved == 0 {
+                let target = trainer.solution[0];
+                if target.kind != trainer.current_stone.kind {
+                    trainer.hold();
+                }
+                trainer.current_stone = target;
+                trainer.place();
+                assert_eq!(trainer.failed, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn situations_from_a_fumen() {
+        let trainer = PcTrainerView::new(1);
+        let mut page = FumenPage::new(trainer.field.clone(), None);
+        page.comment = quiz_comment(trainer.hold, &trainer.pieces);
+        let code = encode_fumen(&[page.clone(), page.clone()]).unwrap();
+
+        let mut from_fumen = PcTrainerView::from_fumen(&code).unwrap();
+        assert_eq!(from_fumen.situations.len(), 2);
+        assert_eq!((from_fumen.hold, &from_fumen.pieces), (trainer.hold, &trainer.pieces));
+        assert_eq!(from_fumen.solution.len(), trainer.solution.len());
+        from_fumen.next_situation();
+        assert_eq!(from_fumen.situation, 1);
+
+        page.comment.clear();
+        assert!(PcTrainerView::from_fumen(&encode_fumen(&[page]).unwrap()).is_err());
+    }
+
+    #[test]
+    fn a_wrong_placement_starts_over() {
+        let mut trainer = PcTrainerView::new(1);
+        let pieces = trainer.pieces.clone();
+        trainer.current_stone.position.x = 0;
+        trainer.place();
+        assert_eq!((trainer.failed, trainer.streak), (1, 0));
+        assert_eq!(trainer.pieces, pieces);
+    }
+}
@@ -1,7 +1,6 @@
+mod stats_panel;
 mod view;
-mod field;
-mod stones;
 
 pub mod play_view {
     pub use super::view::PlayView;
-}
\ No newline at end of file
+}
@@ -0,0 +1,34 @@
+use crate::engine::mechanics::StoneKind;
+use crate::rendering::renderer::{Canvas, Position};
+use crate::stats::statistics::SessionStats;
+
+const HISTOGRAM_WIDTH: u64 = 8;
+
+pub fn render_stats_at(canvas: &mut Canvas, stats: &SessionStats, position: Position) {
+    let lines = [
+        format!("pieces  {}", stats.pieces),
+        format!("PPS     {:.2}", stats.pieces_per_second()),
+        format!("LPM     {:.1}", stats.lines_per_minute()),
+        format!("APM     {:.1}", stats.attack_per_minute()),
+        format!("KPP     {:.2}", stats.keys_per_piece()),
+        format!("tetris  {:.0}%", 100.0 * stats.tetris_rate()),
+        format!("faults  {}", stats.finesse_faults),
+    ];
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    canvas.add_themed_paragraph(&lines, position);
+
+    let most_placed = StoneKind::ALL.iter().map(|&kind| stats.piece_count(kind)).max().unwrap_or(0).max(1);
+    let mut line = position.offset_by(&Position { x: 0, y: lines.len() as i32 + 1 });
+    for &kind in StoneKind::ALL.iter() {
+        let count = stats.piece_count(kind);
+        let bar_length = (count * HISTOGRAM_WIDTH).div_ceil(most_placed);
+        let bar = "█".repeat(bar_length as usize);
+        let color = kind.color(canvas.theme());
+        let background = canvas.theme().text_background;
+
+        canvas.add_themed_text(&format!("{:?}", kind), &line);
+        canvas.add_text(&bar, background, color, &line.offset_by(&Position { x: 2, y: 0 }));
+        canvas.add_themed_text(&count.to_string(), &line.offset_by(&Position { x: 3 + HISTOGRAM_WIDTH as i32, y: 0 }));
+        line.move_down();
+    }
+}
@@ -1,151 +1,209 @@
-use super::field;
-use super::stones::Stone;
-use std::sync::Arc;
-use tokio::sync::Mutex;
-use std::time::{Duration};
-use crate::game::Game;
-use tokio::time::delay_for;
-use crate::rendering::renderer::{Texture, Position, Canvas, Dimensions};
-use tokio::task::JoinHandle;
+use crate::bot::ai::{BotChoice, BotDriver, Weights, DEFAULT_ACTION_DELAY};
+use crate::engine::mechanics::{Field, Stone, StoneKind, Mode, NextQueue, PieceGenerator, Setup};
+use crate::config::settings::Action;
+use crate::net::network::Snapshot;
+use crate::rendering::renderer::{Position, Canvas};
+use crate::stats::statistics::{SessionRecord, SessionStats};
+use crate::views::clipboard::copy_fumen;
+use crate::views::play_view::stats_panel::render_stats_at;
+use crate::views::pause_view::pause_view::PauseView;
+use crate::views::player::Player;
+use crate::views::view::{Context, Transition, View};
+use crossterm::event::Event;
+use std::time::Instant;
 
 pub struct PlayView {
-    field: field::Field,
-    next_stone: Texture,
-    current_stone: Stone,
-    time_per_tick: Duration,
-    time_until_next_tick: Duration,
-    points: u64,
-    cleared_lines: u64,
-    level: u8,
-    minimal_tick_time: Duration,
-    tick_generator: Option<JoinHandle<()>>
+    mode: Mode,
+    player: Player,
+    session_recorded: bool,
+    message: Option<String>,
+    demo: bool,
+    bot: Option<BotChoice>,
+    /// The edited position the game started from, restarts go back to it.
+    setup: Option<Setup>,
 }
 
-impl Default for PlayView {
-    fn default() -> Self {
-        let first_block_texture = Stone::new_i();
-        let first_block_position = Self::get_spawn_position(&first_block_texture.dimensions);
+impl PlayView {
+    pub fn new(mode: Mode, now: Instant) -> Self {
+        Self::with_generator(mode, PieceGenerator::random(), now)
+    }
 
+    pub fn with_generator(mode: Mode, generator: PieceGenerator, now: Instant) -> Self {
         Self {
-            field: field::Field::default(),
-            next_stone: Stone::new_random_texture(),
-            current_stone: Stone::new(first_block_position, first_block_texture),
-            time_until_next_tick: Duration::from_millis(1000),
-            time_per_tick: Duration::from_millis(1000),
-            points: 0,
-            level: 1,
-            cleared_lines: 0,
-            minimal_tick_time: Duration::from_millis(30),
-            tick_generator: None
+            mode,
+            player: Player::new(mode.layout(), generator, now),
+            session_recorded: false,
+            message: None,
+            demo: false,
+            bot: None,
+            setup: None,
         }
     }
-}
 
-impl PlayView {
-    pub fn render_at(&self, canvas: &mut Canvas, position: Position) {
-        self.field.render_at(canvas, position);
-        canvas.add_texture(self.current_stone.texture.clone(), &self.current_stone.position);
-        canvas.add_texture(self.next_stone.clone(), &Position { x: 10, y: 0 });
-        canvas.add_themed_text(format!("level: {}", self.level).as_str(), &Position { x: 10, y: 6 });
-        canvas.add_themed_text(format!("points: {}", self.points).as_str(), &Position { x: 10, y: 7 });
-        canvas.add_themed_paragraph(vec!["q - quit", "esq - menu", "arrows - move block"].as_slice(), Position { x: 0, y: 21 });
+    /// A marathon game from an edited position, its pieces are dealt before the first bag.
+    pub fn from_setup(setup: Setup, now: Instant) -> Self {
+        let generator = PieceGenerator::random().with_preset(&setup.queue);
+        let view = Self::with_generator(Mode::Marathon, generator, now);
+        Self {
+            player: view.player.with_field(setup.field.clone(), setup.hold),
+            setup: Some(setup),
+            ..view
+        }
     }
 
-    fn spawn_next_stone(&mut self) {
-        self.field.add_to_texture(self.current_stone.texture.clone(), self.current_stone.position);
-
-        let new_stone = Stone::new(
-            Self::get_spawn_position(&self.next_stone.dimensions),
-            std::mem::replace(&mut self.next_stone, Stone::new_random_texture()),
-        );
-        self.current_stone = new_stone;
+    /// A game played by the built-in bot, the target of every piece is drawn on the field.
+    pub fn watch_bot(mode: Mode, weights: Weights, now: Instant) -> Self {
+        Self::watching(mode, BotChoice::Builtin(weights), now)
     }
 
-    fn get_spawn_position(dimensions: &Dimensions) -> Position {
-        Position {
-            x: 5 - (dimensions.width as i8) / 2,
-            y: -(dimensions.height as i8) + 1,
+    /// A game played by the chosen bot, restarts start an external bot anew.
+    pub fn watching(mode: Mode, bot: BotChoice, now: Instant) -> Self {
+        let view = Self::new(mode, now);
+        Self {
+            player: view.player.with_bot(bot.driver(DEFAULT_ACTION_DELAY, now)),
+            bot: Some(bot),
+            ..view
         }
     }
 
-    pub fn handle_input(&mut self, event: &crossterm::event::Event) {
-        use crossterm::event::{Event, KeyEvent, KeyCode};
-        match event {
-            Event::Key(KeyEvent { code, modifiers: _ }) => {
-                match code {
-                    KeyCode::Left => self.current_stone.move_left(&self.field),
-                    KeyCode::Right => self.current_stone.move_right(&self.field),
-                    KeyCode::Down => self.current_stone.move_down(&self.field),
-                    KeyCode::Up => self.current_stone.rotate(&self.field),
-                    KeyCode::Char(' ') => {
-                        self.spawn_next_stone();
-                        false
-                    }
-                    _ => false
-                }
-            }
-            _ => false
-        };
+    /// The attract mode of the main menu, the bot plays until a key is pressed.
+    pub fn demo(mode: Mode, weights: Weights, now: Instant) -> Self {
+        Self {
+            demo: true,
+            ..Self::watch_bot(mode, weights, now)
+        }
     }
 
-    fn update_score(&mut self, deleted_lines: usize) {
-        if deleted_lines == 0 {
-            return;
+    fn restart(mode: Mode, bot: Option<BotChoice>, setup: Option<Setup>, now: Instant) -> Self {
+        match (bot, setup) {
+            (Some(bot), _) => Self::watching(mode, bot, now),
+            (None, Some(setup)) => Self::from_setup(setup, now),
+            (None, None) => Self::new(mode, now),
         }
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn field(&self) -> &Field {
+        self.player.field()
+    }
+
+    pub fn current_stone(&self) -> &Stone {
+        self.player.current_stone()
+    }
+
+    pub fn held_stone(&self) -> Option<StoneKind> {
+        self.player.held_stone()
+    }
 
-        self.cleared_lines += deleted_lines as u64;
-        let action_score = 2u8.pow(deleted_lines as u32) as u64 * self.level as u64;
-        self.points += action_score;
-        self.level = 1u8 + (self.cleared_lines / 10) as u8;
+    pub fn stats(&self) -> &SessionStats {
+        self.player.stats()
     }
 
-    fn cancel_tick_generator(&mut self) {
-        //TODO implement cancellation of tick generator, dropping JoinHandle doesn't work
-        self.tick_generator = None;
+    /// The upcoming pieces, including the ones the preview doesn't show.
+    pub fn next_queue(&self) -> &NextQueue {
+        self.player.next_queue()
     }
 
-    fn stop_game(&mut self) {
-        self.cancel_tick_generator();
+    pub fn bot(&self) -> Option<&BotDriver> {
+        self.player.bot()
     }
 
-    fn progress_game(&mut self) {
-        if !self.current_stone.move_down(&self.field) {
-            if self.current_stone.position.y < 0 {
-                self.stop_game();
-            } else {
-                self.spawn_next_stone();
-                let deleted_lines = self.field.try_delete_lines();
-                self.update_score(deleted_lines);
+    fn record_session(&mut self, context: &mut Context) {
+        let stats = self.player.stats();
+        if self.session_recorded || stats.pieces == 0 || self.player.is_bot() || self.setup.is_some() {
+            return;
+        }
+        self.session_recorded = true;
+
+        if let Some(history) = &mut context.stats {
+            history.record(SessionRecord::new(self.mode, self.player.points(), stats));
+            if let Err(error) = history.save() {
+                self.message = Some(error.to_string());
             }
         }
     }
+}
 
-    async fn wait_for_next_tick(game_state: Arc<Mutex<Game>>) {
-        let time_until_next_tick = async {
-            let game = game_state.lock().await;
-            game.current_view.time_until_next_tick
-        }.await;
+impl View for PlayView {
+    fn render_at(&self, canvas: &mut Canvas, position: Position, context: &Context) {
+        let dimensions = *self.player.field().dimensions();
+        let status = self.player.render_at(canvas, position, context);
+        let stats = self.player.stats();
 
-        delay_for(time_until_next_tick).await;
+        if self.player.is_game_over() {
+            canvas.add_highlighted_text("game over", &status);
+            let summary = format!("{} pieces, {} finesse faults", stats.pieces, stats.finesse_faults);
+            canvas.add_themed_text(&summary, &status.offset_by(&Position { x: 0, y: 1 }));
+        }
+        if self.demo {
+            canvas.add_highlighted_text("AI demo - press any key", &status);
+        } else if let Some(bot) = self.player.bot().filter(|_| !self.player.is_game_over()) {
+            canvas.add_themed_text(&format!("watching {}", bot.external_name().unwrap_or("the bot")), &status);
+        }
+        let bot_error = self.player.bot().and_then(BotDriver::error).map(|error| format!("{}, the built-in bot took over", error));
+        if let Some(message) = self.message.as_ref().or(bot_error.as_ref()) {
+            canvas.add_highlighted_text(message, &status.offset_by(&Position { x: 0, y: 2 }));
+        }
+        render_stats_at(canvas, stats, position.offset_by(&Position { x: dimensions.width as i32 + 19, y: 0 }));
+
+        let help = context.config.keys.help();
+        let help: Vec<&str> = help.iter().map(String::as_str).collect();
+        canvas.add_themed_paragraph(help.as_slice(), position.offset_by(&Position { x: 0, y: dimensions.height as i32 + 1 }));
     }
 
-    async fn handle_tick(game_state: Arc<Mutex<Game>>) {
-        let mut game = game_state.lock().await;
-        let play_view = &mut game.current_view;
-        play_view.progress_game();
-        let proposed_tick_time = Duration::from_millis((1000.0 * 0.75f32.powi(play_view.level as i32)) as u64);
-        play_view.time_per_tick = std::cmp::max(proposed_tick_time, play_view.minimal_tick_time);
-        play_view.time_until_next_tick = play_view.time_per_tick;
+    fn handle_input(&mut self, event: &Event, now: Instant, context: &mut Context) -> Transition {
+        if self.demo {
+            return match event {
+                Event::Key(_) => Transition::Pop,
+                _ => Transition::None,
+            };
+        }
+
+        let action = match event {
+            Event::Key(key_event) => context.config.keys.action_for(key_event),
+            _ => None,
+        };
+
+        match action {
+            Some(Action::Quit) => return Transition::Quit,
+            Some(Action::Pause) => {
+                let (mode, bot, setup) = (self.mode, self.bot.clone(), self.setup.clone());
+                return Transition::Push(Box::new(PauseView::new(Box::new(move |now| Box::new(PlayView::restart(mode, bot.clone(), setup.clone(), now))))));
+            }
+            Some(Action::Restart) => return Transition::Replace(Box::new(PlayView::restart(self.mode, self.bot.clone(), self.setup.clone(), now))),
+            Some(Action::CopyFumen) => self.message = Some(copy_fumen(self.player.fumen_page())),
+            Some(action) => {
+                self.player.perform(action, now, &context.config.handling);
+            }
+            None => {}
+        }
+
+        Transition::None
     }
 
-    pub async fn generate_ticks(game_state: Arc<Mutex<Game>>) {
-        loop {
-            Self::wait_for_next_tick(game_state.clone()).await;
-            Self::handle_tick(game_state.clone()).await;
+    fn update(&mut self, now: Instant, context: &mut Context) -> Transition {
+        self.player.update(now, &context.config.handling);
+        if self.player.is_game_over() && self.demo {
+            return Transition::Replace(Box::new(PlayView::demo(self.mode, context.config.bot_weights.clone(), now)));
+        }
+        if self.player.is_game_over() {
+            self.record_session(context);
         }
+        Transition::None
     }
 
-    pub fn on_create(&mut self, game_handle: Arc<Mutex<Game>>) {
-        self.tick_generator = Some(tokio::spawn(Self::generate_ticks(game_handle)));
+    fn on_resume(&mut self, now: Instant, _context: &Context) {
+        self.player.resume(now);
     }
-}
\ No newline at end of file
+
+    fn snapshot(&self) -> Option<Snapshot> {
+        Some(self.player.snapshot())
+    }
+
+    fn on_exit(&mut self, context: &mut Context) {
+        self.record_session(context);
+    }
+}
@@ -0,0 +1,588 @@
+use crate::bot::ai::{BotDriver, Situation};
+use crate::config::settings::{Action, Handling, MAX_NEXT_QUEUE_LENGTH};
+use crate::net::network::{PieceState, Snapshot};
+use crate::engine::mechanics::{quiz_comment, t_spin, AttackTable, BoardLayout, Clear, FinesseResult, FinesseTracker, Field, FumenPage, GarbageHoles, GarbageQueue, HoleSwitch, NextQueue, PieceGenerator, Score, Stone, StoneKind};
+use crate::rendering::renderer::{Canvas, Dimensions, Position, Texture};
+use crate::stats::statistics::SessionStats;
+use crate::views::handling::{AutoShift, KeyHold, Shift};
+use crate::views::view::Context;
+use std::time::{Duration, Instant};
+
+/// Chance that the next attack gets a hole in another column.
+pub const DEFAULT_HOLE_CHANGE: f64 = 0.7;
+
+/// The state of one player's game: the field, the pieces, gravity, scoring and the garbage
+/// exchanged with opponents. Views decide where the actions come from.
+pub struct Player {
+    field: Field,
+    next_queue: NextQueue,
+    current_stone: Stone,
+    held_stone: Option<StoneKind>,
+    hold_used: bool,
+    time_per_tick: Duration,
+    last_fall: Instant,
+    score: Score,
+    minimal_tick_time: Duration,
+    auto_shift: AutoShift,
+    soft_drop: Option<KeyHold>,
+    finesse: FinesseTracker,
+    rotated_last: bool,
+    attack_table: AttackTable,
+    garbage: GarbageQueue,
+    outgoing: u32,
+    holes: GarbageHoles,
+    stats: SessionStats,
+    last_update: Instant,
+    game_over: bool,
+    piece: u64,
+    bot: Option<BotDriver>,
+    gravity: bool,
+    /// How many more pieces the queue deals, `None` for endless bags.
+    queue_left: Option<usize>,
+    last_clear: Option<Clear>,
+}
+
+impl Player {
+    pub fn new(layout: BoardLayout, generator: PieceGenerator, now: Instant) -> Self {
+        let field = Field::new(layout);
+        let holes = GarbageHoles::new(HoleSwitch::PerAttack, DEFAULT_HOLE_CHANGE, generator.seed());
+        let mut next_queue = NextQueue::new(generator, MAX_NEXT_QUEUE_LENGTH);
+        let first_kind = next_queue.pop();
+        let first_block_position = field.spawn_position(first_kind);
+        let first_stone = Stone::new(first_block_position, first_kind);
+
+        Self {
+            field,
+            next_queue,
+            current_stone: first_stone,
+            held_stone: None,
+            hold_used: false,
+            time_per_tick: Duration::from_millis(1000),
+            last_fall: now,
+            score: Score::default(),
+            minimal_tick_time: Duration::from_millis(30),
+            auto_shift: AutoShift::default(),
+            soft_drop: None,
+            finesse: FinesseTracker::new(first_stone),
+            rotated_last: false,
+            attack_table: AttackTable::default(),
+            garbage: GarbageQueue::default(),
+            outgoing: 0,
+            holes,
+            stats: SessionStats::default(),
+            last_update: now,
+            game_over: false,
+            piece: 0,
+            bot: None,
+            gravity: true,
+            queue_left: None,
+            last_clear: None,
+        }
+    }
+
+    /// Lets the bot play instead of the keyboard.
+    pub fn with_bot(self, bot: BotDriver) -> Self {
+        Self { bot: Some(bot), ..self }
+    }
+
+    /// Starts from the field of an edited position with the piece in hold.
+    pub fn with_field(self, field: Field, held_stone: Option<StoneKind>) -> Self {
+        let current_stone = Stone::new(field.spawn_position(self.current_stone.kind), self.current_stone.kind);
+        Self { field, held_stone, current_stone, finesse: FinesseTracker::new(current_stone), ..self }
+    }
+
+    /// Pieces only move down when dropped and only lock on a hard drop.
+    pub fn without_gravity(self) -> Self {
+        Self { gravity: false, ..self }
+    }
+
+    /// Deals only the given number of pieces, including the falling one. Once they're used up the
+    /// held piece comes last, then the game ends.
+    pub fn with_piece_limit(self, pieces: usize) -> Self {
+        Self { queue_left: Some(pieces.saturating_sub(1)), ..self }
+    }
+
+    /// Changes where the holes of received garbage go.
+    pub fn with_garbage_holes(self, holes: GarbageHoles) -> Self {
+        Self { holes, ..self }
+    }
+
+    pub fn field(&self) -> &Field {
+        &self.field
+    }
+
+    pub fn current_stone(&self) -> &Stone {
+        &self.current_stone
+    }
+
+    pub fn held_stone(&self) -> Option<StoneKind> {
+        self.held_stone
+    }
+
+    pub fn stats(&self) -> &SessionStats {
+        &self.stats
+    }
+
+    /// The upcoming pieces, including the ones the preview doesn't show.
+    pub fn next_queue(&self) -> &NextQueue {
+        &self.next_queue
+    }
+
+    /// The field with a quiz comment for the held, falling and upcoming pieces.
+    pub fn fumen_page(&self) -> FumenPage {
+        let mut pieces = vec![self.current_stone.kind];
+        pieces.extend(self.next_queue.pieces());
+        let mut page = FumenPage::new(self.field.clone(), None);
+        page.comment = quiz_comment(self.held_stone, &pieces);
+        page
+    }
+
+    pub fn points(&self) -> u64 {
+        self.score.points
+    }
+
+    pub fn level(&self) -> u8 {
+        self.score.level
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.game_over
+    }
+
+    pub fn is_bot(&self) -> bool {
+        self.bot.is_some()
+    }
+
+    pub fn bot(&self) -> Option<&BotDriver> {
+        self.bot.as_ref()
+    }
+
+    /// What locking the last piece did.
+    pub fn last_clear(&self) -> Option<Clear> {
+        self.last_clear
+    }
+
+    pub fn pending_garbage(&self) -> u32 {
+        self.garbage.pending()
+    }
+
+    /// Garbage this player sent since the last call, after cancelling its own incoming garbage.
+    pub fn take_attack(&mut self) -> u32 {
+        std::mem::take(&mut self.outgoing)
+    }
+
+    /// Queues garbage, it enters the field once a piece locks without clearing lines.
+    pub fn receive_garbage(&mut self, lines: u32) {
+        self.garbage.receive(lines);
+    }
+
+    pub fn stop(&mut self) {
+        self.game_over = true;
+    }
+
+    fn lock_current_stone(&mut self) {
+        if self.field.is_locked_out(&self.current_stone) {
+            self.stop();
+            return;
+        }
+
+        let finesse = self.finesse.evaluate(&self.field, &self.current_stone);
+        if finesse.as_ref().is_some_and(FinesseResult::is_fault) {
+            self.stats.record_finesse_fault();
+        }
+
+        let t_spin = t_spin(&self.field, &self.current_stone, self.rotated_last);
+        self.field.add_stone(&self.current_stone);
+        let lines = self.field.try_delete_lines();
+        let clear = Clear { lines, t_spin, perfect_clear: lines > 0 && self.field.board().is_empty() };
+        self.last_clear = Some(clear);
+        let attack = self.attack_table.attack(&clear);
+        self.stats.record_piece(self.current_stone.kind, lines, attack);
+        self.outgoing += self.garbage.cancel(attack);
+        self.score.record(lines);
+        self.time_per_tick = self.score.fall_interval(self.minimal_tick_time);
+
+        if lines == 0 {
+            self.insert_pending_garbage();
+        }
+        if !self.game_over {
+            self.spawn_next_stone();
+        }
+        self.hold_used = false;
+    }
+
+    fn insert_pending_garbage(&mut self) {
+        for lines in self.garbage.take() {
+            let hole_columns = self.holes.attack(lines as usize, self.field.dimensions().width);
+            if !self.field.insert_garbage_rows(&hole_columns) {
+                self.stop();
+            }
+        }
+    }
+
+    /// Pushes garbage rows in right away, the falling piece is moved up if the stack rises into it.
+    pub fn insert_garbage(&mut self, rows: usize) {
+        let hole_columns = self.holes.attack(rows, self.field.dimensions().width);
+        let fits = self.field.insert_garbage_rows(&hole_columns);
+        if !fits || !self.current_stone.push_up(&self.field) {
+            self.stop();
+        }
+    }
+
+    fn spawn_next_stone(&mut self) {
+        match &mut self.queue_left {
+            Some(0) => {
+                match self.held_stone.take() {
+                    Some(held_kind) => self.spawn_stone(held_kind),
+                    None => self.stop(),
+                }
+                return;
+            }
+            Some(left) => *left -= 1,
+            None => {}
+        }
+        let next_kind = self.next_queue.pop();
+        self.spawn_stone(next_kind);
+    }
+
+    fn spawn_stone(&mut self, kind: StoneKind) {
+        self.current_stone = Stone::new(self.field.spawn_position(kind), kind);
+        self.finesse.reset(self.current_stone);
+        self.rotated_last = false;
+        self.piece += 1;
+
+        if !self.field.can_spawn(&self.current_stone) {
+            self.stop();
+        }
+    }
+
+    fn hold(&mut self) {
+        if self.hold_used || (self.held_stone.is_none() && self.queue_left == Some(0)) {
+            return;
+        }
+
+        match self.held_stone.replace(self.current_stone.kind) {
+            Some(held_kind) => self.spawn_stone(held_kind),
+            None => self.spawn_next_stone(),
+        }
+        self.hold_used = true;
+    }
+
+    fn shift(&mut self, direction: Shift, times: usize) {
+        for _ in 0..times {
+            let moved = match direction {
+                Shift::Left => self.current_stone.move_left(&self.field),
+                Shift::Right => self.current_stone.move_right(&self.field),
+            };
+            if !moved {
+                break;
+            }
+            self.rotated_last = false;
+        }
+    }
+
+    fn rotate(&mut self, quarter_turns: u8) {
+        if self.current_stone.rotate_by(&self.field, quarter_turns) {
+            self.rotated_last = true;
+        }
+    }
+
+    /// Applies a key press of the player. Returns whether it was a press of its own rather than
+    /// the terminal repeating a held key, bots and finished games ignore the keyboard.
+    pub fn perform(&mut self, action: Action, now: Instant, handling: &Handling) -> bool {
+        if self.game_over || self.is_bot() {
+            return false;
+        }
+
+        let pressed = match action {
+            Action::MoveLeft => self.auto_shift.press(Shift::Left, now, handling),
+            Action::MoveRight => self.auto_shift.press(Shift::Right, now, handling),
+            Action::SoftDrop => KeyHold::press(&mut self.soft_drop, now),
+            _ => true,
+        };
+        if pressed {
+            self.apply_action(action, now);
+            self.stats.record_key();
+        }
+        pressed
+    }
+
+    /// Applies the action once, the way a single key press does.
+    fn apply_action(&mut self, action: Action, now: Instant) {
+        match action {
+            Action::MoveLeft => self.shift(Shift::Left, 1),
+            Action::MoveRight => self.shift(Shift::Right, 1),
+            Action::SoftDrop => {
+                self.finesse.record_soft_drop();
+                while self.current_stone.move_down(&self.field) {
+                    self.last_fall = now;
+                    self.rotated_last = false;
+                    if self.gravity {
+                        break;
+                    }
+                }
+            }
+            Action::HardDrop => self.hard_drop(now),
+            Action::RotateCW => self.rotate(1),
+            Action::RotateCCW => self.rotate(3),
+            Action::Rotate180 => self.rotate(2),
+            Action::Hold => self.hold(),
+            Action::Pause | Action::Restart | Action::CopyFumen | Action::Quit => {}
+        }
+
+        if matches!(action, Action::MoveLeft | Action::MoveRight | Action::RotateCW | Action::RotateCCW | Action::Rotate180) {
+            self.finesse.record_input();
+        }
+    }
+
+    fn bot_action(&mut self, now: Instant) -> Option<Action> {
+        self.bot.as_ref()?;
+        let situation = Situation {
+            field: &self.field,
+            current: &self.current_stone,
+            piece: self.piece,
+            hold: self.held_stone.or_else(|| Some(self.next_queue.peek())),
+            can_hold: !self.hold_used,
+            held: self.held_stone,
+            queue: self.next_queue.pieces(),
+            combo: self.attack_table.combo().map_or(0, |combo| combo as u32 + 1),
+            back_to_back: self.attack_table.back_to_back(),
+        };
+        self.bot.as_mut()?.next_action(now, &situation)
+    }
+
+    fn hard_drop(&mut self, now: Instant) {
+        while self.current_stone.move_down(&self.field) {
+            self.rotated_last = false;
+        }
+        self.lock_current_stone();
+        self.last_fall = now;
+        self.soft_drop = None;
+    }
+
+    fn fall_interval(&self, now: Instant, sdf: u32) -> Duration {
+        match self.soft_drop {
+            Some(hold) if !hold.is_released(now) && sdf == 0 => Duration::from_millis(0),
+            Some(hold) if !hold.is_released(now) => self.time_per_tick / sdf,
+            _ => self.time_per_tick,
+        }
+    }
+
+    /// Advances the clock, auto shift, the bot and gravity.
+    pub fn update(&mut self, now: Instant, handling: &Handling) {
+        if self.game_over {
+            return;
+        }
+        self.stats.elapsed += now.saturating_duration_since(self.last_update);
+        self.last_update = now;
+
+        if let Some((direction, times)) = self.auto_shift.update(now, handling) {
+            self.shift(direction, times);
+        }
+
+        if let Some(action) = self.bot_action(now) {
+            self.apply_action(action, now);
+            self.stats.record_key();
+            if self.game_over {
+                return;
+            }
+        }
+
+        if !self.gravity {
+            return;
+        }
+        let elapsed = now.saturating_duration_since(self.last_fall);
+        let fall_interval = self.fall_interval(now, handling.sdf);
+        if elapsed < fall_interval {
+            return;
+        }
+
+        let falls = if fall_interval.as_nanos() == 0 { usize::MAX } else { (elapsed.as_nanos() / fall_interval.as_nanos()) as usize };
+        let mut fell = false;
+        for _ in 0..falls {
+            if !self.current_stone.move_down(&self.field) {
+                break;
+            }
+            fell = true;
+            self.rotated_last = false;
+        }
+
+        if fell {
+            self.last_fall = now;
+        } else if elapsed >= self.time_per_tick {
+            self.lock_current_stone();
+            self.last_fall = now;
+        }
+    }
+
+    /// The visible state of the game for opponents and spectators on other machines.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            rows: Snapshot::rows_of(&self.field),
+            piece: if self.game_over { None } else { Some(PieceState::of(&self.current_stone, &self.field)) },
+            hold: self.held_stone,
+            next: self.next_queue.pieces(),
+            points: self.score.points,
+            level: self.score.level,
+            lines: self.score.lines,
+            pending_garbage: self.garbage.pending(),
+            game_over: self.game_over,
+        }
+    }
+
+    /// Restarts the clocks after the game was paused, keys held before are forgotten.
+    pub fn resume(&mut self, now: Instant) {
+        self.last_fall = now;
+        self.last_update = now;
+        self.auto_shift.release();
+        self.soft_drop = None;
+    }
+
+    /// Draws the well with the pending garbage on its left wall and the sidebar with hold, next,
+    /// level and points. Returns the position below the sidebar for further status lines.
+    pub fn render_at(&self, canvas: &mut Canvas, position: Position, context: &Context) -> Position {
+        let dimensions = *self.field.dimensions();
+        let field_position = position.offset_by(&Position { x: 1, y: 0 });
+        let stone_offset = field_position.offset_by(&self.field.visible_offset());
+        let sidebar = position.offset_by(&Position { x: dimensions.width as i32 + 3, y: 0 });
+        let queue_length = context.config.next_queue_length;
+
+        canvas.add_well(&position, &dimensions);
+        render_garbage_meter(canvas, &position, &dimensions, self.garbage.pending());
+        self.field.render_at(canvas, field_position);
+        if context.config.ghost {
+            self.current_stone.render_ghost_at(canvas, &stone_offset, &self.field);
+        }
+        if let Some(target) = self.bot.as_ref().and_then(BotDriver::target) {
+            let tile = canvas.theme().target_tile(target.kind.color(canvas.theme()));
+            canvas.add_texture(target.texture(tile), &target.position.offset_by(&stone_offset));
+        }
+        self.current_stone.render_at(canvas, &stone_offset);
+
+        let preview = self.next_queue.preview(queue_length).take(self.queue_left.unwrap_or(queue_length));
+        render_sidebar(canvas, &sidebar, self.held_stone, preview, queue_length, self.score.level, self.score.points)
+    }
+}
+
+pub fn render_preview(canvas: &mut Canvas, kind: StoneKind, position: &Position) {
+    let stone = Stone::new(Position { x: 0, y: -kind.shape(0).min_y }, kind);
+    stone.render_at(canvas, position);
+}
+
+/// Fills the left wall of the well from the bottom, one block per pending garbage row.
+fn render_garbage_meter(canvas: &mut Canvas, well: &Position, dimensions: &Dimensions, pending: u32) {
+    let pending = (pending as usize).min(dimensions.height);
+    if pending == 0 {
+        return;
+    }
+
+    let mut texture = Texture::new(Dimensions { width: 1, height: pending });
+    let tile = canvas.theme().block_tile(canvas.theme().highlight);
+    texture.pixels.iter_mut().for_each(|row| row[0] = Some(tile));
+    canvas.add_texture(texture, &well.offset_by(&Position { x: 0, y: (dimensions.height - pending) as i32 }));
+}
+
+/// Draws hold, the next pieces, level and points. Returns the position below them.
+fn render_sidebar(canvas: &mut Canvas, sidebar: &Position, hold: Option<StoneKind>, next: impl Iterator<Item=StoneKind>, queue_length: usize, level: u8, points: u64) -> Position {
+    canvas.add_themed_text("hold", sidebar);
+    if let Some(kind) = hold {
+        render_preview(canvas, kind, &sidebar.offset_by(&Position { x: 1, y: 1 }));
+    }
+
+    let mut preview = next.take(queue_length);
+    if let Some(first) = preview.next() {
+        canvas.add_highlighted_text("next", &sidebar.offset_by(&Position { x: 0, y: 4 }));
+        canvas.add_frame(&sidebar.offset_by(&Position { x: 0, y: 5 }), &Dimensions { width: 4, height: 2 });
+        render_preview(canvas, first, &sidebar.offset_by(&Position { x: 1, y: 6 }));
+    }
+    for (index, kind) in preview.enumerate() {
+        render_preview(canvas, kind, &sidebar.offset_by(&Position { x: 1, y: 9 + 3 * index as i32 }));
+    }
+
+    let preview_height = if queue_length == 0 { 0 } else { 3 * queue_length as i32 + 2 };
+    let status = sidebar.offset_by(&Position { x: 0, y: 5 + preview_height });
+    canvas.add_themed_text(format!("level: {}", level).as_str(), &status);
+    canvas.add_themed_text(format!("points: {}", points).as_str(), &status.offset_by(&Position { x: 0, y: 1 }));
+    status.offset_by(&Position { x: 0, y: 3 })
+}
+
+/// Draws a game received from another machine the way `Player::render_at` draws a local one.
+/// Returns the position below the sidebar.
+pub fn render_snapshot(canvas: &mut Canvas, snapshot: &Snapshot, position: Position, context: &Context) -> Position {
+    let field = match snapshot.field() {
+        Ok(field) => field,
+        Err(error) => {
+            canvas.add_highlighted_text(&error, &position);
+            return position.offset_by(&Position { x: 0, y: 1 });
+        }
+    };
+    let dimensions = *field.dimensions();
+    let field_position = position.offset_by(&Position { x: 1, y: 0 });
+    let sidebar = position.offset_by(&Position { x: dimensions.width as i32 + 3, y: 0 });
+    let queue_length = context.config.next_queue_length;
+
+    canvas.add_well(&position, &dimensions);
+    render_garbage_meter(canvas, &position, &dimensions, snapshot.pending_garbage);
+    field.render_at(canvas, field_position);
+    if let Some(stone) = snapshot.piece.and_then(|piece| piece.placed(&field)) {
+        if context.config.ghost {
+            stone.render_ghost_at(canvas, &field_position, &field);
+        }
+        stone.render_at(canvas, &field_position);
+    }
+
+    render_sidebar(canvas, &sidebar, snapshot.hold, snapshot.next.iter().copied(), queue_length, snapshot.level, snapshot.points)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::settings::{Action, Config, Handling};
+    use crate::engine::mechanics::{Mode, PieceGenerator, StoneKind};
+    use crate::net::network::{PieceState, Snapshot};
+    use crate::rendering::renderer::{Canvas, Dimensions, Position};
+    use crate::views::player::{render_snapshot, Player};
+    use crate::views::view::Context;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn garbage_enters_after_a_piece_without_clears() {
+        let now = Instant::now();
+        let handling = Handling::default();
+        let mut player = Player::new(Mode::Marathon.layout(), PieceGenerator::new(7), now);
+
+        player.receive_garbage(3);
+        assert_eq!(player.pending_garbage(), 3);
+        assert!(player.perform(Action::HardDrop, now, &handling));
+        assert_eq!(player.pending_garbage(), 0);
+
+        let bottom = player.field().total_height() as i32 - 1;
+        let garbage_cells = (0..10).filter(|&x| !player.field().board().is_occupied(x, bottom)).count();
+        assert_eq!(garbage_cells, 1);
+        assert_eq!(player.take_attack(), 0);
+        assert_eq!(player.stats().pieces, 1);
+    }
+
+    #[test]
+    fn rising_garbage_lifts_the_piece() {
+        let mut player = Player::new(Mode::Marathon.layout(), PieceGenerator::new(7), Instant::now());
+        while player.current_stone.move_down(&player.field) {}
+        let landed = player.current_stone().position;
+
+        player.insert_garbage(2);
+        assert_eq!(player.current_stone().position.y, landed.y - 2);
+        assert!(!player.is_game_over());
+    }
+
+    #[test]
+    fn hostile_snapshots_render_quickly() {
+        let context = Context::new(Config::default(), Vec::new(), None);
+        let mut canvas = Canvas::new(Dimensions { width: 80, height: 30 });
+        let started = Instant::now();
+        for &(x, y) in [(3, -1_500_000_000), (i32::MAX, i32::MAX), (i32::MIN, i32::MIN), (3, -1)].iter() {
+            let piece = PieceState { kind: StoneKind::T, x, y, rotation: 0 };
+            let snapshot = Snapshot { rows: vec![".".repeat(10); 20], piece: Some(piece), hold: None, next: vec![], points: 0, level: 1, lines: 0, pending_garbage: 0, game_over: false };
+            render_snapshot(&mut canvas, &snapshot, Position::default(), &context);
+        }
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,7 @@
+mod select;
+mod view;
+
+pub mod puzzle_view {
+    pub use super::select::PuzzleSelectView;
+    pub use super::view::PuzzleView;
+}
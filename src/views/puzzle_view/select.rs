@@ -0,0 +1,105 @@
+use crate::config::settings::Action;
+use crate::puzzle::puzzles::{Campaign, Progress};
+use crate::rendering::renderer::{Canvas, Position};
+use crate::views::puzzle_view::puzzle_view::PuzzleView;
+use crate::views::view::{Context, Transition, View};
+use crossterm::event::{Event, KeyCode};
+use std::time::Instant;
+
+/// How many levels are listed at once.
+const LIST_HEIGHT: usize = 16;
+
+/// Lists the levels of a campaign with a mark for the solved ones, the chosen level is played on
+/// top of it.
+pub struct PuzzleSelectView {
+    campaign: Campaign,
+    progress: Progress,
+    selected: usize,
+    message: Option<String>,
+}
+
+impl PuzzleSelectView {
+    pub fn new(campaign: Campaign) -> Self {
+        let mut view = Self { campaign, progress: Progress::default(), selected: 0, message: None };
+        view.reload_progress();
+        view.selected = view.campaign.levels().iter().position(|level| !view.progress.level(&level.name).completed).unwrap_or(0);
+        view
+    }
+
+    /// The built-in levels and the ones in the puzzles directory, broken files are reported.
+    pub fn load() -> Self {
+        let (campaign, errors) = Campaign::load();
+        let mut view = Self::new(campaign);
+        if let Some(error) = errors.first() {
+            view.message = Some(match errors.len() {
+                1 => error.clone(),
+                count => format!("{} and {} more", error, count - 1),
+            });
+        }
+        view
+    }
+
+    fn reload_progress(&mut self) {
+        match Progress::load() {
+            Ok(progress) => self.progress = progress,
+            Err(error) => self.message = Some(error),
+        }
+    }
+}
+
+impl View for PuzzleSelectView {
+    fn render_at(&self, canvas: &mut Canvas, position: Position, _context: &Context) {
+        let levels = self.campaign.levels();
+        let solved = self.progress.completed(levels.iter().map(|level| level.name.as_str()));
+        canvas.add_highlighted_text(&format!("puzzles - {}/{} solved", solved, levels.len()), &position);
+
+        let first = self.selected.saturating_sub(LIST_HEIGHT / 2).min(levels.len().saturating_sub(LIST_HEIGHT));
+        let mut line = position.offset_by(&Position { x: 0, y: 2 });
+        for (index, level) in levels.iter().enumerate().skip(first).take(LIST_HEIGHT) {
+            let progress = self.progress.level(&level.name);
+            let mark = if progress.completed { "[x]" } else { "[ ]" };
+            let best = progress.best_pieces.map(|pieces| format!("best {} pieces", pieces)).unwrap_or_default();
+            let text = format!("{} {:>2}. {:<24} {:<24} {}", mark, index + 1, level.name, level.goal.describe(), best);
+            if index == self.selected {
+                canvas.add_highlighted_text(&text, &line);
+            } else {
+                canvas.add_themed_text(&text, &line);
+            }
+            line.move_down();
+        }
+
+        line.move_down();
+        if let Some(level) = levels.get(self.selected).filter(|level| !level.description.is_empty()) {
+            canvas.add_themed_text(&level.description, &line);
+        }
+        line.move_down();
+        if let Some(message) = &self.message {
+            canvas.add_highlighted_text(message, &line);
+        }
+        line.move_down();
+        line.move_down();
+        canvas.add_themed_paragraph(&["up/down - select, enter - play, esc - back"], line);
+    }
+
+    fn handle_input(&mut self, event: &Event, now: Instant, context: &mut Context) -> Transition {
+        let key_event = match event {
+            Event::Key(key_event) => key_event,
+            _ => return Transition::None,
+        };
+        let count = self.campaign.levels().len();
+        match key_event.code {
+            KeyCode::Up => self.selected = (self.selected + count - 1) % count,
+            KeyCode::Down => self.selected = (self.selected + 1) % count,
+            KeyCode::Enter => return Transition::Push(Box::new(PuzzleView::new(self.campaign.clone(), self.selected, now))),
+            KeyCode::Esc => return Transition::Pop,
+            _ => if let Some(Action::Quit) = context.config.keys.action_for(key_event) {
+                return Transition::Quit;
+            },
+        }
+        Transition::None
+    }
+
+    fn on_resume(&mut self, _now: Instant, _context: &Context) {
+        self.reload_progress();
+    }
+}
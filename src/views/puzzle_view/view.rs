@@ -0,0 +1,215 @@
+use crate::config::settings::Action;
+use crate::engine::mechanics::{Mode, PieceGenerator};
+use crate::puzzle::puzzles::{Campaign, Level, Progress};
+use crate::rendering::renderer::{Canvas, Position};
+use crate::views::clipboard::copy_fumen;
+use crate::views::pause_view::pause_view::PauseView;
+use crate::views::player::Player;
+use crate::views::view::{Context, Transition, View};
+use crossterm::event::{Event, KeyCode};
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// Plays one level of a campaign without gravity: pieces stay where they are until they're hard
+/// dropped and only the level's pieces are dealt. The attempt ends once the goal is reached or
+/// can't be reached anymore, the result goes into the saved progress.
+pub struct PuzzleView {
+    campaign: Campaign,
+    level: usize,
+    player: Player,
+    placed: u64,
+    solved: Option<bool>,
+    message: Option<String>,
+    /// Where the result of the attempt is saved.
+    progress: Option<PathBuf>,
+}
+
+impl PuzzleView {
+    pub fn new(campaign: Campaign, level: usize, now: Instant) -> Self {
+        let setup = campaign.levels()[level].setup.clone();
+        let generator = PieceGenerator::random().with_preset(&setup.queue);
+        let player = Player::new(Mode::Marathon.layout(), generator, now)
+            .with_field(setup.field, setup.hold)
+            .without_gravity()
+            .with_piece_limit(setup.queue.len());
+        Self { campaign, level, player, placed: 0, solved: None, message: None, progress: Progress::path() }
+    }
+
+    fn level(&self) -> &Level {
+        &self.campaign.levels()[self.level]
+    }
+
+    /// Ends the attempt as soon as a lock reaches the goal or uses up the allowed pieces.
+    fn check(&mut self) {
+        if self.solved.is_some() {
+            return;
+        }
+        let stats = self.player.stats();
+        if stats.pieces != self.placed {
+            self.placed = stats.pieces;
+            let reached = self.player.last_clear().is_some_and(|clear| self.level().goal.is_reached(stats.lines, &clear));
+            if reached || self.level().max_pieces.is_some_and(|max| self.placed >= max as u64) {
+                self.finish(reached);
+                return;
+            }
+        }
+        if self.player.is_game_over() {
+            self.finish(false);
+        }
+    }
+
+    fn finish(&mut self, solved: bool) {
+        self.player.stop();
+        self.solved = Some(solved);
+        let name = self.level().name.clone();
+        let pieces = self.placed as u32;
+        if let Some(path) = &self.progress {
+            let saved = Progress::load_from(path).and_then(|mut progress| {
+                progress.record_attempt(&name);
+                if solved {
+                    progress.record_completion(&name, pieces);
+                }
+                progress.save_to(path)
+            });
+            self.message = saved.err();
+        }
+    }
+
+    fn next_level(&self) -> Option<usize> {
+        Some(self.level + 1).filter(|&next| next < self.campaign.levels().len())
+    }
+}
+
+impl View for PuzzleView {
+    fn render_at(&self, canvas: &mut Canvas, position: Position, context: &Context) {
+        let dimensions = *self.player.field().dimensions();
+        let status = self.player.render_at(canvas, position, context);
+        let level = self.level();
+
+        let pieces = match level.max_pieces {
+            Some(max) => format!("{}/{} pieces", self.placed, max),
+            None => format!("{} pieces", self.placed),
+        };
+        let lines = [
+            format!("{}/{} {}", self.level + 1, self.campaign.levels().len(), level.name),
+            level.goal.describe(),
+            pieces,
+        ];
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        canvas.add_themed_paragraph(&lines, status);
+        if !level.description.is_empty() {
+            canvas.add_themed_text(&level.description, &status.offset_by(&Position { x: 0, y: 4 }));
+        }
+        let result = match self.solved {
+            Some(true) if self.next_level().is_some() => Some("solved! enter - next level"),
+            Some(true) => Some("solved! that was the last level"),
+            Some(false) => Some("not solved, try again"),
+            None => None,
+        };
+        if let Some(result) = result {
+            canvas.add_highlighted_text(result, &status.offset_by(&Position { x: 0, y: 6 }));
+        }
+        if let Some(message) = &self.message {
+            canvas.add_highlighted_text(message, &status.offset_by(&Position { x: 0, y: 7 }));
+        }
+
+        let keys = &context.config.keys;
+        let help = [
+            "pieces don't fall on their own, soft drop moves the piece to the bottom".to_string(),
+            format!("{} - try again, {} - copy as fumen, {} - pause, tab - back to the levels",
+                    keys.describe(Action::Restart), keys.describe(Action::CopyFumen), keys.describe(Action::Pause)),
+        ];
+        let help: Vec<&str> = help.iter().map(String::as_str).collect();
+        canvas.add_themed_paragraph(&help, position.offset_by(&Position { x: 0, y: dimensions.height as i32 + 1 }));
+    }
+
+    fn handle_input(&mut self, event: &Event, now: Instant, context: &mut Context) -> Transition {
+        let key_event = match event {
+            Event::Key(key_event) => key_event,
+            _ => return Transition::None,
+        };
+        match (key_event.code, self.next_level()) {
+            (KeyCode::Enter, Some(next)) if self.solved == Some(true) => {
+                return Transition::Replace(Box::new(PuzzleView::new(self.campaign.clone(), next, now)));
+            }
+            (KeyCode::Tab, _) => return Transition::Pop,
+            _ => {}
+        }
+
+        match context.config.keys.action_for(key_event) {
+            Some(Action::Quit) => return Transition::Quit,
+            Some(Action::Pause) => {
+                let (campaign, level) = (self.campaign.clone(), self.level);
+                return Transition::Push(Box::new(PauseView::new(Box::new(move |now| Box::new(PuzzleView::new(campaign.clone(), level, now))))));
+            }
+            Some(Action::Restart) => return Transition::Replace(Box::new(PuzzleView::new(self.campaign.clone(), self.level, now))),
+            Some(Action::CopyFumen) => self.message = Some(copy_fumen(self.player.fumen_page())),
+            Some(action) => {
+                self.player.perform(action, now, &context.config.handling);
+                self.check();
+            }
+            None => {}
+        }
+        Transition::None
+    }
+
+    fn update(&mut self, now: Instant, context: &mut Context) -> Transition {
+        self.player.update(now, &context.config.handling);
+        self.check();
+        Transition::None
+    }
+
+    fn on_resume(&mut self, now: Instant, _context: &Context) {
+        self.player.resume(now);
+    }
+
+    fn on_exit(&mut self, _context: &mut Context) {
+        if self.solved.is_none() && self.placed > 0 {
+            self.finish(false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::settings::{Action, Handling};
+    use crate::puzzle::puzzles::{Campaign, Progress};
+    use crate::views::puzzle_view::puzzle_view::PuzzleView;
+    use std::time::{Duration, Instant};
+
+    fn play(view: &mut PuzzleView, actions: &[Action], now: Instant) {
+        for (index, &action) in actions.iter().enumerate() {
+            view.player.perform(action, now + Duration::from_secs(index as u64), &Handling::default());
+            view.check();
+        }
+    }
+
+    #[test]
+    fn levels_end_when_solved_or_out_of_pieces() {
+        let path = std::env::temp_dir().join(format!("tet-rs-puzzle-view-{}.toml", std::process::id()));
+        let now = Instant::now();
+        let campaign = Campaign::default();
+        let tetris = campaign.levels().iter().position(|level| level.name == "tetris").unwrap();
+
+        let mut view = PuzzleView::new(campaign.clone(), tetris, now);
+        view.progress = Some(path.clone());
+        view.player.update(now + Duration::from_secs(60), &Handling::default());
+        assert_eq!(view.player.stats().pieces, 0, "pieces don't fall without gravity");
+        play(&mut view, &[Action::HardDrop], now);
+        assert_eq!(view.solved, Some(false));
+
+        let mut view = PuzzleView::new(campaign.clone(), tetris, now);
+        view.progress = Some(path.clone());
+        let mut actions = vec![Action::RotateCW];
+        actions.extend(std::iter::repeat_n(Action::MoveRight, 9));
+        actions.push(Action::HardDrop);
+        play(&mut view, &actions, now);
+        assert_eq!(view.solved, Some(true));
+        assert_eq!(view.next_level(), Some(tetris + 1));
+
+        let progress = Progress::load_from(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let level = progress.level("tetris");
+        assert_eq!((level.attempts, level.completed, level.best_pieces), (2, true, Some(1)));
+    }
+}
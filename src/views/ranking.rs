@@ -0,0 +1,71 @@
+use crate::rendering::renderer::{Canvas, Position};
+use crate::stats::statistics::Leaderboard;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How many results of the leaderboard are shown next to the field.
+const SHOWN_RESULTS: usize = 5;
+
+/// Minutes, seconds and tenths, rounded to the tenth.
+pub fn format_time(time: Duration) -> String {
+    let tenths = (time.as_secs_f64() * 10.0).round() as u64;
+    format!("{}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
+
+/// The leaderboard of a game mode, the default one isn't saved anywhere.
+#[derive(Default)]
+pub struct Ranking {
+    pub leaderboard: Leaderboard,
+    path: Option<PathBuf>,
+    /// The place of the game once it's recorded, `None` if it didn't make the leaderboard.
+    pub place: Option<Option<usize>>,
+}
+
+impl Ranking {
+    /// Loads the saved leaderboard, if that fails it starts empty and the error is returned too.
+    pub fn load() -> (Self, Option<String>) {
+        let path = Leaderboard::path();
+        let (leaderboard, error) = match path.as_deref().map(Leaderboard::load_from).transpose() {
+            Ok(leaderboard) => (leaderboard.unwrap_or_default(), None),
+            Err(error) => (Leaderboard::default(), Some(error.to_string())),
+        };
+        (Self { leaderboard, path, place: None }, error)
+    }
+
+    /// Records the game with `record`, which returns its place, and saves the leaderboard.
+    pub fn record(&mut self, record: impl FnOnce(&mut Leaderboard) -> Option<usize>) -> Result<(), String> {
+        self.place = Some(record(&mut self.leaderboard));
+        match &self.path {
+            Some(path) => self.leaderboard.save_to(path).map_err(|error| error.to_string()),
+            None => Ok(()),
+        }
+    }
+
+    /// Lists the best of the results with the one of this game highlighted.
+    pub fn render_at(&self, canvas: &mut Canvas, position: Position, results: impl Iterator<Item=String>) {
+        let mut line = position;
+        canvas.add_themed_text("leaderboard", &line);
+        for (index, result) in results.take(SHOWN_RESULTS).enumerate() {
+            line.move_down();
+            let text = format!("{}. {}", index + 1, result);
+            if self.place == Some(Some(index)) {
+                canvas.add_highlighted_text(&text, &line);
+            } else {
+                canvas.add_themed_text(&text, &line);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::views::ranking::format_time;
+    use std::time::Duration;
+
+    #[test]
+    fn times_round_to_tenths() {
+        let time = |millis| format_time(Duration::from_millis(millis));
+        assert_eq!((time(0), time(5_040), time(59_940), time(59_960)), ("0:00.0".to_string(), "0:05.0".to_string(), "0:59.9".to_string(), "1:00.0".to_string()));
+        assert_eq!(time(754_321), "12:34.3");
+    }
+}
@@ -0,0 +1,46 @@
+use crate::config::settings::{Action, Key};
+use crate::rendering::renderer::{Canvas, Position};
+use crate::views::view::{Context, Transition, View};
+use crossterm::event::{Event, KeyCode};
+use std::time::Instant;
+
+pub struct KeyCaptureView {
+    action: Action,
+    message: Option<String>,
+}
+
+impl KeyCaptureView {
+    pub fn new(action: Action) -> Self {
+        Self { action, message: None }
+    }
+}
+
+impl View for KeyCaptureView {
+    fn render_at(&self, canvas: &mut Canvas, position: Position, context: &Context) {
+        canvas.add_highlighted_text(&format!("press a key to bind to {}", self.action.name()), &position);
+        canvas.add_themed_paragraph(&[
+            &format!("current keys: {}", context.config.keys.describe(self.action)),
+            "esc - cancel",
+        ], position.offset_by(&Position { x: 0, y: 2 }));
+
+        if let Some(message) = &self.message {
+            canvas.add_highlighted_text(message, &position.offset_by(&Position { x: 0, y: 5 }));
+        }
+    }
+
+    fn handle_input(&mut self, event: &Event, _now: Instant, context: &mut Context) -> Transition {
+        match event {
+            Event::Key(key_event) if key_event.code == KeyCode::Esc => Transition::Pop,
+            Event::Key(key_event) => {
+                match context.config.bind_key(self.action, Key::from_event(key_event)) {
+                    Ok(()) => Transition::Pop,
+                    Err(message) => {
+                        self.message = Some(message);
+                        Transition::None
+                    }
+                }
+            }
+            _ => Transition::None,
+        }
+    }
+}
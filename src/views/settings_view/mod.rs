@@ -0,0 +1,6 @@
+mod capture;
+mod view;
+
+pub mod settings_view {
+    pub use super::view::SettingsView;
+}
@@ -0,0 +1,215 @@
+use crate::bot::ai::Difficulty;
+use crate::config::settings::{Action, Config, MAX_FRAME_RATE, MAX_NEXT_QUEUE_LENGTH, MIN_FRAME_RATE};
+use crate::engine::mechanics::Mode;
+use crate::rendering::renderer::{Canvas, Position, RenderMode};
+use crate::views::settings_view::capture::KeyCaptureView;
+use crate::views::view::{Context, Transition, View};
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Setting {
+    Theme,
+    RenderMode,
+    Ghost,
+    NextQueueLength,
+    DefaultMode,
+    FrameRate,
+    CpuDifficulty,
+    DelayedAutoShift,
+    AutoRepeatRate,
+    SoftDropFactor,
+}
+
+impl Setting {
+    const ALL: [Setting; 10] = [
+        Setting::Theme,
+        Setting::RenderMode,
+        Setting::Ghost,
+        Setting::NextQueueLength,
+        Setting::DefaultMode,
+        Setting::FrameRate,
+        Setting::CpuDifficulty,
+        Setting::DelayedAutoShift,
+        Setting::AutoRepeatRate,
+        Setting::SoftDropFactor,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Setting::Theme => "theme",
+            Setting::RenderMode => "colors",
+            Setting::Ghost => "ghost piece",
+            Setting::NextQueueLength => "next pieces",
+            Setting::DefaultMode => "default mode",
+            Setting::FrameRate => "frame rate",
+            Setting::CpuDifficulty => "cpu difficulty",
+            Setting::DelayedAutoShift => "DAS (ms)",
+            Setting::AutoRepeatRate => "ARR (ms)",
+            Setting::SoftDropFactor => "SDF",
+        }
+    }
+
+    fn value(self, context: &Context) -> String {
+        let config = &context.config;
+        match self {
+            Setting::Theme => context.theme().name.clone(),
+            Setting::RenderMode => config.render_mode.name().to_string(),
+            Setting::Ghost => if config.ghost { "on" } else { "off" }.to_string(),
+            Setting::NextQueueLength => config.next_queue_length.to_string(),
+            Setting::DefaultMode => config.default_mode.name().to_string(),
+            Setting::FrameRate => config.frame_rate.to_string(),
+            Setting::CpuDifficulty => config.cpu_difficulty.name().to_string(),
+            Setting::DelayedAutoShift => config.handling.das.to_string(),
+            Setting::AutoRepeatRate => config.handling.arr.to_string(),
+            Setting::SoftDropFactor if config.handling.sdf == 0 => "instant".to_string(),
+            Setting::SoftDropFactor => config.handling.sdf.to_string(),
+        }
+    }
+
+    fn change(self, context: &mut Context, step: i32) {
+        let theme_index = context.theme_index();
+        let theme_count = context.themes.len();
+        let config = &mut context.config;
+
+        match self {
+            Setting::Theme => config.theme = context.themes[cycle(theme_index, theme_count, step)].name.clone(),
+            Setting::RenderMode => config.render_mode = RenderMode::ALL[cycle(index_of(&RenderMode::ALL, config.render_mode), RenderMode::ALL.len(), step)],
+            Setting::Ghost => config.ghost = !config.ghost,
+            Setting::NextQueueLength => config.next_queue_length = adjust(config.next_queue_length as i64, step as i64, 0, MAX_NEXT_QUEUE_LENGTH as i64) as usize,
+            Setting::DefaultMode => config.default_mode = Mode::ALL[cycle(index_of(&Mode::ALL, config.default_mode), Mode::ALL.len(), step)],
+            Setting::FrameRate => config.frame_rate = adjust(config.frame_rate as i64, 5 * step as i64, MIN_FRAME_RATE as i64, MAX_FRAME_RATE as i64) as u32,
+            Setting::CpuDifficulty => config.cpu_difficulty = Difficulty::ALL[cycle(index_of(&Difficulty::ALL, config.cpu_difficulty), Difficulty::ALL.len(), step)],
+            Setting::DelayedAutoShift => config.handling.das = adjust(config.handling.das as i64, 10 * step as i64, 0, 1000) as u64,
+            Setting::AutoRepeatRate => config.handling.arr = adjust(config.handling.arr as i64, 5 * step as i64, 0, 500) as u64,
+            Setting::SoftDropFactor => config.handling.sdf = adjust(config.handling.sdf as i64, step as i64, 0, 40) as u32,
+        }
+    }
+}
+
+fn index_of<T: PartialEq>(values: &[T], value: T) -> usize {
+    values.iter().position(|candidate| *candidate == value).unwrap_or(0)
+}
+
+fn cycle(index: usize, length: usize, step: i32) -> usize {
+    (index as i64 + step as i64).rem_euclid(length as i64) as usize
+}
+
+fn adjust(value: i64, step: i64, min: i64, max: i64) -> i64 {
+    (value + step).clamp(min, max)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    Setting(Setting),
+    Binding(Action),
+}
+
+impl Row {
+    fn all() -> Vec<Row> {
+        Setting::ALL.iter().map(|&setting| Row::Setting(setting))
+            .chain(Action::ALL.iter().map(|&action| Row::Binding(action)))
+            .collect()
+    }
+}
+
+pub struct SettingsView {
+    rows: Vec<Row>,
+    selected: usize,
+    config_before: Config,
+    message: Option<String>,
+}
+
+impl SettingsView {
+    pub fn new(context: &Context) -> Self {
+        Self {
+            rows: Row::all(),
+            selected: 0,
+            config_before: context.config.clone(),
+            message: None,
+        }
+    }
+
+    fn close(&mut self, context: &mut Context) -> Transition {
+        if context.config == self.config_before {
+            return Transition::Pop;
+        }
+
+        match context.config.save() {
+            Ok(()) => Transition::Pop,
+            Err(error) => {
+                self.config_before = context.config.clone();
+                self.message = Some(format!("{}, press esc again to continue without saving", error));
+                Transition::None
+            }
+        }
+    }
+
+    fn clear_binding(&mut self, action: Action, context: &mut Context) {
+        if action == Action::Pause {
+            self.message = Some("pause needs at least one key".to_string());
+            return;
+        }
+        context.config.keys.keys_mut(action).clear();
+    }
+
+    fn add_row(&self, canvas: &mut Canvas, index: usize, text: &str, line: &Position) {
+        if index == self.selected {
+            canvas.add_highlighted_text(text, line);
+        } else {
+            canvas.add_themed_text(text, line);
+        }
+    }
+}
+
+impl View for SettingsView {
+    fn render_at(&self, canvas: &mut Canvas, position: Position, context: &Context) {
+        canvas.add_highlighted_text("settings", &position);
+
+        let mut line = position.offset_by(&Position { x: 0, y: 2 });
+        for (index, row) in self.rows.iter().enumerate() {
+            let text = match row {
+                Row::Setting(setting) => format!("{:<14}< {} >", setting.label(), setting.value(context)),
+                Row::Binding(action) => format!("  {:<24}{}", action.name(), context.config.keys.describe(*action)),
+            };
+            if index == Setting::ALL.len() {
+                line.move_down();
+                canvas.add_themed_text("key bindings", &line);
+                line.move_down();
+            }
+            self.add_row(canvas, index, &text, &line);
+            line.move_down();
+        }
+
+        line.move_down();
+        canvas.add_themed_paragraph(&[
+            "up/down - select, left/right - change",
+            "enter - add key binding, backspace - remove key bindings",
+            "esc - save and close",
+        ], line);
+
+        if let Some(message) = &self.message {
+            canvas.add_highlighted_text(message, &line.offset_by(&Position { x: 0, y: 4 }));
+        }
+    }
+
+    fn handle_input(&mut self, event: &Event, _now: Instant, context: &mut Context) -> Transition {
+        let code = match event {
+            Event::Key(KeyEvent { code, modifiers: _ }) => *code,
+            _ => return Transition::None,
+        };
+
+        match (code, self.rows[self.selected]) {
+            (KeyCode::Up, _) => self.selected = cycle(self.selected, self.rows.len(), -1),
+            (KeyCode::Down, _) => self.selected = cycle(self.selected, self.rows.len(), 1),
+            (KeyCode::Left, Row::Setting(setting)) => setting.change(context, -1),
+            (KeyCode::Right, Row::Setting(setting)) | (KeyCode::Enter, Row::Setting(setting)) => setting.change(context, 1),
+            (KeyCode::Enter, Row::Binding(action)) => return Transition::Push(Box::new(KeyCaptureView::new(action))),
+            (KeyCode::Backspace, Row::Binding(action)) | (KeyCode::Delete, Row::Binding(action)) => self.clear_binding(action, context),
+            (KeyCode::Esc, _) => return self.close(context),
+            _ => {}
+        }
+
+        Transition::None
+    }
+}
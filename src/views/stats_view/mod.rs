@@ -0,0 +1,5 @@
+mod view;
+
+pub mod stats_view {
+    pub use super::view::StatsView;
+}
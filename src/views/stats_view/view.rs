@@ -0,0 +1,89 @@
+use crate::rendering::renderer::{Canvas, Position};
+use crate::stats::statistics::{sparkline, SessionRecord, StatsHistory};
+use crate::views::view::{Context, Transition, View};
+use crossterm::event::{Event, KeyCode};
+use std::time::Instant;
+
+const TREND_LENGTH: usize = 20;
+
+pub struct StatsView;
+
+impl StatsView {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn totals(history: &StatsHistory) -> Vec<String> {
+        let totals = &history.totals;
+        let seconds = totals.seconds as u64;
+        let per_second = |count: u64| if totals.seconds > 0.0 { count as f64 / totals.seconds } else { 0.0 };
+
+        vec![
+            format!("games played   {}", totals.games),
+            format!("time played    {}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60),
+            format!("best score     {}", totals.best_points),
+            format!("pieces         {}", totals.pieces),
+            format!("lines          {}", totals.lines),
+            format!("tetrises       {}", totals.tetrises),
+            format!("finesse faults {}", totals.finesse_faults),
+            format!("PPS            {:.2}", per_second(totals.pieces)),
+            format!("LPM            {:.1}", 60.0 * per_second(totals.lines)),
+            format!("APM            {:.1}", 60.0 * per_second(totals.attack)),
+            format!("KPP            {:.2}", if totals.pieces > 0 { totals.keys as f64 / totals.pieces as f64 } else { 0.0 }),
+        ]
+    }
+
+    fn trend(history: &StatsHistory, label: &str, value: fn(&SessionRecord) -> f64) -> String {
+        let values = history.trend(TREND_LENGTH, value);
+        let average = values.iter().sum::<f64>() / values.len().max(1) as f64;
+        let last = values.last().copied().unwrap_or(0.0);
+        format!("{:<8}{:<21}last {:>7.2}  avg {:>7.2}", label, sparkline(&values), last, average)
+    }
+}
+
+impl Default for StatsView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl View for StatsView {
+    fn render_at(&self, canvas: &mut Canvas, position: Position, context: &Context) {
+        canvas.add_highlighted_text("statistics", &position);
+        let content = position.offset_by(&Position { x: 0, y: 2 });
+
+        let history = match &context.stats {
+            Some(history) => history,
+            None => {
+                canvas.add_themed_text("the stats file couldn't be read, statistics aren't recorded", &content);
+                return;
+            }
+        };
+
+        let totals = Self::totals(history);
+        let totals: Vec<&str> = totals.iter().map(String::as_str).collect();
+        canvas.add_themed_paragraph(&totals, content);
+
+        let trends = [
+            format!("last {} games", history.trend(TREND_LENGTH, SessionRecord::pieces_per_second).len()),
+            Self::trend(history, "score", |session| session.points as f64),
+            Self::trend(history, "PPS", SessionRecord::pieces_per_second),
+            Self::trend(history, "LPM", SessionRecord::lines_per_minute),
+            Self::trend(history, "APM", SessionRecord::attack_per_minute),
+            Self::trend(history, "KPP", SessionRecord::keys_per_piece),
+            Self::trend(history, "faults", SessionRecord::faults_per_piece),
+        ];
+        let trends: Vec<&str> = trends.iter().map(String::as_str).collect();
+        let trends_position = content.offset_by(&Position { x: 0, y: totals.len() as i32 + 1 });
+        canvas.add_themed_paragraph(&trends, trends_position);
+
+        canvas.add_themed_text("esc/enter - back", &trends_position.offset_by(&Position { x: 0, y: trends.len() as i32 + 1 }));
+    }
+
+    fn handle_input(&mut self, event: &Event, _now: Instant, _context: &mut Context) -> Transition {
+        match event {
+            Event::Key(key_event) if key_event.code == KeyCode::Esc || key_event.code == KeyCode::Enter => Transition::Pop,
+            _ => Transition::None,
+        }
+    }
+}
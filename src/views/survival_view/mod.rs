@@ -0,0 +1,5 @@
+mod view;
+
+pub mod survival_view {
+    pub use super::view::SurvivalView;
+}
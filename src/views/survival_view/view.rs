@@ -0,0 +1,161 @@
+use crate::config::settings::{Action, Survival};
+use crate::engine::mechanics::{Mode, PieceGenerator};
+use crate::net::network::Snapshot;
+use crate::rendering::renderer::{Canvas, Position};
+use crate::stats::statistics::SurvivalRecord;
+use crate::views::clipboard::copy_fumen;
+use crate::views::pause_view::pause_view::PauseView;
+use crate::views::player::Player;
+use crate::views::ranking::{format_time, Ranking};
+use crate::views::view::{Context, Transition, View};
+use crossterm::event::Event;
+use std::time::{Duration, Instant};
+
+/// A marathon game where garbage rows rise from below on a clock of their own, faster the
+/// longer the game lasts as the tempo of the config says. Games that top out go on the
+/// leaderboard by the time survived.
+pub struct SurvivalView {
+    player: Player,
+    /// The time survived at which the next row rises, scheduled on the first update.
+    next_rise: Option<Duration>,
+    risen: u32,
+    ranking: Ranking,
+    message: Option<String>,
+}
+
+impl SurvivalView {
+    pub fn new(now: Instant) -> Self {
+        let player = Player::new(Mode::Marathon.layout(), PieceGenerator::random(), now);
+        let (ranking, message) = Ranking::load();
+        Self { player, next_rise: None, risen: 0, ranking, message }
+    }
+
+    /// Pushes in the rows that are due by the time survived.
+    fn rise(&mut self, tempo: &Survival) {
+        let survived = self.player.stats().elapsed;
+        let mut next_rise = self.next_rise.unwrap_or_else(|| tempo.rise_interval(Duration::from_secs(0)));
+        while !self.player.is_game_over() && survived >= next_rise {
+            self.player.insert_garbage(1);
+            self.risen += 1;
+            next_rise += tempo.rise_interval(next_rise);
+        }
+        self.next_rise = Some(next_rise);
+    }
+
+    fn finish(&mut self) {
+        let record = SurvivalRecord::new(self.player.stats());
+        if let Err(error) = self.ranking.record(|leaderboard| leaderboard.record_survival(record)) {
+            self.message = Some(error);
+        }
+    }
+}
+
+impl View for SurvivalView {
+    fn render_at(&self, canvas: &mut Canvas, position: Position, context: &Context) {
+        let dimensions = *self.player.field().dimensions();
+        let status = self.player.render_at(canvas, position, context);
+        let stats = self.player.stats();
+
+        let next_rise = self.next_rise.map_or(Duration::from_secs(0), |next_rise| next_rise.saturating_sub(stats.elapsed));
+        let lines = [
+            "survival".to_string(),
+            format!("time   {}", format_time(stats.elapsed)),
+            format!("risen  {} rows", self.risen),
+            format!("next   {:.1}s", next_rise.as_secs_f64()),
+        ];
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        canvas.add_themed_paragraph(&lines, status);
+
+        let result = status.offset_by(&Position { x: 0, y: 5 });
+        match self.ranking.place {
+            Some(Some(place)) => canvas.add_highlighted_text(&format!("topped out, place {} on the leaderboard", place + 1), &result),
+            Some(None) => canvas.add_highlighted_text("topped out", &result),
+            None => {}
+        }
+        if let Some(message) = &self.message {
+            canvas.add_highlighted_text(message, &result.offset_by(&Position { x: 0, y: 1 }));
+        }
+
+        let results = self.ranking.leaderboard.survival().iter()
+            .map(|record| format!("{}  {} lines", format_time(Duration::from_secs_f64(record.seconds)), record.lines));
+        self.ranking.render_at(canvas, result.offset_by(&Position { x: 0, y: 3 }), results);
+
+        let keys = &context.config.keys;
+        let help = [
+            "garbage rises faster and faster, survive as long as you can".to_string(),
+            format!("{} - restart, {} - copy as fumen, {} - pause",
+                    keys.describe(Action::Restart), keys.describe(Action::CopyFumen), keys.describe(Action::Pause)),
+        ];
+        let help: Vec<&str> = help.iter().map(String::as_str).collect();
+        canvas.add_themed_paragraph(&help, position.offset_by(&Position { x: 0, y: dimensions.height as i32 + 1 }));
+    }
+
+    fn handle_input(&mut self, event: &Event, now: Instant, context: &mut Context) -> Transition {
+        let action = match event {
+            Event::Key(key_event) => context.config.keys.action_for(key_event),
+            _ => None,
+        };
+
+        match action {
+            Some(Action::Quit) => return Transition::Quit,
+            Some(Action::Pause) => return Transition::Push(Box::new(PauseView::new(Box::new(|now| Box::new(SurvivalView::new(now)))))),
+            Some(Action::Restart) => return Transition::Replace(Box::new(SurvivalView::new(now))),
+            Some(Action::CopyFumen) => self.message = Some(copy_fumen(self.player.fumen_page())),
+            Some(action) => {
+                self.player.perform(action, now, &context.config.handling);
+            }
+            None => {}
+        }
+        Transition::None
+    }
+
+    fn update(&mut self, now: Instant, context: &mut Context) -> Transition {
+        self.player.update(now, &context.config.handling);
+        self.rise(&context.config.survival);
+        if self.player.is_game_over() && self.ranking.place.is_none() {
+            self.finish();
+        }
+        Transition::None
+    }
+
+    fn on_resume(&mut self, now: Instant, _context: &Context) {
+        self.player.resume(now);
+    }
+
+    fn snapshot(&self) -> Option<Snapshot> {
+        Some(self.player.snapshot())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::settings::{Handling, Survival};
+    use crate::views::ranking::Ranking;
+    use crate::views::survival_view::survival_view::SurvivalView;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn garbage_rises_with_the_tempo() {
+        let tempo = Survival { tempo: vec![(0, 1000), (3, 500)] };
+        let now = Instant::now();
+        let mut view = SurvivalView::new(now);
+        view.ranking = Ranking::default();
+
+        view.rise(&tempo);
+        assert_eq!((view.risen, view.next_rise), (0, Some(Duration::from_secs(1))));
+        view.player.update(now + Duration::from_millis(4200), &Handling::default());
+        view.rise(&tempo);
+        // at 1, 2 and 3 seconds and then every half second
+        assert_eq!(view.risen, 5);
+        assert_eq!(view.player.field().garbage_rows(), 5);
+        assert_eq!(view.next_rise, Some(Duration::from_millis(4500)));
+
+        for second in 5..60 {
+            view.player.update(now + Duration::from_secs(second), &Handling::default());
+            view.rise(&tempo);
+        }
+        assert!(view.player.is_game_over());
+        view.finish();
+        assert_eq!(view.ranking.place, Some(Some(0)));
+    }
+}
@@ -0,0 +1,5 @@
+mod view;
+
+pub mod trainer_view {
+    pub use super::view::TrainerView;
+}
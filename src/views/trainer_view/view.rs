@@ -0,0 +1,246 @@
+use crate::config::settings::Action;
+use crate::engine::mechanics::{describe, landing_cells, placements, Field, FinesseTracker, Input, Mode, NextQueue, PieceGenerator, Stone};
+use crate::rendering::renderer::{Canvas, Position};
+use crate::views::handling::{AutoShift, Shift};
+use crate::views::pause_view::pause_view::PauseView;
+use crate::views::view::{Context, Transition, View};
+use crossterm::event::Event;
+use rand::seq::SliceRandom;
+use std::time::Instant;
+
+/// Presents random placements as targets. A target only counts once it's reached with the fewest
+/// possible inputs, otherwise the piece is reset and the shortest sequence is shown.
+pub struct TrainerView {
+    field: Field,
+    next_queue: NextQueue,
+    start: Stone,
+    current_stone: Stone,
+    target: Stone,
+    optimal: Vec<Input>,
+    finesse: FinesseTracker,
+    auto_shift: AutoShift,
+    attempts: u32,
+    successes: u32,
+    streak: u32,
+    best_streak: u32,
+    feedback: Option<String>,
+}
+
+impl TrainerView {
+    pub fn new(generator: PieceGenerator) -> Self {
+        let field = Field::new(Mode::Marathon.layout());
+        let mut next_queue = NextQueue::new(generator, 1);
+        let kind = next_queue.pop();
+        let start = Stone::new(field.spawn_position(kind), kind);
+
+        let mut view = Self {
+            field,
+            next_queue,
+            start,
+            current_stone: start,
+            target: start,
+            optimal: Vec::new(),
+            finesse: FinesseTracker::new(start),
+            auto_shift: AutoShift::default(),
+            attempts: 0,
+            successes: 0,
+            streak: 0,
+            best_streak: 0,
+            feedback: None,
+        };
+        view.choose_target();
+        view
+    }
+
+    fn choose_target(&mut self) {
+        let mut candidates = placements(&self.field, &self.start);
+        candidates.shuffle(&mut rand::thread_rng());
+        let (target, optimal) = candidates.pop().expect("a piece on an empty field can always be placed");
+        self.target = target;
+        self.optimal = optimal;
+        self.reset_piece();
+    }
+
+    fn next_piece(&mut self) {
+        let kind = self.next_queue.pop();
+        self.start = Stone::new(self.field.spawn_position(kind), kind);
+        self.choose_target();
+    }
+
+    fn reset_piece(&mut self) {
+        self.current_stone = self.start;
+        self.finesse.reset(self.start);
+        self.auto_shift.release();
+    }
+
+    fn judge(&mut self) {
+        self.attempts += 1;
+        let placed = landing_cells(&self.current_stone, &self.field);
+        let target = landing_cells(&self.target, &self.field);
+
+        let failure = if placed != target {
+            Some("wrong placement, try again".to_string())
+        } else if self.finesse.inputs() as usize > self.optimal.len() {
+            Some(format!("{} inputs instead of {}: {}", self.finesse.inputs(), self.optimal.len(), describe(&self.optimal)))
+        } else {
+            None
+        };
+
+        match failure {
+            Some(feedback) => {
+                self.streak = 0;
+                self.feedback = Some(feedback);
+                self.reset_piece();
+            }
+            None => {
+                self.successes += 1;
+                self.streak += 1;
+                self.best_streak = self.best_streak.max(self.streak);
+                self.feedback = None;
+                self.next_piece();
+            }
+        }
+    }
+
+    fn shift(&mut self, direction: Shift, times: usize) {
+        for _ in 0..times {
+            let moved = match direction {
+                Shift::Left => self.current_stone.move_left(&self.field),
+                Shift::Right => self.current_stone.move_right(&self.field),
+            };
+            if !moved {
+                break;
+            }
+        }
+    }
+}
+
+impl View for TrainerView {
+    fn render_at(&self, canvas: &mut Canvas, position: Position, context: &Context) {
+        let dimensions = *self.field.dimensions();
+        let field_position = position.offset_by(&Position { x: 1, y: 0 });
+        let stone_offset = field_position.offset_by(&self.field.visible_offset());
+        let sidebar = position.offset_by(&Position { x: dimensions.width as i32 + 3, y: 0 });
+
+        canvas.add_well(&position, &dimensions);
+        self.field.render_at(canvas, field_position);
+        let target_tile = canvas.theme().target_tile(self.target.kind.color(canvas.theme()));
+        canvas.add_texture(self.target.texture(target_tile), &self.target.position.offset_by(&stone_offset));
+        self.current_stone.render_at(canvas, &stone_offset);
+
+        canvas.add_highlighted_text("finesse trainer", &sidebar);
+        canvas.add_themed_text("next", &sidebar.offset_by(&Position { x: 0, y: 2 }));
+        let next_kind = self.next_queue.peek();
+        Stone::new(Position { x: 1, y: 3 - next_kind.shape(0).min_y }, next_kind).render_at(canvas, &sidebar);
+
+        let accuracy = (100 * self.successes).checked_div(self.attempts).unwrap_or(100);
+        let lines = [
+            format!("placed    {}", self.successes),
+            format!("accuracy  {}%", accuracy),
+            format!("streak    {}", self.streak),
+            format!("best      {}", self.best_streak),
+            format!("inputs    {}/{}", self.finesse.inputs(), self.optimal.len()),
+        ];
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        canvas.add_themed_paragraph(&lines, sidebar.offset_by(&Position { x: 0, y: 6 }));
+
+        if let Some(feedback) = &self.feedback {
+            canvas.add_highlighted_text(feedback, &sidebar.offset_by(&Position { x: 0, y: 12 }));
+        }
+
+        let keys = &context.config.keys;
+        let help = [
+            "place the piece on the outlined target with as few inputs as possible".to_string(),
+            format!("{} - skip piece, {} - pause", keys.describe(Action::Hold), keys.describe(Action::Pause)),
+        ];
+        let help: Vec<&str> = help.iter().map(String::as_str).collect();
+        canvas.add_themed_paragraph(&help, position.offset_by(&Position { x: 0, y: dimensions.height as i32 + 1 }));
+    }
+
+    fn handle_input(&mut self, event: &Event, now: Instant, context: &mut Context) -> Transition {
+        let action = match event {
+            Event::Key(key_event) => context.config.keys.action_for(key_event),
+            _ => None,
+        };
+        let handling = &context.config.handling;
+
+        let counts_as_input = match action {
+            Some(Action::Quit) => return Transition::Quit,
+            Some(Action::Pause) => return Transition::Push(Box::new(PauseView::new(Box::new(|_| Box::new(TrainerView::new(PieceGenerator::random())))))),
+            Some(Action::Restart) => return Transition::Replace(Box::new(TrainerView::new(PieceGenerator::random()))),
+            Some(Action::MoveLeft) | Some(Action::MoveRight) => {
+                let direction = if action == Some(Action::MoveLeft) { Shift::Left } else { Shift::Right };
+                let pressed = self.auto_shift.press(direction, now, handling);
+                if pressed {
+                    self.shift(direction, 1);
+                }
+                pressed
+            }
+            Some(Action::RotateCW) => {
+                self.current_stone.rotate(&self.field);
+                true
+            }
+            Some(Action::RotateCCW) => {
+                self.current_stone.rotate_counterclockwise(&self.field);
+                true
+            }
+            Some(Action::Rotate180) => {
+                self.current_stone.rotate_180(&self.field);
+                true
+            }
+            Some(Action::HardDrop) => {
+                self.judge();
+                false
+            }
+            Some(Action::Hold) => {
+                self.feedback = None;
+                self.next_piece();
+                false
+            }
+            Some(Action::SoftDrop) | Some(Action::CopyFumen) | None => false,
+        };
+
+        if counts_as_input {
+            self.finesse.record_input();
+        }
+        Transition::None
+    }
+
+    fn update(&mut self, now: Instant, context: &mut Context) -> Transition {
+        if let Some((direction, times)) = self.auto_shift.update(now, &context.config.handling) {
+            self.shift(direction, times);
+        }
+        Transition::None
+    }
+
+    fn on_resume(&mut self, _now: Instant, _context: &Context) {
+        self.auto_shift.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::mechanics::PieceGenerator;
+    use crate::views::trainer_view::trainer_view::TrainerView;
+
+    #[test]
+    fn only_optimal_placements_advance() {
+        let mut trainer = TrainerView::new(PieceGenerator::new(1));
+        let target = trainer.target;
+        let optimal = trainer.optimal.clone();
+
+        trainer.current_stone = target;
+        trainer.current_stone.position.y = trainer.start.position.y;
+        (0..optimal.len() + 1).for_each(|_| trainer.finesse.record_input());
+        trainer.judge();
+        assert_eq!(trainer.streak, 0);
+        assert_eq!(trainer.current_stone, trainer.start);
+
+        for input in optimal.iter() {
+            input.apply(&mut trainer.current_stone, &trainer.field);
+            trainer.finesse.record_input();
+        }
+        trainer.judge();
+        assert_eq!((trainer.successes, trainer.streak, trainer.attempts), (1, 1, 2));
+    }
+}
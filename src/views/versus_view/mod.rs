@@ -0,0 +1,5 @@
+mod view;
+
+pub mod versus_view {
+    pub use super::view::{Opponent, VersusView};
+}
@@ -0,0 +1,210 @@
+use crate::bot::ai::{Bot, BotChoice, BotDriver, Difficulty, Weights, DEFAULT_ACTION_DELAY};
+use crate::config::settings::Action;
+use crate::engine::mechanics::{Mode, PieceGenerator};
+use crate::net::network::Snapshot;
+use crate::rendering::renderer::{Canvas, Position};
+use crate::views::pause_view::pause_view::PauseView;
+use crate::views::player::Player;
+use crate::views::view::{Context, Transition, View};
+use crossterm::event::Event;
+use std::time::Instant;
+
+/// Width of the sidebar next to each well.
+const SIDEBAR_WIDTH: i32 = 16;
+
+/// Who plays the field on the right.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Opponent {
+    Cpu(Difficulty, Weights),
+    /// An external bot speaking the Tetris Bot Protocol, started from this command.
+    External(String),
+    /// A second person on the same keyboard, both use the split screen keys.
+    Local,
+}
+
+/// Two fields side by side that get the same pieces, every attack that isn't used to cancel
+/// incoming garbage is sent to the other side.
+pub struct VersusView {
+    opponent: Opponent,
+    left: Player,
+    right: Player,
+}
+
+impl VersusView {
+    pub fn new(opponent: Opponent, now: Instant) -> Self {
+        let generator = PieceGenerator::random();
+        let layout = Mode::Marathon.layout();
+        let right = Player::new(layout, PieceGenerator::new(generator.seed()), now);
+        let right = match &opponent {
+            Opponent::Cpu(difficulty, weights) => right.with_bot(BotDriver::with_difficulty(Bot::new(weights.clone()), *difficulty, now)),
+            Opponent::External(command) => right.with_bot(BotChoice::External(command.clone()).driver(DEFAULT_ACTION_DELAY, now)),
+            Opponent::Local => right,
+        };
+
+        Self {
+            opponent,
+            left: Player::new(layout, generator, now),
+            right,
+        }
+    }
+
+    pub fn against_cpu(difficulty: Difficulty, weights: Weights, now: Instant) -> Self {
+        Self::new(Opponent::Cpu(difficulty, weights), now)
+    }
+
+    pub fn local(now: Instant) -> Self {
+        Self::new(Opponent::Local, now)
+    }
+
+    fn exchange_garbage(&mut self) {
+        self.right.receive_garbage(self.left.take_attack());
+        self.left.receive_garbage(self.right.take_attack());
+    }
+
+    fn is_over(&self) -> bool {
+        self.left.is_game_over() || self.right.is_game_over()
+    }
+
+    fn names(&self) -> (String, String) {
+        match &self.opponent {
+            Opponent::Cpu(difficulty, _) => ("you".to_string(), format!("cpu ({})", difficulty.name())),
+            Opponent::External(_) => {
+                let name = self.right.bot().and_then(BotDriver::external_name).unwrap_or("cpu");
+                ("you".to_string(), name.to_string())
+            }
+            Opponent::Local => ("player 1".to_string(), "player 2".to_string()),
+        }
+    }
+
+    /// The game keys are checked first, the piece keys go to the player they're bound for. Against
+    /// the CPU the normal bindings move the left field.
+    fn route(&mut self, event: &Event, now: Instant, context: &Context) -> Option<Action> {
+        let key_event = match event {
+            Event::Key(key_event) => key_event,
+            _ => return None,
+        };
+        let keys = &context.config.keys;
+        let handling = &context.config.handling;
+
+        let action = keys.action_for(key_event);
+        if action.is_some_and(|action| !action.is_piece_action()) {
+            return action;
+        }
+        if self.is_over() {
+            return None;
+        }
+
+        match self.opponent {
+            Opponent::Cpu(..) | Opponent::External(_) => {
+                if let Some(action) = action {
+                    self.left.perform(action, now, handling);
+                }
+            }
+            Opponent::Local => {
+                let two_player = &context.config.two_player;
+                if let Some(action) = two_player.left.action_for(key_event) {
+                    self.left.perform(action, now, handling);
+                } else if let Some(action) = two_player.right.action_for(key_event) {
+                    self.right.perform(action, now, handling);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl View for VersusView {
+    fn render_at(&self, canvas: &mut Canvas, position: Position, context: &Context) {
+        let dimensions = *self.left.field().dimensions();
+        let right_position = position.offset_by(&Position { x: dimensions.width as i32 + 3 + SIDEBAR_WIDTH, y: 0 });
+        let left_status = self.left.render_at(canvas, position, context);
+        let right_status = self.right.render_at(canvas, right_position, context);
+
+        let (left_name, right_name) = self.names();
+        canvas.add_themed_text(&left_name, &left_status);
+        canvas.add_themed_text(&right_name, &right_status);
+        if self.is_over() {
+            let (winner, loser) = if self.left.is_game_over() { (right_status, left_status) } else { (left_status, right_status) };
+            canvas.add_highlighted_text("winner", &winner.offset_by(&Position { x: 0, y: 1 }));
+            canvas.add_themed_text("game over", &loser.offset_by(&Position { x: 0, y: 1 }));
+        }
+        if let Some(error) = self.right.bot().and_then(BotDriver::error) {
+            canvas.add_highlighted_text(&format!("{}, the built-in bot took over", error), &right_status.offset_by(&Position { x: 0, y: 2 }));
+        }
+
+        let keys = &context.config.keys;
+        let help_position = position.offset_by(&Position { x: 0, y: dimensions.height as i32 + 1 });
+        let help = format!("{} - restart, {} - pause", keys.describe(Action::Restart), keys.describe(Action::Pause));
+        canvas.add_themed_text(&help, &help_position);
+        if self.opponent == Opponent::Local {
+            let two_player = &context.config.two_player;
+            for (keys, offset) in [(&two_player.left, 0), (&two_player.right, right_position.x - position.x)].iter() {
+                let help = keys.help();
+                let help: Vec<&str> = help.iter().map(String::as_str).collect();
+                canvas.add_themed_paragraph(help.as_slice(), help_position.offset_by(&Position { x: *offset, y: 1 }));
+            }
+        }
+    }
+
+    fn handle_input(&mut self, event: &Event, now: Instant, context: &mut Context) -> Transition {
+        match self.route(event, now, context) {
+            Some(Action::Quit) => Transition::Quit,
+            Some(Action::Pause) => {
+                let opponent = self.opponent.clone();
+                Transition::Push(Box::new(PauseView::new(Box::new(move |now| Box::new(VersusView::new(opponent.clone(), now))))))
+            }
+            Some(Action::Restart) => Transition::Replace(Box::new(VersusView::new(self.opponent.clone(), now))),
+            _ => Transition::None,
+        }
+    }
+
+    fn update(&mut self, now: Instant, context: &mut Context) -> Transition {
+        if self.is_over() {
+            return Transition::None;
+        }
+
+        let handling = &context.config.handling;
+        self.left.update(now, handling);
+        self.right.update(now, handling);
+        self.exchange_garbage();
+        if self.is_over() {
+            self.left.stop();
+            self.right.stop();
+        }
+        Transition::None
+    }
+
+    fn on_resume(&mut self, now: Instant, _context: &Context) {
+        self.left.resume(now);
+        self.right.resume(now);
+    }
+
+    fn snapshot(&self) -> Option<Snapshot> {
+        Some(self.left.snapshot())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::settings::Config;
+    use crate::views::versus_view::versus_view::VersusView;
+    use crate::views::view::Context;
+    use crossterm::event::{Event, KeyCode, KeyEvent};
+    use std::time::Instant;
+
+    #[test]
+    fn keys_go_to_their_player() {
+        let now = Instant::now();
+        let context = Context::new(Config::default(), Vec::new(), None);
+        let mut versus = VersusView::local(now);
+
+        versus.route(&Event::Key(KeyEvent::from(KeyCode::Char('w'))), now, &context);
+        assert_eq!(versus.left.stats().pieces, 1);
+        assert_eq!(versus.right.stats().pieces, 0);
+
+        versus.route(&Event::Key(KeyEvent::from(KeyCode::Up)), now, &context);
+        versus.route(&Event::Key(KeyEvent::from(KeyCode::Up)), now, &context);
+        assert_eq!(versus.left.stats().pieces, 1);
+        assert_eq!(versus.right.stats().pieces, 2);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::config::settings::Config;
+use crate::net::network::Snapshot;
+use crate::rendering::renderer::{Canvas, Position, Theme};
+use crate::stats::statistics::StatsHistory;
+use crossterm::event::Event;
+use std::time::Instant;
+
+pub struct Context {
+    pub config: Config,
+    pub themes: Vec<Theme>,
+    /// `None` if the stats file couldn't be read, it's not overwritten then.
+    pub stats: Option<StatsHistory>,
+}
+
+impl Context {
+    pub fn new(config: Config, themes: Vec<Theme>, stats: Option<StatsHistory>) -> Self {
+        Self { config, themes, stats }
+    }
+
+    pub fn theme_index(&self) -> usize {
+        self.themes.iter()
+            .position(|theme| theme.name == self.config.theme)
+            .unwrap_or(0)
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.themes[self.theme_index()]
+    }
+}
+
+/// Creates a fresh instance of a view, e.g. to restart a game.
+pub type ViewFactory = Box<dyn Fn(Instant) -> Box<dyn View> + Send>;
+
+pub enum Transition {
+    None,
+    Push(Box<dyn View>),
+    Pop,
+    Replace(Box<dyn View>),
+    Reset(Box<dyn View>),
+    Quit,
+}
+
+pub trait View: Send {
+    fn render_at(&self, canvas: &mut Canvas, position: Position, context: &Context);
+
+    fn handle_input(&mut self, event: &Event, now: Instant, context: &mut Context) -> Transition;
+
+    fn update(&mut self, _now: Instant, _context: &mut Context) -> Transition {
+        Transition::None
+    }
+
+    fn on_resume(&mut self, _now: Instant, _context: &Context) {}
+
+    fn on_exit(&mut self, _context: &mut Context) {}
+
+    /// The game spectators see while this view is open, `None` for views without one.
+    fn snapshot(&self) -> Option<Snapshot> {
+        None
+    }
+}
@@ -0,0 +1,5 @@
+mod view;
+
+pub mod watch_view {
+    pub use super::view::WatchView;
+}
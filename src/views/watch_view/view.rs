@@ -0,0 +1,62 @@
+use crate::config::settings::Action;
+use crate::net::network::{Spectator, StreamAddress};
+use crate::rendering::renderer::{Canvas, Position};
+use crate::views::player::render_snapshot;
+use crate::views::view::{Context, Transition, View};
+use crossterm::event::Event;
+use std::time::Instant;
+
+/// Shows the game another instance streams, read-only.
+pub struct WatchView {
+    address: StreamAddress,
+    spectator: Spectator,
+}
+
+impl WatchView {
+    pub fn new(address: StreamAddress, spectator: Spectator) -> Self {
+        Self { address, spectator }
+    }
+}
+
+impl View for WatchView {
+    fn render_at(&self, canvas: &mut Canvas, position: Position, context: &Context) {
+        let status = match self.spectator.snapshot() {
+            Some(snapshot) => {
+                let status = render_snapshot(canvas, snapshot, position, context);
+                if snapshot.game_over {
+                    canvas.add_highlighted_text("game over", &status);
+                }
+                canvas.add_themed_text(&format!("lines: {}", snapshot.lines), &status.offset_by(&Position { x: 0, y: 1 }));
+                let height = snapshot.rows.len() as i32;
+                position.offset_by(&Position { x: 0, y: height + 1 })
+            }
+            None => {
+                canvas.add_themed_text("waiting for a game", &position);
+                position.offset_by(&Position { x: 0, y: 2 })
+            }
+        };
+
+        let help = format!("watching {}, {} - quit", self.address, context.config.keys.describe(Action::Quit));
+        canvas.add_themed_text(&help, &status);
+        if let Some(error) = self.spectator.error() {
+            canvas.add_highlighted_text(&error.to_string(), &status.offset_by(&Position { x: 0, y: 1 }));
+        }
+    }
+
+    fn handle_input(&mut self, event: &Event, _now: Instant, context: &mut Context) -> Transition {
+        let action = match event {
+            Event::Key(key_event) => context.config.keys.action_for(key_event),
+            _ => None,
+        };
+
+        match action {
+            Some(Action::Quit) | Some(Action::Pause) => Transition::Quit,
+            _ => Transition::None,
+        }
+    }
+
+    fn update(&mut self, _now: Instant, _context: &mut Context) -> Transition {
+        self.spectator.update();
+        Transition::None
+    }
+}
@@ -0,0 +1,25 @@
+use tet_rs::bot::ai::{BotChoice, BotDriver, DEFAULT_ACTION_DELAY};
+use tet_rs::config::settings::Config;
+use tet_rs::engine::mechanics::Mode;
+use tet_rs::views::views::{Context, PlayView, View};
+use std::time::{Duration, Instant};
+
+#[test]
+fn the_reference_bot_plays_a_game() {
+    let start = Instant::now();
+    let mut context = Context::new(Config::default(), Vec::new(), None);
+    let bot = BotChoice::External(env!("CARGO_BIN_EXE_tbp-reference-bot").to_string());
+    let mut view = PlayView::watching(Mode::Marathon, bot, start);
+
+    for frame in 1..5000 {
+        view.update(start + DEFAULT_ACTION_DELAY * frame, &mut context);
+        if view.stats().pieces >= 20 {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    assert_eq!(view.bot().and_then(BotDriver::error), None);
+    assert!(view.stats().pieces >= 20);
+    assert!(view.bot().and_then(BotDriver::external_name).is_some_and(|name| name.starts_with("tet-rs reference bot")));
+}